@@ -0,0 +1,76 @@
+-- Payments keep the full request and the decision records as JSON so a
+-- payment can be rebuilt exactly; parties and transactions are extracted
+-- into their own tables for reporting and lookups.
+CREATE TABLE payments (
+    id                UUID PRIMARY KEY,
+    uetr              UUID NOT NULL UNIQUE,
+    message_type      TEXT NOT NULL,
+    payment_type      TEXT NOT NULL,
+    sender_id         TEXT NOT NULL,
+    request_id        TEXT NOT NULL,
+    channel           TEXT,
+    submitted_by      TEXT,
+    status            TEXT NOT NULL,
+    message_payload   JSONB NOT NULL,
+    transliterations  JSONB NOT NULL DEFAULT '[]',
+    flags             JSONB NOT NULL DEFAULT '[]',
+    screening_hits    JSONB NOT NULL DEFAULT '[]',
+    hold_decision     JSONB,
+    fraud_assessment  JSONB,
+    approval          JSONB,
+    approvals         JSONB NOT NULL DEFAULT '[]',
+    -- Hash of the request as submitted. A request id that comes back with
+    -- the same fingerprint is a resend and gets the stored outcome; any
+    -- other payload under that request id is rejected.
+    fingerprint       TEXT,
+    created_at        TIMESTAMPTZ NOT NULL,
+    updated_at        TIMESTAMPTZ NOT NULL,
+    -- A sender's request id names exactly one payment, so a retried or
+    -- redelivered request can never be stored twice.
+    CONSTRAINT payments_sender_request_key UNIQUE (sender_id, request_id)
+);
+
+CREATE INDEX payments_status_idx ON payments (status, created_at);
+
+CREATE TABLE payment_parties (
+    payment_id  UUID NOT NULL REFERENCES payments (id) ON DELETE CASCADE,
+    role        TEXT NOT NULL,
+    name        TEXT,
+    account     TEXT,
+    agent       TEXT,
+    PRIMARY KEY (payment_id, role)
+);
+
+CREATE INDEX payment_parties_account_idx ON payment_parties (account);
+
+-- Every transaction carries its own UETR; the payment is also known by the
+-- first one, in payments.uetr. Amounts are decimal in ISO 20022 and must not
+-- pick up binary rounding.
+CREATE TABLE payment_transactions (
+    payment_id              UUID NOT NULL REFERENCES payments (id) ON DELETE CASCADE,
+    sequence                INTEGER NOT NULL,
+    uetr                    UUID,
+    end_to_end_id           TEXT,
+    amount                  NUMERIC,
+    currency                TEXT,
+    creditor_name           TEXT,
+    creditor_account        TEXT,
+    remittance_information  TEXT,
+    PRIMARY KEY (payment_id, sequence)
+);
+
+CREATE INDEX payment_transactions_end_to_end_idx ON payment_transactions (end_to_end_id);
+CREATE UNIQUE INDEX payment_transactions_uetr_key ON payment_transactions (uetr);
+
+-- Every status change records where it came from, who made it and why.
+CREATE TABLE payment_status_history (
+    id           BIGSERIAL PRIMARY KEY,
+    payment_id   UUID NOT NULL REFERENCES payments (id) ON DELETE CASCADE,
+    from_status  TEXT,
+    status       TEXT NOT NULL,
+    actor        TEXT NOT NULL,
+    reason       TEXT,
+    changed_at   TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX payment_status_history_payment_idx ON payment_status_history (payment_id, id);
@@ -0,0 +1,23 @@
+-- Append-only log of everything that happened to a payment. There is no
+-- foreign key to payments: events are the record, not a projection of it.
+CREATE TABLE payment_events (
+    id           UUID PRIMARY KEY,
+    payment_id   UUID NOT NULL,
+    sequence     BIGINT NOT NULL,
+    event_type   TEXT NOT NULL,
+    data         JSONB NOT NULL,
+    occurred_at  TIMESTAMPTZ NOT NULL,
+    UNIQUE (payment_id, sequence)
+);
+
+CREATE INDEX payment_events_type_idx ON payment_events (event_type, occurred_at);
+
+CREATE FUNCTION payment_events_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'payment_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER payment_events_append_only
+    BEFORE UPDATE OR DELETE ON payment_events
+    FOR EACH ROW EXECUTE FUNCTION payment_events_append_only();
@@ -0,0 +1,17 @@
+-- Messages written in the same transaction as the payment change they
+-- announce, drained to the broker by the outbox relay. available_at doubles
+-- as the claim lease and the retry time after a failed publish.
+CREATE TABLE payment_outbox (
+    id            BIGSERIAL PRIMARY KEY,
+    payment_id    UUID NOT NULL,
+    uetr          UUID NOT NULL,
+    routing_key   TEXT NOT NULL,
+    payload       JSONB NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL,
+    available_at  TIMESTAMPTZ NOT NULL,
+    attempts      INTEGER NOT NULL DEFAULT 0,
+    last_error    TEXT,
+    published_at  TIMESTAMPTZ
+);
+
+CREATE INDEX payment_outbox_pending_idx ON payment_outbox (payment_id, id) WHERE published_at IS NULL;
@@ -0,0 +1,40 @@
+-- Messages collected from the dead-letter queues for operators to inspect,
+-- resubmit or discard, and the audit trail of what they did.
+CREATE TABLE dead_letters (
+    id                UUID PRIMARY KEY,
+    queue             TEXT NOT NULL,
+    message_id        TEXT,
+    message_type      TEXT,
+    correlation_id    TEXT,
+    reply_to          TEXT,
+    failure_class     TEXT,
+    failure_reason    TEXT,
+    attempts          INTEGER NOT NULL,
+    payload           TEXT NOT NULL,
+    dead_lettered_at  TIMESTAMPTZ NOT NULL,
+    status            TEXT NOT NULL
+);
+
+CREATE INDEX dead_letters_status_idx ON dead_letters (status, queue, dead_lettered_at);
+
+CREATE TABLE dead_letter_actions (
+    id               UUID PRIMARY KEY,
+    dead_letter_id   UUID NOT NULL REFERENCES dead_letters (id),
+    action           TEXT NOT NULL,
+    actor            TEXT NOT NULL,
+    reason           TEXT,
+    edited_payload   TEXT,
+    at               TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX dead_letter_actions_letter_idx ON dead_letter_actions (dead_letter_id, at);
+
+CREATE FUNCTION dead_letter_actions_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'dead_letter_actions is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER dead_letter_actions_append_only
+    BEFORE UPDATE OR DELETE ON dead_letter_actions
+    FOR EACH ROW EXECUTE FUNCTION dead_letter_actions_append_only();
@@ -0,0 +1,75 @@
+-- State the service used to keep in memory, so it survives restarts and is
+-- shared by every instance.
+
+-- Idempotency-Key requests. locked_until is the lease of the attempt in
+-- flight; once it lapses without a stored response the key may be retried.
+CREATE TABLE idempotency_keys (
+    scope         TEXT NOT NULL,
+    sender_id     TEXT NOT NULL,
+    key           TEXT NOT NULL,
+    fingerprint   TEXT NOT NULL,
+    status_code   INTEGER,
+    response      JSONB,
+    locked_until  TIMESTAMPTZ,
+    created_at    TIMESTAMPTZ NOT NULL,
+    expires_at    TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (scope, sender_id, key)
+);
+
+CREATE INDEX idempotency_keys_expiry_idx ON idempotency_keys (expires_at);
+
+CREATE TABLE account_entitlements (
+    sender_id      TEXT NOT NULL,
+    account        TEXT NOT NULL,
+    payment_types  JSONB NOT NULL DEFAULT '[]',
+    granted_by     TEXT NOT NULL,
+    updated_at     TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (sender_id, account)
+);
+
+-- One counter per limit rule, subject and period.
+CREATE TABLE limit_usage (
+    rule          TEXT NOT NULL,
+    subject       TEXT NOT NULL,
+    period        TEXT NOT NULL,
+    period_start  DATE NOT NULL,
+    amount        NUMERIC NOT NULL DEFAULT 0,
+    count         BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (rule, subject, period, period_start)
+);
+
+CREATE TABLE duplicate_fingerprints (
+    fingerprint  TEXT NOT NULL,
+    payment_id   UUID NOT NULL,
+    seen_at      TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (fingerprint, payment_id)
+);
+
+CREATE INDEX duplicate_fingerprints_seen_idx ON duplicate_fingerprints (fingerprint, seen_at);
+CREATE INDEX duplicate_fingerprints_expiry_idx ON duplicate_fingerprints (seen_at);
+
+CREATE TABLE monitoring_alerts (
+    id            UUID PRIMARY KEY,
+    rule          TEXT NOT NULL,
+    party         TEXT NOT NULL,
+    reason        TEXT NOT NULL,
+    transactions  JSONB NOT NULL,
+    raised_at     TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX monitoring_alerts_party_rule_idx ON monitoring_alerts (party, rule, raised_at);
+CREATE INDEX monitoring_alerts_raised_idx ON monitoring_alerts (raised_at);
+
+-- Sender history the fraud rules score against. The total only feeds a
+-- heuristic average, so it is kept as a float.
+CREATE TABLE fraud_sender_profiles (
+    sender_id     TEXT PRIMARY KEY,
+    payments      BIGINT NOT NULL DEFAULT 0,
+    total_amount  DOUBLE PRECISION NOT NULL DEFAULT 0
+);
+
+CREATE TABLE fraud_sender_beneficiaries (
+    sender_id    TEXT NOT NULL,
+    beneficiary  TEXT NOT NULL,
+    PRIMARY KEY (sender_id, beneficiary)
+);
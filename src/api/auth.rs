@@ -0,0 +1,265 @@
+use std::fmt;
+use std::future::{ready, Ready};
+
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::{web, FromRequest, HttpRequest};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use tracing::{error, warn};
+
+use crate::error::ApiError;
+
+#[derive(Clone, Deserialize)]
+pub struct AuthSettings {
+    // HS256 secret the identity provider signs access tokens with. Until one
+    // is configured every authenticated route refuses its requests.
+    #[serde(default)]
+    pub jwt_secret: String,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub audience: Option<String>,
+    // Role needed for the operational /admin routes: entitlements and dead
+    // letters.
+    #[serde(default = "default_admin_role")]
+    pub admin_role: String,
+    // Role needed to work the compliance hold queue.
+    #[serde(default = "default_compliance_role")]
+    pub compliance_role: String,
+}
+
+fn default_admin_role() -> String {
+    "payments-admin".to_string()
+}
+
+fn default_compliance_role() -> String {
+    "payments-compliance".to_string()
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            jwt_secret: String::new(),
+            issuer: None,
+            audience: None,
+            admin_role: default_admin_role(),
+            compliance_role: default_compliance_role(),
+        }
+    }
+}
+
+// The secret is left out so settings can be logged.
+impl fmt::Debug for AuthSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthSettings")
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("admin_role", &self.admin_role)
+            .field("compliance_role", &self.compliance_role)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+// Verifies bearer tokens; registered once as app data and used by the
+// Principal and Admin extractors.
+pub struct Authenticator {
+    settings: AuthSettings,
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl Authenticator {
+    pub fn new(settings: AuthSettings) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        if let Some(issuer) = &settings.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &settings.audience {
+            validation.set_audience(&[audience]);
+        }
+        Self {
+            key: DecodingKey::from_secret(settings.jwt_secret.as_bytes()),
+            settings,
+            validation,
+        }
+    }
+
+    pub fn authenticate(&self, token: &str) -> Result<Principal, ApiError> {
+        if self.settings.jwt_secret.is_empty() {
+            return Err(ApiError::Unauthorized("authentication is not configured".to_string()));
+        }
+        let claims = decode::<Claims>(token, &self.key, &self.validation)
+            .map_err(|e| {
+                warn!("Rejected access token: {}", e);
+                ApiError::Unauthorized("invalid access token".to_string())
+            })?
+            .claims;
+        let subject = claims.sub.trim();
+        if subject.is_empty() {
+            return Err(ApiError::Unauthorized("access token has no subject".to_string()));
+        }
+        Ok(Principal {
+            subject: subject.to_string(),
+            roles: claims.roles,
+        })
+    }
+}
+
+// The caller as established by their access token. Handlers take the
+// acting user from here rather than from the request body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub subject: String,
+    pub roles: Vec<String>,
+}
+
+impl Principal {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|granted| granted == role)
+    }
+
+    // None when the request carries no Authorization header; a header that
+    // does not hold a valid token is still an error.
+    pub fn optional(request: &HttpRequest) -> Result<Option<Self>, ApiError> {
+        match request.headers().get(header::AUTHORIZATION) {
+            Some(_) => authenticate(request).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+fn authenticate(request: &HttpRequest) -> Result<Principal, ApiError> {
+    let authenticator = request.app_data::<web::Data<Authenticator>>().ok_or_else(|| {
+        error!("No authenticator registered for {}", request.path());
+        ApiError::InternalServerError
+    })?;
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| ApiError::Unauthorized("a bearer token is required".to_string()))?;
+    authenticator.authenticate(token)
+}
+
+impl FromRequest for Principal {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(authenticate(request))
+    }
+}
+
+fn authenticate_with_role(
+    request: &HttpRequest,
+    role_of: fn(&AuthSettings) -> &str,
+) -> Result<Principal, ApiError> {
+    let principal = authenticate(request)?;
+    let role = request
+        .app_data::<web::Data<Authenticator>>()
+        .map(|authenticator| role_of(&authenticator.settings))
+        .unwrap_or_default();
+    if principal.has_role(role) {
+        Ok(principal)
+    } else {
+        Err(ApiError::Forbidden(format!("{} does not have the {} role", principal.subject, role)))
+    }
+}
+
+// A principal holding the configured admin role.
+#[derive(Debug, Clone)]
+pub struct Admin(pub Principal);
+
+impl FromRequest for Admin {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(authenticate_with_role(request, |settings| &settings.admin_role).map(Admin))
+    }
+}
+
+// A principal holding the configured compliance role.
+#[derive(Debug, Clone)]
+pub struct Compliance(pub Principal);
+
+impl FromRequest for Compliance {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(authenticate_with_role(request, |settings| &settings.compliance_role).map(Compliance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App, HttpResponse};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &str = "test-secret";
+
+    fn token(subject: &str, roles: &[&str]) -> String {
+        let claims = json!({ "sub": subject, "roles": roles, "exp": chrono::Utc::now().timestamp() + 60 });
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_bytes())).unwrap()
+    }
+
+    async fn whoami(principal: Principal) -> HttpResponse {
+        HttpResponse::Ok().body(principal.subject)
+    }
+
+    async fn admin_only(_admin: Admin) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    async fn compliance_only(_analyst: Compliance) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn takes_the_caller_from_the_token_and_checks_the_admin_role() {
+        let authenticator = web::Data::new(Authenticator::new(AuthSettings {
+            jwt_secret: SECRET.to_string(),
+            ..AuthSettings::default()
+        }));
+        let app = test::init_service(
+            App::new()
+                .app_data(authenticator)
+                .route("/whoami", web::get().to(whoami))
+                .route("/admin", web::get().to(admin_only))
+                .route("/compliance", web::get().to(compliance_only)),
+        )
+        .await;
+        let call = |uri: &'static str, token: Option<String>| {
+            let mut request = test::TestRequest::get().uri(uri);
+            if let Some(token) = token {
+                request = request.insert_header((header::AUTHORIZATION, format!("Bearer {}", token)));
+            }
+            request.to_request()
+        };
+
+        let response = test::call_service(&app, call("/whoami", Some(token("alice", &[])))).await;
+        assert_eq!(test::read_body(response).await, "alice");
+        assert_eq!(test::call_service(&app, call("/whoami", None)).await.status(), 401);
+        assert_eq!(test::call_service(&app, call("/whoami", Some("forged".to_string()))).await.status(), 401);
+        assert_eq!(test::call_service(&app, call("/admin", Some(token("alice", &[])))).await.status(), 403);
+        let admin = token("bob", &["payments-admin"]);
+        assert_eq!(test::call_service(&app, call("/admin", Some(admin.clone()))).await.status(), 200);
+        assert_eq!(test::call_service(&app, call("/compliance", Some(admin))).await.status(), 403);
+        let analyst = token("carol", &["payments-compliance"]);
+        assert_eq!(test::call_service(&app, call("/compliance", Some(analyst))).await.status(), 200);
+    }
+}
@@ -0,0 +1,95 @@
+use actix_web::{get, post, web, HttpResponse};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::auth::Admin;
+use crate::domain::dead_letter::{DeadLetterQuery, DiscardRequest, ResubmitRequest};
+use crate::error::{ApiError, DeadLetterError};
+use crate::service::dead_letters::DeadLetterService;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1/admin/dead-letters")
+            .service(list_dead_letters)
+            .service(get_dead_letter)
+            .service(get_dead_letter_actions)
+            .service(resubmit_dead_letter)
+            .service(discard_dead_letter)
+    );
+}
+
+fn service_error(e: DeadLetterError) -> ApiError {
+    error!("Dead letter request failed: {:?}", e);
+    e.into()
+}
+
+#[get("")]
+async fn list_dead_letters(
+    _admin: Admin,
+    query: web::Query<DeadLetterQuery>,
+    service: web::Data<DeadLetterService>,
+) -> Result<HttpResponse, ApiError> {
+    let letters = service.list(&query).await.map_err(service_error)?;
+
+    Ok(HttpResponse::Ok().json(letters))
+}
+
+// Includes the raw payload; the caller is recorded in the audit trail.
+#[get("/{dead_letter_id}")]
+async fn get_dead_letter(
+    Admin(admin): Admin,
+    dead_letter_id: web::Path<Uuid>,
+    service: web::Data<DeadLetterService>,
+) -> Result<HttpResponse, ApiError> {
+    let letter = service
+        .view(&dead_letter_id, &admin.subject)
+        .await
+        .map_err(service_error)?;
+
+    Ok(HttpResponse::Ok().json(letter))
+}
+
+#[get("/{dead_letter_id}/actions")]
+async fn get_dead_letter_actions(
+    _admin: Admin,
+    dead_letter_id: web::Path<Uuid>,
+    service: web::Data<DeadLetterService>,
+) -> Result<HttpResponse, ApiError> {
+    let actions = service.actions(&dead_letter_id).await.map_err(service_error)?;
+
+    Ok(HttpResponse::Ok().json(actions))
+}
+
+#[post("/{dead_letter_id}/resubmit")]
+async fn resubmit_dead_letter(
+    Admin(admin): Admin,
+    dead_letter_id: web::Path<Uuid>,
+    request: web::Json<ResubmitRequest>,
+    service: web::Data<DeadLetterService>,
+) -> Result<HttpResponse, ApiError> {
+    info!("Received resubmit request for dead letter {}", dead_letter_id);
+
+    let letter = service
+        .resubmit(&dead_letter_id, &admin.subject, request.into_inner())
+        .await
+        .map_err(service_error)?;
+
+    Ok(HttpResponse::Ok().json(letter))
+}
+
+#[post("/{dead_letter_id}/discard")]
+async fn discard_dead_letter(
+    Admin(admin): Admin,
+    dead_letter_id: web::Path<Uuid>,
+    request: web::Json<DiscardRequest>,
+    service: web::Data<DeadLetterService>,
+) -> Result<HttpResponse, ApiError> {
+    info!("Received discard request for dead letter {}", dead_letter_id);
+
+    let letter = service
+        .discard(&dead_letter_id, &admin.subject, request.into_inner())
+        .await
+        .map_err(service_error)?;
+
+    Ok(HttpResponse::Ok().json(letter))
+}
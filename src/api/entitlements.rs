@@ -0,0 +1,89 @@
+use actix_web::{delete, get, put, web, HttpResponse};
+use chrono::Utc;
+use tracing::{error, info};
+
+use crate::api::auth::Admin;
+use crate::domain::entitlement::{normalize_account, AccountEntitlement, EntitlementRequest};
+use crate::error::ApiError;
+use crate::infrastructure::database::entitlements::EntitlementRepository;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1/admin/senders/{sender_id}/entitlements")
+            .service(list_entitlements)
+            .service(put_entitlement)
+            .service(revoke_entitlement)
+    );
+}
+
+fn repository_error(e: impl std::fmt::Debug) -> ApiError {
+    error!("Entitlement repository failed: {:?}", e);
+    ApiError::InternalServerError
+}
+
+#[get("")]
+async fn list_entitlements(
+    _admin: Admin,
+    sender_id: web::Path<String>,
+    repository: web::Data<dyn EntitlementRepository>,
+) -> Result<HttpResponse, ApiError> {
+    let entitlements = repository
+        .list_for_sender(&sender_id)
+        .await
+        .map_err(repository_error)?;
+
+    Ok(HttpResponse::Ok().json(entitlements))
+}
+
+#[put("/{account}")]
+async fn put_entitlement(
+    Admin(admin): Admin,
+    path: web::Path<(String, String)>,
+    request: web::Json<EntitlementRequest>,
+    repository: web::Data<dyn EntitlementRepository>,
+) -> Result<HttpResponse, ApiError> {
+    let (sender_id, account) = path.into_inner();
+    let request = request.into_inner();
+
+    // The grant is recorded under the administrator's token subject.
+    let entitlement = AccountEntitlement {
+        sender_id,
+        account: normalize_account(&account),
+        payment_types: request.payment_types,
+        granted_by: admin.subject,
+        updated_at: Utc::now(),
+    };
+    info!(
+        sender_id = %entitlement.sender_id,
+        account = %entitlement.account,
+        granted_by = %entitlement.granted_by,
+        payment_types = ?entitlement.payment_types,
+        "Granting account entitlement"
+    );
+    repository
+        .upsert(entitlement.clone())
+        .await
+        .map_err(repository_error)?;
+
+    Ok(HttpResponse::Ok().json(entitlement))
+}
+
+#[delete("/{account}")]
+async fn revoke_entitlement(
+    Admin(admin): Admin,
+    path: web::Path<(String, String)>,
+    repository: web::Data<dyn EntitlementRepository>,
+) -> Result<HttpResponse, ApiError> {
+    let (sender_id, account) = path.into_inner();
+    let account = normalize_account(&account);
+    let revoked = repository
+        .revoke(&sender_id, &account)
+        .await
+        .map_err(repository_error)?;
+    if !revoked {
+        return Err(ApiError::NotFound(format!("entitlement of {} to {}", sender_id, account)));
+    }
+
+    info!(sender_id = %sender_id, account = %account, revoked_by = %admin.subject, "Revoked account entitlement");
+    Ok(HttpResponse::NoContent().finish())
+}
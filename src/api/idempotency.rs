@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use futures::Future;
+use ring::digest::{digest, SHA256};
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::domain::status_reason::ReasonCode;
+use crate::error::ApiError;
+use crate::infrastructure::idempotency::{IdempotencyKey, IdempotencyOutcome, IdempotencyStore, StoredResponse};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+// The Idempotency-Key header wins; payment requests fall back to their
+// request_id so clients that predate the header still get retry safety.
+// Keys always belong to a sender, so two senders picking the same key never
+// see each other's responses.
+pub fn key_for(
+    http_request: &HttpRequest,
+    scope: &str,
+    sender_id: &str,
+    request_id: Option<&str>,
+) -> Result<Option<IdempotencyKey>, ApiError> {
+    let sender_id = sender_id.trim();
+    if sender_id.is_empty() {
+        return Err(ApiError::validation(
+            "idempotency",
+            ReasonCode::CH16,
+            format!("{} needs a sender to belong to", IDEMPOTENCY_KEY_HEADER),
+        ));
+    }
+    let header = http_request
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| {
+                    ApiError::validation(
+                        "idempotency",
+                        ReasonCode::CH16,
+                        format!("{} must be visible ASCII", IDEMPOTENCY_KEY_HEADER),
+                    )
+                })
+        })
+        .transpose()?;
+
+    let key = match header.or(request_id).map(str::trim) {
+        Some(key) if !key.is_empty() => key,
+        _ => return Ok(None),
+    };
+    if key.len() > MAX_KEY_LENGTH {
+        return Err(ApiError::validation(
+            "idempotency",
+            ReasonCode::CH16,
+            format!("{} must not exceed {} characters", IDEMPOTENCY_KEY_HEADER, MAX_KEY_LENGTH),
+        ));
+    }
+
+    Ok(Some(IdempotencyKey {
+        scope: scope.to_string(),
+        sender_id: sender_id.to_string(),
+        key: key.to_string(),
+    }))
+}
+
+pub fn fingerprint<T: Serialize>(body: &T) -> Result<String, ApiError> {
+    let canonical = serde_json::to_vec(body).map_err(|_| ApiError::InternalServerError)?;
+    Ok(digest(&SHA256, &canonical)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+// Holds the key while the operation runs. actix drops the handler future
+// when the client goes away; the attempt then ends without an outcome, so its
+// lease is given up at once rather than making retries wait for it to lapse.
+struct Attempt {
+    store: Arc<dyn IdempotencyStore>,
+    key: Option<IdempotencyKey>,
+}
+
+impl Attempt {
+    fn finish(mut self) -> Option<IdempotencyKey> {
+        self.key.take()
+    }
+}
+
+impl Drop for Attempt {
+    fn drop(&mut self) {
+        if let (Some(key), Ok(runtime)) = (self.key.take(), tokio::runtime::Handle::try_current()) {
+            let store = self.store.clone();
+            runtime.spawn(async move {
+                if let Err(e) = store.abandon(&key).await {
+                    error!(key = %key.key, "Failed to abandon idempotency key: {:?}", e);
+                }
+            });
+        }
+    }
+}
+
+pub async fn execute<R, F>(
+    store: Arc<dyn IdempotencyStore>,
+    key: Option<IdempotencyKey>,
+    fingerprint: String,
+    status: StatusCode,
+    operation: F,
+) -> Result<HttpResponse, ApiError>
+where
+    R: Serialize,
+    F: Future<Output = Result<R, ApiError>>,
+{
+    let key = match key {
+        Some(key) => key,
+        None => {
+            let body = operation.await?;
+            return Ok(HttpResponse::build(status).json(body));
+        }
+    };
+
+    match store.begin(&key, &fingerprint).await.map_err(|e| {
+        error!("Idempotency store unavailable: {:?}", e);
+        ApiError::InternalServerError
+    })? {
+        IdempotencyOutcome::New => {}
+        IdempotencyOutcome::Replay(stored) => {
+            info!(key = %key.key, "Replaying stored response for repeated idempotency key");
+            let status = StatusCode::from_u16(stored.status).map_err(|_| ApiError::InternalServerError)?;
+            return Ok(HttpResponse::build(status)
+                .insert_header((IDEMPOTENT_REPLAYED_HEADER, "true"))
+                .json(stored.body));
+        }
+        IdempotencyOutcome::InProgress => {
+            return Err(ApiError::Conflict(format!(
+                "a request with {} {} is still being processed",
+                IDEMPOTENCY_KEY_HEADER, key.key
+            )));
+        }
+        IdempotencyOutcome::Mismatch => {
+            return Err(ApiError::business_rule(
+                "idempotency",
+                ReasonCode::AM05,
+                format!("{} {} was already used with a different request body", IDEMPOTENCY_KEY_HEADER, key.key),
+            ));
+        }
+    }
+
+    let attempt = Attempt {
+        store: store.clone(),
+        key: Some(key),
+    };
+    let result = operation
+        .await
+        .and_then(|body| serde_json::to_value(body).map_err(|_| ApiError::InternalServerError));
+    let key = match attempt.finish() {
+        Some(key) => key,
+        None => return result.map(|body| HttpResponse::build(status).json(body)),
+    };
+
+    match result {
+        Ok(body) => {
+            let stored = StoredResponse {
+                status: status.as_u16(),
+                body: body.clone(),
+            };
+            if let Err(e) = store.complete(&key, stored).await {
+                error!("Failed to store idempotent response: {:?}", e);
+            }
+            Ok(HttpResponse::build(status).json(body))
+        }
+        // A rejection stored nothing, so the key is forgotten and the client
+        // may send a corrected request under it.
+        Err(e) if e.status_code().is_client_error() => {
+            if let Err(release_error) = store.release(&key).await {
+                error!("Failed to release idempotency key: {:?}", release_error);
+            }
+            Err(e)
+        }
+        // The payment may have been stored before the failure. Only the same
+        // request may retry, and the service answers it from the stored payment.
+        Err(e) => {
+            if let Err(abandon_error) = store.abandon(&key).await {
+                error!("Failed to abandon idempotency key: {:?}", abandon_error);
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::idempotency::InMemoryIdempotencyStore;
+    use std::time::Duration;
+
+    fn key() -> IdempotencyKey {
+        IdempotencyKey {
+            scope: "payments".to_string(),
+            sender_id: "sender".to_string(),
+            key: "abc".to_string(),
+        }
+    }
+
+    fn store() -> Arc<dyn IdempotencyStore> {
+        Arc::new(InMemoryIdempotencyStore::new(Duration::from_secs(60)))
+    }
+
+    async fn fail(store: &Arc<dyn IdempotencyStore>, error: ApiError) {
+        let operation = async move { Err::<(), _>(error) };
+        assert!(execute(store.clone(), Some(key()), "f1".to_string(), StatusCode::OK, operation).await.is_err());
+    }
+
+    #[test]
+    fn keys_are_scoped_to_their_sender() {
+        let request = actix_web::test::TestRequest::default()
+            .insert_header((IDEMPOTENCY_KEY_HEADER, "abc"))
+            .to_http_request();
+        let first = key_for(&request, "payments", "sender-a", None).unwrap().unwrap();
+        let second = key_for(&request, "payments", "sender-b", None).unwrap().unwrap();
+        assert_ne!(first, second);
+        assert!(key_for(&request, "payments", " ", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn rejections_release_the_key() {
+        let store = store();
+        fail(&store, ApiError::business_rule("amount", ReasonCode::AM12, "bad amount")).await;
+        assert!(matches!(store.begin(&key(), "f2").await.unwrap(), IdempotencyOutcome::New));
+    }
+
+    #[tokio::test]
+    async fn server_errors_keep_the_key_for_the_same_request() {
+        let store = store();
+        fail(&store, ApiError::InternalServerError).await;
+        assert!(matches!(store.begin(&key(), "f2").await.unwrap(), IdempotencyOutcome::Mismatch));
+        assert!(matches!(store.begin(&key(), "f1").await.unwrap(), IdempotencyOutcome::New));
+    }
+
+    #[tokio::test]
+    async fn dropped_requests_give_up_their_lease() {
+        let store = store();
+        let operation = futures::future::pending::<Result<(), ApiError>>();
+        let request = execute(store.clone(), Some(key()), "f1".to_string(), StatusCode::OK, operation);
+        assert!(tokio::time::timeout(Duration::from_millis(10), request).await.is_err());
+        tokio::task::yield_now().await;
+
+        assert!(matches!(store.begin(&key(), "f1").await.unwrap(), IdempotencyOutcome::New));
+    }
+}
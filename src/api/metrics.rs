@@ -0,0 +1,13 @@
+use actix_web::{get, web, HttpResponse};
+use metrics_exporter_prometheus::PrometheusHandle;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(render);
+}
+
+#[get("/metrics")]
+async fn render(handle: web::Data<PrometheusHandle>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(handle.render())
+}
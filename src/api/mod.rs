@@ -0,0 +1,55 @@
+pub mod auth;
+pub mod dead_letters;
+pub mod entitlements;
+pub mod idempotency;
+pub mod metrics;
+pub mod monitoring;
+pub mod payment;
+
+use actix_web::web;
+
+use crate::error::{json_error, path_error, query_error};
+
+// Extractor settings shared by every scope, so malformed requests get the
+// same problem+json responses as the handlers' own errors.
+pub fn extractor_config(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default().error_handler(json_error))
+        .app_data(web::PathConfig::default().error_handler(path_error))
+        .app_data(web::QueryConfig::default().error_handler(query_error));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PROBLEM_JSON;
+    use actix_web::http::header::CONTENT_TYPE;
+    use actix_web::{test, App, HttpResponse};
+    use uuid::Uuid;
+
+    async fn echo(_body: web::Json<serde_json::Value>, _id: web::Path<Uuid>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn malformed_requests_get_problem_details() {
+        let app = test::init_service(
+            App::new()
+                .configure(extractor_config)
+                .route("/things/{id}", web::post().to(echo)),
+        )
+        .await;
+
+        for (path, body) in [("/things/not-a-uuid", "{}"), (&*format!("/things/{}", Uuid::new_v4()), "{")] {
+            let request = test::TestRequest::post()
+                .uri(path)
+                .insert_header((CONTENT_TYPE, "application/json"))
+                .set_payload(body)
+                .to_request();
+            let response = test::call_service(&app, request).await;
+            assert_eq!(response.status(), 400);
+            assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), PROBLEM_JSON);
+            let problem: serde_json::Value = test::read_body_json(response).await;
+            assert_eq!(problem["violations"][0]["reason_code"], "FF01");
+        }
+    }
+}
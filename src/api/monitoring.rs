@@ -0,0 +1,51 @@
+use actix_web::{get, web, HttpResponse};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::auth::Compliance;
+use crate::error::ApiError;
+use crate::monitoring::monitor::AlertQuery;
+use crate::monitoring::TransactionMonitor;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1/alerts")
+            .service(list_alerts)
+            .service(get_alert)
+    );
+}
+
+#[get("")]
+async fn list_alerts(
+    _analyst: Compliance,
+    query: web::Query<AlertQuery>,
+    monitor: web::Data<dyn TransactionMonitor>,
+) -> Result<HttpResponse, ApiError> {
+    let alerts = monitor
+        .alerts(&query)
+        .await
+        .map_err(|e| {
+            error!("Failed to list monitoring alerts: {:?}", e);
+            ApiError::InternalServerError
+        })?;
+
+    Ok(HttpResponse::Ok().json(alerts))
+}
+
+#[get("/{alert_id}")]
+async fn get_alert(
+    _analyst: Compliance,
+    alert_id: web::Path<Uuid>,
+    monitor: web::Data<dyn TransactionMonitor>,
+) -> Result<HttpResponse, ApiError> {
+    let alert = monitor
+        .get_alert(&alert_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to retrieve monitoring alert: {:?}", e);
+            ApiError::InternalServerError
+        })?
+        .ok_or_else(|| ApiError::NotFound(alert_id.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(alert))
+}
@@ -1,20 +1,15 @@
-use actix_web::{web, HttpResponse, Scope};
+use actix_web::http::StatusCode;
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
 use tracing::{info, error};
 use uuid::Uuid;
 
-use crate::domain::payment::{
-    PaymentRequest, PaymentResponse, CreditTransferRequest, DirectDebitRequest,
-    InstantPaymentRequest, BulkPaymentRequest, MandateRequest
-};
+use crate::api::auth::{Compliance, Principal};
+use crate::api::idempotency;
+use crate::domain::payment::{ApprovalRequest, HoldDecisionRequest, PaymentRequest};
 use crate::error::ApiError;
-use crate::service::{
-    payment::PaymentService,
-    credit_transfer::CreditTransferService,
-    direct_debit::DirectDebitService,
-    instant_payment::InstantPaymentService,
-    bulk_payment::BulkPaymentService,
-    mandate::MandateService
-};
+use crate::infrastructure::idempotency::IdempotencyStore;
+use crate::service::limits::HeadroomQuery;
+use crate::service::PaymentService;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -23,323 +18,243 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                 web::scope("/payments")
                     .service(submit_payment)
                     .service(get_payment_status)
-                    .service(search_payments)
+                    .service(get_payment_by_uetr)
+                    .service(list_held_payments)
+                    .service(release_payment)
+                    .service(reject_payment)
+                    .service(list_pending_approval)
+                    .service(get_approvals)
+                    .service(approve_payment)
+                    .service(reject_approval)
+                    .service(get_payment_events)
             )
             .service(
-                web::scope("/credit-transfers")
-                    .service(submit_credit_transfer)
-                    .service(get_credit_transfer_status)
-                    .service(cancel_credit_transfer)
-            )
-            .service(
-                web::scope("/direct-debits")
-                    .service(submit_direct_debit)
-                    .service(get_direct_debit_status)
-                    .service(cancel_direct_debit)
-                    .service(manage_mandate)
-            )
-            .service(
-                web::scope("/instant-payments")
-                    .service(submit_instant_payment)
-                    .service(get_instant_payment_status)
-                    .service(verify_instant_payment)
-            )
-            .service(
-                web::scope("/bulk-payments")
-                    .service(submit_bulk_payment)
-                    .service(get_bulk_status)
-                    .service(cancel_bulk_payment)
-            )
-            .service(
-                web::scope("/mandates")
-                    .service(create_mandate)
-                    .service(update_mandate)
-                    .service(cancel_mandate)
-                    .service(get_mandate_status)
+                web::scope("/limits")
+                    .service(get_limit_headroom)
             )
     );
 }
 
 #[post("")]
 async fn submit_payment(
+    http_request: HttpRequest,
     payment_request: web::Json<PaymentRequest>,
-    payment_service: web::Data<PaymentService>,
+    payment_service: web::Data<dyn PaymentService>,
+    idempotency_store: web::Data<dyn IdempotencyStore>,
 ) -> Result<HttpResponse, ApiError> {
     info!("Received payment submission request");
-    
-    let response = payment_service
-        .process_payment(payment_request.into_inner())
-        .await
-        .map_err(|e| {
-            error!("Payment processing failed: {:?}", e);
-            e.into()
-        })?;
 
-    Ok(HttpResponse::Ok().json(response))
+    // Who submitted the payment is taken from the caller's token, never from
+    // the body.
+    let mut payment_request = payment_request.into_inner();
+    payment_request.submitted_by = Principal::optional(&http_request)?.map(|principal| principal.subject);
+    let key = idempotency::key_for(
+        &http_request,
+        "payments",
+        &payment_request.sender_id,
+        Some(&payment_request.request_id),
+    )?;
+    let fingerprint = idempotency::fingerprint(&payment_request)?;
+
+    idempotency::execute(idempotency_store.into_inner(), key, fingerprint, StatusCode::OK, async move {
+        payment_service
+            .process_payment(payment_request)
+            .await
+            .map_err(|e| {
+                error!("Payment processing failed: {:?}", e);
+                ApiError::from(e)
+            })
+    })
+    .await
 }
 
 #[get("/{payment_id}/status")]
 async fn get_payment_status(
     payment_id: web::Path<Uuid>,
-    payment_service: web::Data<PaymentService>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
     let status = payment_service
         .get_status(&payment_id)
         .await
         .map_err(|e| {
             error!("Failed to retrieve payment status: {:?}", e);
-            e.into()
+            ApiError::from(e)
         })?;
 
     Ok(HttpResponse::Ok().json(status))
 }
 
-// Credit Transfer APIs
-#[post("/credit-transfers")]
-async fn submit_credit_transfer(
-    request: web::Json<CreditTransferRequest>,
-    service: web::Data<CreditTransferService>,
+#[get("/uetr/{uetr}")]
+async fn get_payment_by_uetr(
+    uetr: web::Path<Uuid>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    info!("Received credit transfer request");
-    
-    let response = service
-        .process_credit_transfer(request.into_inner())
+    let payment = payment_service
+        .get_payment_by_uetr(&uetr)
         .await
         .map_err(|e| {
-            error!("Credit transfer processing failed: {:?}", e);
-            e.into()
+            error!("Failed to retrieve payment by UETR: {:?}", e);
+            ApiError::from(e)
         })?;
 
-    Ok(HttpResponse::Ok().json(response))
+    Ok(HttpResponse::Ok().json(payment))
 }
 
-#[get("/credit-transfers/{transfer_id}/status")]
-async fn get_credit_transfer_status(
-    transfer_id: web::Path<Uuid>,
-    service: web::Data<CreditTransferService>,
+#[get("/held")]
+async fn list_held_payments(
+    _analyst: Compliance,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    let status = service
-        .get_credit_transfer_status(&transfer_id)
+    let held = payment_service
+        .list_held()
         .await
-        .map_err(|e| e.into())?;
+        .map_err(|e| {
+            error!("Failed to list held payments: {:?}", e);
+            ApiError::from(e)
+        })?;
 
-    Ok(HttpResponse::Ok().json(status))
+    Ok(HttpResponse::Ok().json(held))
 }
 
-#[delete("/credit-transfers/{transfer_id}")]
-async fn cancel_credit_transfer(
-    transfer_id: web::Path<Uuid>,
-    service: web::Data<CreditTransferService>,
+#[post("/{payment_id}/release")]
+async fn release_payment(
+    payment_id: web::Path<Uuid>,
+    Compliance(analyst): Compliance,
+    decision: web::Json<HoldDecisionRequest>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    service
-        .cancel_credit_transfer(&transfer_id)
-        .await
-        .map_err(|e| e.into())?;
-
-    Ok(HttpResponse::Ok().finish())
-}
+    info!("Received release decision for held payment {} from {}", payment_id, analyst.subject);
 
-// Direct Debit APIs
-#[post("/direct-debits")]
-async fn submit_direct_debit(
-    request: web::Json<DirectDebitRequest>,
-    service: web::Data<DirectDebitService>,
-) -> Result<HttpResponse, ApiError> {
-    info!("Received direct debit request");
-    
-    let response = service
-        .process_direct_debit(request.into_inner())
+    let payment = payment_service
+        .release_payment(&payment_id, &analyst.subject, decision.into_inner())
         .await
         .map_err(|e| {
-            error!("Direct debit processing failed: {:?}", e);
-            e.into()
+            error!("Failed to release payment: {:?}", e);
+            ApiError::from(e)
         })?;
 
-    Ok(HttpResponse::Ok().json(response))
+    Ok(HttpResponse::Ok().json(payment))
 }
 
-#[get("/direct-debits/{debit_id}/status")]
-async fn get_direct_debit_status(
-    debit_id: web::Path<Uuid>,
-    service: web::Data<DirectDebitService>,
+#[post("/{payment_id}/reject")]
+async fn reject_payment(
+    payment_id: web::Path<Uuid>,
+    Compliance(analyst): Compliance,
+    decision: web::Json<HoldDecisionRequest>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    let status = service
-        .get_direct_debit_status(&debit_id)
-        .await
-        .map_err(|e| e.into())?;
+    info!("Received reject decision for held payment {} from {}", payment_id, analyst.subject);
 
-    Ok(HttpResponse::Ok().json(status))
-}
-
-#[delete("/direct-debits/{debit_id}")]
-async fn cancel_direct_debit(
-    debit_id: web::Path<Uuid>,
-    service: web::Data<DirectDebitService>,
-) -> Result<HttpResponse, ApiError> {
-    service
-        .cancel_direct_debit(&debit_id)
+    let payment = payment_service
+        .reject_payment(&payment_id, &analyst.subject, decision.into_inner())
         .await
-        .map_err(|e| e.into())?;
+        .map_err(|e| {
+            error!("Failed to reject payment: {:?}", e);
+            ApiError::from(e)
+        })?;
 
-    Ok(HttpResponse::Ok().finish())
+    Ok(HttpResponse::Ok().json(payment))
 }
 
-// Instant Payment APIs
-#[post("/instant-payments")]
-async fn submit_instant_payment(
-    request: web::Json<InstantPaymentRequest>,
-    service: web::Data<InstantPaymentService>,
+#[get("/pending-approval")]
+async fn list_pending_approval(
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    info!("Received instant payment request");
-    
-    let response = service
-        .process_instant_payment(request.into_inner())
+    let pending = payment_service
+        .list_pending_approval()
         .await
         .map_err(|e| {
-            error!("Instant payment processing failed: {:?}", e);
-            e.into()
+            error!("Failed to list payments pending approval: {:?}", e);
+            ApiError::from(e)
         })?;
 
-    Ok(HttpResponse::Ok().json(response))
+    Ok(HttpResponse::Ok().json(pending))
 }
 
-#[get("/instant-payments/{payment_id}/status")]
-async fn get_instant_payment_status(
+#[get("/{payment_id}/approvals")]
+async fn get_approvals(
     payment_id: web::Path<Uuid>,
-    service: web::Data<InstantPaymentService>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    let status = service
-        .get_instant_payment_status(&payment_id)
+    let approvals = payment_service
+        .get_approvals(&payment_id)
         .await
-        .map_err(|e| e.into())?;
+        .map_err(|e| {
+            error!("Failed to retrieve approval trail: {:?}", e);
+            ApiError::from(e)
+        })?;
 
-    Ok(HttpResponse::Ok().json(status))
+    Ok(HttpResponse::Ok().json(approvals))
 }
 
-#[post("/instant-payments/{payment_id}/verify")]
-async fn verify_instant_payment(
+#[get("/{payment_id}/events")]
+async fn get_payment_events(
     payment_id: web::Path<Uuid>,
-    service: web::Data<InstantPaymentService>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    let verification = service
-        .verify_instant_payment(&payment_id)
-        .await
-        .map_err(|e| e.into())?;
-
-    Ok(HttpResponse::Ok().json(verification))
-}
-
-// Bulk Payment APIs
-#[post("/bulk-payments")]
-async fn submit_bulk_payment(
-    request: web::Json<BulkPaymentRequest>,
-    service: web::Data<BulkPaymentService>,
-) -> Result<HttpResponse, ApiError> {
-    info!("Received bulk payment request");
-    
-    let response = service
-        .process_bulk_payment(request.into_inner())
+    let timeline = payment_service
+        .get_events(&payment_id)
         .await
         .map_err(|e| {
-            error!("Bulk payment processing failed: {:?}", e);
-            e.into()
+            error!("Failed to retrieve payment events: {:?}", e);
+            ApiError::from(e)
         })?;
 
-    Ok(HttpResponse::Ok().json(response))
+    Ok(HttpResponse::Ok().json(timeline))
 }
 
-#[get("/bulk-payments/{bulk_id}/status")]
-async fn get_bulk_status(
-    bulk_id: web::Path<Uuid>,
-    service: web::Data<BulkPaymentService>,
+#[post("/{payment_id}/approvals/approve")]
+async fn approve_payment(
+    payment_id: web::Path<Uuid>,
+    principal: Principal,
+    approval: web::Json<ApprovalRequest>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    let status = service
-        .get_bulk_payment_status(&bulk_id)
-        .await
-        .map_err(|e| e.into())?;
+    info!("Received approval for payment {} from {}", payment_id, principal.subject);
 
-    Ok(HttpResponse::Ok().json(status))
-}
-
-#[delete("/bulk-payments/{bulk_id}")]
-async fn cancel_bulk_payment(
-    bulk_id: web::Path<Uuid>,
-    service: web::Data<BulkPaymentService>,
-) -> Result<HttpResponse, ApiError> {
-    service
-        .cancel_bulk_payment(&bulk_id)
-        .await
-        .map_err(|e| e.into())?;
-
-    Ok(HttpResponse::Ok().finish())
-}
-
-// Mandate Management APIs
-#[post("/mandates")]
-async fn create_mandate(
-    request: web::Json<MandateRequest>,
-    service: web::Data<MandateService>,
-) -> Result<HttpResponse, ApiError> {
-    info!("Received mandate creation request");
-    
-    let response = service
-        .create_mandate(request.into_inner())
+    let payment = payment_service
+        .approve_payment(&payment_id, &principal.subject, approval.into_inner())
         .await
         .map_err(|e| {
-            error!("Mandate creation failed: {:?}", e);
-            e.into()
+            error!("Failed to approve payment: {:?}", e);
+            ApiError::from(e)
         })?;
 
-    Ok(HttpResponse::Created().json(response))
+    Ok(HttpResponse::Ok().json(payment))
 }
 
-#[put("/mandates/{mandate_id}")]
-async fn update_mandate(
-    mandate_id: web::Path<Uuid>,
-    request: web::Json<MandateRequest>,
-    service: web::Data<MandateService>,
+#[post("/{payment_id}/approvals/reject")]
+async fn reject_approval(
+    payment_id: web::Path<Uuid>,
+    principal: Principal,
+    approval: web::Json<ApprovalRequest>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    let response = service
-        .update_mandate(&mandate_id, request.into_inner())
-        .await
-        .map_err(|e| e.into())?;
+    info!("Received approval rejection for payment {} from {}", payment_id, principal.subject);
 
-    Ok(HttpResponse::Ok().json(response))
-}
-
-#[delete("/mandates/{mandate_id}")]
-async fn cancel_mandate(
-    mandate_id: web::Path<Uuid>,
-    service: web::Data<MandateService>,
-) -> Result<HttpResponse, ApiError> {
-    service
-        .cancel_mandate(&mandate_id)
+    let payment = payment_service
+        .reject_approval(&payment_id, &principal.subject, approval.into_inner())
         .await
-        .map_err(|e| e.into())?;
+        .map_err(|e| {
+            error!("Failed to reject payment approval: {:?}", e);
+            ApiError::from(e)
+        })?;
 
-    Ok(HttpResponse::Ok().finish())
+    Ok(HttpResponse::Ok().json(payment))
 }
 
-#[get("/mandates/{mandate_id}/status")]
-async fn get_mandate_status(
-    mandate_id: web::Path<Uuid>,
-    service: web::Data<MandateService>,
+#[get("/headroom")]
+async fn get_limit_headroom(
+    query: web::Query<HeadroomQuery>,
+    payment_service: web::Data<dyn PaymentService>,
 ) -> Result<HttpResponse, ApiError> {
-    let status = service
-        .get_mandate_status(&mandate_id)
+    let headroom = payment_service
+        .get_limit_headroom(&query)
         .await
-        .map_err(|e| e.into())?;
-
-    Ok(HttpResponse::Ok().json(status))
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use actix_web::{test, App};
-    use crate::service::payment::MockPaymentService;
-    use crate::domain::payment::{PaymentRequest, PaymentType};
-    use serde_json::json;
+        .map_err(|e| {
+            error!("Failed to retrieve limit headroom: {:?}", e);
+            ApiError::from(e)
+        })?;
 
-    // Remove the test functions
+    Ok(HttpResponse::Ok().json(headroom))
 }
@@ -1,11 +1,48 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
 
+use crate::api::auth::AuthSettings;
+use crate::domain::payment::PaymentType;
+use crate::fraud::FraudSettings;
+use crate::infrastructure::messaging::kafka::KafkaSettings;
+use crate::infrastructure::messaging::outbox_relay::OutboxSettings;
+use crate::infrastructure::messaging::retry::RetrySettings;
+use crate::monitoring::MonitoringSettings;
+use crate::screening::ScreeningSettings;
+use crate::service::approval::ApprovalSettings;
+use crate::service::limits::LimitSettings;
+use crate::validation::charset::CharsetPolicies;
+use crate::validation::duplicate::DuplicateSettings;
+use crate::validation::entitlements::EntitlementSettings;
+use crate::validation::pipeline::{default_rules, RuleConfig};
+
 #[derive(Debug, Deserialize)]
 pub struct Settings {
     pub server: ServerSettings,
     pub database: DatabaseSettings,
     pub messaging: MessagingSettings,
+    #[serde(default)]
+    pub validation: ValidationSettings,
+    #[serde(default)]
+    pub idempotency: IdempotencySettings,
+    #[serde(default)]
+    pub screening: ScreeningSettings,
+    #[serde(default)]
+    pub monitoring: MonitoringSettings,
+    #[serde(default)]
+    pub fraud: FraudSettings,
+    #[serde(default)]
+    pub approval: ApprovalSettings,
+    #[serde(default)]
+    pub limits: LimitSettings,
+    #[serde(default)]
+    pub outbox: OutboxSettings,
+    #[serde(default)]
+    pub auth: AuthSettings,
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,3 +50,230 @@ pub struct ServerSettings {
     pub port: u16,
     pub host: String,
 }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DatabaseSettings {
+    pub url: String,
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    #[serde(default)]
+    pub min_connections: u32,
+    #[serde(default = "default_acquire_timeout_seconds")]
+    pub acquire_timeout_seconds: u64,
+    #[serde(default = "default_idle_timeout_seconds")]
+    pub idle_timeout_seconds: u64,
+    // When off, startup only verifies the schema and pending migrations must
+    // be applied with the `migrate` command.
+    #[serde(default = "default_migrate_on_startup")]
+    pub migrate_on_startup: bool,
+}
+
+fn default_max_connections() -> u32 {
+    10
+}
+
+fn default_acquire_timeout_seconds() -> u64 {
+    5
+}
+
+fn default_idle_timeout_seconds() -> u64 {
+    10 * 60
+}
+
+fn default_migrate_on_startup() -> bool {
+    true
+}
+
+impl DatabaseSettings {
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout_seconds)
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_seconds)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MessagingSettings {
+    // Where outbound payment events go. Inbound payments and dead letters
+    // always use RabbitMQ.
+    #[serde(default)]
+    pub backend: MessagingBackend,
+    pub url: String,
+    #[serde(default = "default_exchange")]
+    pub exchange: String,
+    // direct, fanout, headers or topic
+    #[serde(default = "default_exchange_kind")]
+    pub exchange_kind: String,
+    // Prepended to every routing key, e.g. "eu." to route "eu.payment.received".
+    #[serde(default)]
+    pub routing_key_prefix: String,
+    // Publishes as mandatory so a message no queue is bound for fails instead
+    // of being dropped by the broker.
+    #[serde(default = "default_mandatory")]
+    pub mandatory: bool,
+    #[serde(default = "default_confirm_timeout_ms")]
+    pub confirm_timeout_ms: u64,
+    #[serde(default = "default_reconnect_attempts")]
+    pub reconnect_attempts: u32,
+    #[serde(default = "default_reconnect_initial_delay_ms")]
+    pub reconnect_initial_delay_ms: u64,
+    #[serde(default = "default_reconnect_max_delay_ms")]
+    pub reconnect_max_delay_ms: u64,
+    #[serde(default)]
+    pub inbound: Vec<InboundQueue>,
+    #[serde(default)]
+    pub retry: RetrySettings,
+    #[serde(default)]
+    pub kafka: KafkaSettings,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum MessagingBackend {
+    #[default]
+    #[serde(rename = "rabbitmq")]
+    RabbitMq,
+    #[serde(rename = "kafka")]
+    Kafka,
+}
+
+// A queue an upstream system delivers ISO messages on. The queue, not the
+// message, determines the sender, so one system cannot submit as another.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InboundQueue {
+    pub queue: String,
+    pub sender_id: String,
+    pub payment_type: PaymentType,
+    // Used when a message has no AMQP type property.
+    #[serde(default)]
+    pub message_type: Option<String>,
+    // Where status reports go when a message has no reply-to property.
+    #[serde(default)]
+    pub reply_queue: Option<String>,
+    #[serde(default = "default_inbound_channel")]
+    pub channel: String,
+    #[serde(default = "default_prefetch")]
+    pub prefetch: u16,
+}
+
+fn default_inbound_channel() -> String {
+    "amqp".to_string()
+}
+
+fn default_prefetch() -> u16 {
+    10
+}
+
+fn default_exchange() -> String {
+    "payments".to_string()
+}
+
+fn default_exchange_kind() -> String {
+    "topic".to_string()
+}
+
+fn default_mandatory() -> bool {
+    true
+}
+
+fn default_confirm_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_reconnect_attempts() -> u32 {
+    5
+}
+
+fn default_reconnect_initial_delay_ms() -> u64 {
+    200
+}
+
+fn default_reconnect_max_delay_ms() -> u64 {
+    10_000
+}
+
+impl MessagingSettings {
+    pub fn confirm_timeout(&self) -> Duration {
+        Duration::from_millis(self.confirm_timeout_ms)
+    }
+
+    // Exponential backoff for the given reconnect attempt, starting at 0.
+    pub fn reconnect_delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .reconnect_initial_delay_ms
+            .saturating_mul(1u64 << attempt.min(32))
+            .min(self.reconnect_max_delay_ms);
+        Duration::from_millis(delay)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidationSettings {
+    // JSON schema file per message type, e.g. pacs.008: schemas/pacs.008.json
+    #[serde(default)]
+    pub schemas: HashMap<String, PathBuf>,
+    #[serde(default)]
+    pub charset: CharsetPolicies,
+    #[serde(default)]
+    pub duplicates: DuplicateSettings,
+    #[serde(default = "default_rules")]
+    pub rules: Vec<RuleConfig>,
+    #[serde(default)]
+    pub entitlements: EntitlementSettings,
+}
+
+impl Default for ValidationSettings {
+    fn default() -> Self {
+        Self {
+            schemas: HashMap::new(),
+            charset: CharsetPolicies::default(),
+            duplicates: DuplicateSettings::default(),
+            rules: default_rules(),
+            entitlements: EntitlementSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdempotencySettings {
+    pub ttl_seconds: u64,
+    // How long a request holds its key before a retry may take it over, in
+    // case the instance handling it died.
+    #[serde(default = "default_lease_seconds")]
+    pub lease_seconds: u64,
+}
+
+fn default_lease_seconds() -> u64 {
+    60
+}
+
+impl Default for IdempotencySettings {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: 24 * 60 * 60,
+            lease_seconds: default_lease_seconds(),
+        }
+    }
+}
+
+impl IdempotencySettings {
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_seconds)
+    }
+
+    pub fn lease(&self) -> Duration {
+        Duration::from_secs(self.lease_seconds)
+    }
+}
+
+pub fn load_config() -> Result<Settings, ConfigError> {
+    let environment = std::env::var("APP_ENVIRONMENT").unwrap_or_else(|_| "local".to_string());
+
+    Config::builder()
+        .add_source(File::with_name("config/base").required(false))
+        .add_source(File::with_name(&format!("config/{}", environment)).required(false))
+        .add_source(Environment::with_prefix("APP").separator("__"))
+        .build()?
+        .try_deserialize()
+}
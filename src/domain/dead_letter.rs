@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterStatus {
+    Pending,
+    // Claimed for resubmission and being published; a letter left here was
+    // possibly published and needs checking before it is tried again.
+    Resubmitting,
+    Resubmitted,
+    Discarded,
+}
+
+// A message taken off a dead-letter queue, kept until someone decides what
+// to do with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: Uuid,
+    // The queue the message was consumed from before it failed.
+    pub queue: String,
+    pub message_id: Option<String>,
+    pub message_type: Option<String>,
+    pub correlation_id: Option<String>,
+    pub reply_to: Option<String>,
+    pub failure_class: Option<String>,
+    pub failure_reason: Option<String>,
+    pub attempts: u32,
+    pub payload: String,
+    pub dead_lettered_at: DateTime<Utc>,
+    pub status: DeadLetterStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeadLetterSummary {
+    pub id: Uuid,
+    pub queue: String,
+    pub message_id: Option<String>,
+    pub message_type: Option<String>,
+    pub failure_class: Option<String>,
+    pub failure_reason: Option<String>,
+    pub attempts: u32,
+    pub dead_lettered_at: DateTime<Utc>,
+    pub status: DeadLetterStatus,
+}
+
+impl From<&DeadLetter> for DeadLetterSummary {
+    fn from(letter: &DeadLetter) -> Self {
+        Self {
+            id: letter.id,
+            queue: letter.queue.clone(),
+            message_id: letter.message_id.clone(),
+            message_type: letter.message_type.clone(),
+            failure_class: letter.failure_class.clone(),
+            failure_reason: letter.failure_reason.clone(),
+            attempts: letter.attempts,
+            dead_lettered_at: letter.dead_lettered_at,
+            status: letter.status,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterActionKind {
+    Viewed,
+    Resubmitted,
+    Discarded,
+}
+
+// Audit record of one thing an operator did with a dead letter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterAction {
+    pub id: Uuid,
+    pub dead_letter_id: Uuid,
+    pub action: DeadLetterActionKind,
+    pub actor: String,
+    pub reason: Option<String>,
+    // Set when a message was resubmitted with a changed payload.
+    pub edited_payload: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DeadLetterQuery {
+    pub queue: Option<String>,
+    pub status: Option<DeadLetterStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResubmitRequest {
+    pub reason: String,
+    // Replaces the stored payload when present.
+    #[serde(default)]
+    pub payload: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiscardRequest {
+    pub reason: String,
+}
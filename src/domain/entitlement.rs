@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::payment::PaymentType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEntitlement {
+    pub sender_id: String,
+    pub account: String,
+    // Empty means every payment type.
+    pub payment_types: Vec<PaymentType>,
+    pub granted_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntitlementRequest {
+    #[serde(default)]
+    pub payment_types: Vec<PaymentType>,
+}
+
+impl AccountEntitlement {
+    pub fn permits(&self, payment_type: PaymentType) -> bool {
+        self.payment_types.is_empty() || self.payment_types.contains(&payment_type)
+    }
+}
+
+// IBANs are compared without spaces and case, as printed and electronic forms
+// differ only in those.
+pub fn normalize_account(account: &str) -> String {
+    account
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
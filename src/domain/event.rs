@@ -0,0 +1,190 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::payment::{
+    ApprovalRecord, ApprovalRequirement, FraudAssessment, HoldDecision, Payment, PaymentFlag, PaymentRequest,
+    PaymentStatus, ScreeningHit, TransliterationRecord,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PaymentEventKind {
+    Received {
+        uetr: Uuid,
+        request: PaymentRequest,
+    },
+    Validated {
+        flags: Vec<PaymentFlag>,
+        transliterations: Vec<TransliterationRecord>,
+    },
+    Screened {
+        hits: Vec<ScreeningHit>,
+    },
+    FraudAssessed {
+        assessment: FraudAssessment,
+    },
+    ApprovalRequested {
+        requirement: ApprovalRequirement,
+    },
+    StatusChanged {
+        from: Option<PaymentStatus>,
+        to: PaymentStatus,
+        actor: String,
+        reason: Option<String>,
+    },
+    HoldDecided {
+        decision: HoldDecision,
+    },
+    ApprovalRecorded {
+        record: ApprovalRecord,
+    },
+    Published {
+        routing_key: String,
+    },
+    // A pacs.002 from the next party in the chain.
+    StatusReportReceived {
+        message_id: Option<String>,
+        transaction_status: String,
+        reason_code: Option<String>,
+        additional_information: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentEvent {
+    pub id: Uuid,
+    pub payment_id: Uuid,
+    // Starts at 1 and has no gaps within a payment.
+    pub sequence: u64,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: PaymentEventKind,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaymentTimeline {
+    pub payment_id: Uuid,
+    pub status: PaymentStatus,
+    pub events: Vec<PaymentEvent>,
+}
+
+// Folds a payment's events, oldest first, back into the payment. Returns None
+// until the Received event is seen.
+pub fn replay(events: &[PaymentEvent]) -> Option<Payment> {
+    let mut payment: Option<Payment> = None;
+    for event in events {
+        if let PaymentEventKind::Received { uetr, request } = &event.kind {
+            let mut received = Payment::new(request.clone(), *uetr, Vec::new(), Vec::new());
+            received.id = event.payment_id;
+            received.created_at = event.occurred_at;
+            payment = Some(received);
+        }
+        let payment = match payment.as_mut() {
+            Some(payment) => payment,
+            None => continue,
+        };
+
+        match &event.kind {
+            PaymentEventKind::Received { .. } => {}
+            PaymentEventKind::Validated {
+                flags,
+                transliterations,
+            } => {
+                payment.flags = flags.clone();
+                payment.transliterations = transliterations.clone();
+            }
+            PaymentEventKind::Screened { hits } => payment.screening_hits = hits.clone(),
+            PaymentEventKind::FraudAssessed { assessment } => payment.fraud_assessment = Some(assessment.clone()),
+            PaymentEventKind::ApprovalRequested { requirement } => payment.approval = Some(requirement.clone()),
+            PaymentEventKind::StatusChanged { to, .. } => payment.status = *to,
+            PaymentEventKind::HoldDecided { decision } => payment.hold_decision = Some(decision.clone()),
+            PaymentEventKind::ApprovalRecorded { record } => payment.approvals.push(record.clone()),
+            PaymentEventKind::Published { .. } | PaymentEventKind::StatusReportReceived { .. } => {}
+        }
+        payment.updated_at = event.occurred_at;
+    }
+    payment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::{HoldOutcome, PaymentType, ReviewAction};
+    use serde_json::json;
+
+    fn event(sequence: u64, payment_id: Uuid, kind: PaymentEventKind) -> PaymentEvent {
+        PaymentEvent {
+            id: Uuid::new_v4(),
+            payment_id,
+            sequence,
+            occurred_at: Utc::now(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn replays_events_into_current_state() {
+        let payment_id = Uuid::new_v4();
+        let request = PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: json!({}),
+            sender_id: "acme".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        };
+        let flag = PaymentFlag {
+            rule: "sanctions_screening".to_string(),
+            action: ReviewAction::Hold,
+            reason: "potential match".to_string(),
+            related_payment_id: None,
+        };
+        let events = vec![
+            event(1, payment_id, PaymentEventKind::Received { uetr: Uuid::new_v4(), request }),
+            event(2, payment_id, PaymentEventKind::Validated { flags: vec![flag], transliterations: Vec::new() }),
+            event(
+                3,
+                payment_id,
+                PaymentEventKind::StatusChanged {
+                    from: None,
+                    to: PaymentStatus::Held,
+                    actor: "acme".to_string(),
+                    reason: None,
+                },
+            ),
+            event(
+                4,
+                payment_id,
+                PaymentEventKind::HoldDecided {
+                    decision: HoldDecision {
+                        outcome: HoldOutcome::Released,
+                        decided_by: "analyst".to_string(),
+                        reason: "false positive".to_string(),
+                        decided_at: Utc::now(),
+                    },
+                },
+            ),
+            event(
+                5,
+                payment_id,
+                PaymentEventKind::StatusChanged {
+                    from: Some(PaymentStatus::Held),
+                    to: PaymentStatus::Received,
+                    actor: "analyst".to_string(),
+                    reason: Some("false positive".to_string()),
+                },
+            ),
+        ];
+
+        assert!(replay(&events[1..]).is_none());
+        let payment = replay(&events).unwrap();
+        assert_eq!(payment.id, payment_id);
+        assert_eq!(payment.status, PaymentStatus::Received);
+        assert_eq!(payment.flags.len(), 1);
+        assert_eq!(payment.hold_decision.unwrap().decided_by, "analyst");
+        assert_eq!(payment.updated_at, events[4].occurred_at);
+    }
+}
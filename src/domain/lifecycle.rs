@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::payment::{PaymentStatus, PaymentType};
+
+use PaymentStatus::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub status: PaymentStatus,
+    pub actor: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusTransition {
+    // None for the status a payment was first stored with.
+    pub from: Option<PaymentStatus>,
+    pub to: PaymentStatus,
+    pub actor: String,
+    pub reason: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+// Where a payment may go next. Intake can end in Received, Validated, Held,
+// PendingApproval, Accepted or Rejected; Rejected, Returned and Cancelled are
+// final, and so is Settled for anything that cannot be returned.
+pub fn next_statuses(payment_type: PaymentType, from: PaymentStatus) -> &'static [PaymentStatus] {
+    match (payment_type, from) {
+        // Instant payments are irrevocable once accepted; a settled one can
+        // only come back through a recall.
+        (PaymentType::RealTimePayment, Accepted) => &[Sent, Rejected],
+        (PaymentType::RealTimePayment, Sent) => &[Settled, Rejected],
+        // A request for payment can be withdrawn until the debtor pays it, and
+        // paying it creates a separate credit transfer rather than a return.
+        (PaymentType::RequestForPayment, Sent) => &[Settled, Rejected, Cancelled],
+        (PaymentType::RequestForPayment, Settled) => &[],
+        (PaymentType::PaymentReturn, Accepted) => &[Sent, Rejected],
+        (PaymentType::PaymentReturn, Settled) => &[],
+
+        (_, Received) => &[Validated, Held, PendingApproval, Accepted, Rejected, Cancelled],
+        (_, Validated) => &[Held, PendingApproval, Accepted, Rejected, Cancelled],
+        (_, Held) => &[Received, PendingApproval, Rejected],
+        (_, PendingApproval) => &[Received, Rejected, Cancelled],
+        (_, Accepted) => &[Sent, Rejected, Cancelled],
+        (_, Sent) => &[Settled, Rejected],
+        (_, Settled) => &[Returned],
+        (_, Rejected) | (_, Returned) | (_, Cancelled) => &[],
+    }
+}
+
+pub fn can_transition(payment_type: PaymentType, from: PaymentStatus, to: PaymentStatus) -> bool {
+    next_statuses(payment_type, from).contains(&to)
+}
+
+// Statuses a payment may be stored with when it is first created.
+pub fn can_start(payment_type: PaymentType, status: PaymentStatus) -> bool {
+    status == Received || can_transition(payment_type, Received, status)
+}
+
+pub fn is_final(payment_type: PaymentType, status: PaymentStatus) -> bool {
+    next_statuses(payment_type, status).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_per_type_lifecycles() {
+        assert!(can_start(PaymentType::CreditTransfer, Held));
+        assert!(!can_start(PaymentType::CreditTransfer, Settled));
+
+        assert!(can_transition(PaymentType::CreditTransfer, Held, Received));
+        assert!(can_transition(PaymentType::CreditTransfer, Accepted, Cancelled));
+        assert!(can_transition(PaymentType::DirectDebit, Settled, Returned));
+        assert!(!can_transition(PaymentType::CreditTransfer, Rejected, Accepted));
+        assert!(!can_transition(PaymentType::CreditTransfer, Held, Accepted));
+
+        assert!(!can_transition(PaymentType::RealTimePayment, Accepted, Cancelled));
+        assert!(can_transition(PaymentType::RequestForPayment, Sent, Cancelled));
+        assert!(is_final(PaymentType::RequestForPayment, Settled));
+        assert!(is_final(PaymentType::PaymentReturn, Settled));
+        assert!(!is_final(PaymentType::CreditTransfer, Settled));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod dead_letter;
+pub mod entitlement;
+pub mod event;
+pub mod lifecycle;
+pub mod payment;
+pub mod payment_details;
+pub mod status_reason;
+pub mod status_report;
+pub mod uetr;
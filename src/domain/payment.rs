@@ -1,17 +1,48 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use ring::digest::{digest, SHA256};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::domain::payment_details;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentRequest {
     pub message_type: String,
     pub payment_type: PaymentType,
     pub message_payload: serde_json::Value,
     pub sender_id: String,
     pub request_id: String,
+    #[serde(default)]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub uetr: Option<Uuid>,
+    // The authenticated user who keyed or uploaded the payment, set by the
+    // API from the caller's token; required when four-eyes approval applies
+    // so the approver can be checked against it.
+    #[serde(default)]
+    pub submitted_by: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl PaymentRequest {
+    // Who to attribute the submission to: the keying user when known,
+    // otherwise the sending system.
+    pub fn submitter(&self) -> &str {
+        self.submitted_by.as_deref().unwrap_or(&self.sender_id)
+    }
+
+    // SHA-256 over what the sender asked for. serde_json keeps object keys
+    // sorted, so the same message hashes the same however it was formatted.
+    pub fn fingerprint(&self) -> String {
+        let canonical = serde_json::json!([self.message_type, self.payment_type, self.message_payload, self.uetr]);
+        digest(&SHA256, canonical.to_string().as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PaymentType {
     CreditTransfer,
     DirectDebit,
@@ -20,42 +51,298 @@ pub enum PaymentType {
     RealTimePayment,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreditTransferRequest {
-    pub amount: f64,
-    pub currency: String,
-    pub sender_account: String,
-    pub receiver_account: String,
-    pub transfer_date: DateTime<Utc>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentStatus {
+    Received,
+    Validated,
+    Held,
+    PendingApproval,
+    Accepted,
+    Sent,
+    Settled,
+    Rejected,
+    Returned,
+    Cancelled,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DirectDebitRequest {
-    pub amount: f64,
-    pub currency: String,
-    pub sender_account: String,
-    pub receiver_account: String,
-    pub debit_date: DateTime<Utc>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewAction {
+    Flag,
+    Hold,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentFlag {
+    pub rule: String,
+    pub action: ReviewAction,
+    pub reason: String,
+    pub related_payment_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransliterationRecord {
+    pub path: String,
+    pub original: String,
+    pub transliterated: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreeningHit {
+    pub list: String,
+    pub entry_id: String,
+    pub matched_name: String,
+    pub field: String,
+    pub screened_value: String,
+    pub score: f64,
+    pub programs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldOutcome {
+    Released,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldDecision {
+    pub outcome: HoldOutcome,
+    pub decided_by: String,
+    pub reason: String,
+    pub decided_at: DateTime<Utc>,
+}
+
+// The analyst is the authenticated caller, never part of the body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldDecisionRequest {
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FraudDecision {
+    Accept,
+    Review,
+    Reject,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FraudAssessment {
+    // None when the scorer missed its budget or failed and the configured
+    // fallback decision was applied instead.
+    pub score: Option<f64>,
+    pub decision: FraudDecision,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequirement {
+    pub reason: String,
+    pub required_approvals: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalOutcome {
+    Approved,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub outcome: ApprovalOutcome,
+    pub approver: String,
+    pub comment: Option<String>,
+    pub decided_at: DateTime<Utc>,
+}
+
+// The approver is the authenticated caller, never part of the body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payment {
+    pub id: Uuid,
+    pub uetr: Uuid,
+    pub request: PaymentRequest,
+    pub status: PaymentStatus,
+    pub transliterations: Vec<TransliterationRecord>,
+    pub flags: Vec<PaymentFlag>,
+    #[serde(default)]
+    pub screening_hits: Vec<ScreeningHit>,
+    #[serde(default)]
+    pub hold_decision: Option<HoldDecision>,
+    #[serde(default)]
+    pub fraud_assessment: Option<FraudAssessment>,
+    #[serde(default)]
+    pub approval: Option<ApprovalRequirement>,
+    #[serde(default)]
+    pub approvals: Vec<ApprovalRecord>,
+    // Fingerprint of the request as submitted, so a repeated request_id can be
+    // told apart from a resend of the same payment.
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Payment {
+    pub fn new(
+        request: PaymentRequest,
+        uetr: Uuid,
+        transliterations: Vec<TransliterationRecord>,
+        flags: Vec<PaymentFlag>,
+    ) -> Self {
+        let now = Utc::now();
+        let status = if flags.iter().any(|flag| flag.action == ReviewAction::Hold) {
+            PaymentStatus::Held
+        } else {
+            PaymentStatus::Received
+        };
+        Self {
+            id: Uuid::new_v4(),
+            uetr,
+            request,
+            status,
+            transliterations,
+            flags,
+            screening_hits: Vec::new(),
+            hold_decision: None,
+            fraud_assessment: None,
+            approval: None,
+            approvals: Vec::new(),
+            fingerprint: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct InstantPaymentRequest {
-    pub amount: f64,
-    pub currency: String,
-    pub sender_account: String,
-    pub receiver_account: String,
-    pub payment_date: DateTime<Utc>,
+pub struct PaymentResponse {
+    pub payment_id: Uuid,
+    pub uetr: Uuid,
+    pub status: PaymentStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub flags: Vec<PaymentFlag>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub hold_decision: Option<HoldDecision>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub transactions: Vec<TransactionReference>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Payment> for PaymentResponse {
+    fn from(payment: &Payment) -> Self {
+        Self {
+            payment_id: payment.id,
+            uetr: payment.uetr,
+            status: payment.status,
+            flags: payment.flags.clone(),
+            hold_decision: payment.hold_decision.clone(),
+            transactions: TransactionReference::all(&payment.request.message_payload),
+            created_at: payment.created_at,
+        }
+    }
+}
+
+// The identifiers of one transaction in the message, as quoted back by
+// status reports in OrgnlEndToEndId, OrgnlTxId and OrgnlUETR.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransactionReference {
+    // JSON pointer to the transaction within the message.
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub end_to_end_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub transaction_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub uetr: Option<String>,
+}
+
+impl TransactionReference {
+    pub fn all(payload: &serde_json::Value) -> Vec<Self> {
+        payment_details::transactions(payload)
+            .iter()
+            .map(|transaction| {
+                let payment_id = transaction.find("PmtId");
+                let text = |element: &str| {
+                    payment_id
+                        .and_then(|id| id.get(element))
+                        .and_then(serde_json::Value::as_str)
+                        .map(str::to_string)
+                };
+                Self {
+                    path: transaction.path.clone(),
+                    end_to_end_id: text("EndToEndId"),
+                    transaction_id: text("TxId"),
+                    uetr: text("UETR"),
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct BulkPaymentRequest {
-    pub payments: Vec<PaymentRequest>,
+pub struct PendingApprovalResponse {
+    pub payment_id: Uuid,
+    pub uetr: Uuid,
+    pub sender_id: String,
+    pub payment_type: PaymentType,
+    pub submitted_by: Option<String>,
+    pub reason: String,
+    pub required_approvals: usize,
+    pub approvals: Vec<ApprovalRecord>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Payment> for PendingApprovalResponse {
+    fn from(payment: &Payment) -> Self {
+        Self {
+            payment_id: payment.id,
+            uetr: payment.uetr,
+            sender_id: payment.request.sender_id.clone(),
+            payment_type: payment.request.payment_type,
+            submitted_by: payment.request.submitted_by.clone(),
+            reason: payment.approval.as_ref().map(|a| a.reason.clone()).unwrap_or_default(),
+            required_approvals: payment.approval.as_ref().map_or(0, |a| a.required_approvals),
+            approvals: payment.approvals.clone(),
+            created_at: payment.created_at,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct MandateRequest {
-    pub mandate_id: Uuid,
-    pub debtor_account: String,
-    pub creditor_account: String,
-    pub mandate_date: DateTime<Utc>,
+pub struct HeldPaymentResponse {
+    pub payment_id: Uuid,
+    pub uetr: Uuid,
+    pub sender_id: String,
+    pub payment_type: PaymentType,
+    pub reasons: Vec<PaymentFlag>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub screening_hits: Vec<ScreeningHit>,
+    pub held_since: DateTime<Utc>,
+}
+
+impl From<&Payment> for HeldPaymentResponse {
+    fn from(payment: &Payment) -> Self {
+        Self {
+            payment_id: payment.id,
+            uetr: payment.uetr,
+            sender_id: payment.request.sender_id.clone(),
+            payment_type: payment.request.payment_type,
+            reasons: payment
+                .flags
+                .iter()
+                .filter(|flag| flag.action == ReviewAction::Hold)
+                .cloned()
+                .collect(),
+            screening_hits: payment.screening_hits.clone(),
+            held_since: payment.created_at,
+        }
+    }
 }
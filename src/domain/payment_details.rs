@@ -0,0 +1,296 @@
+use serde_json::Value;
+
+// Flattened view over the ISO 20022 JSON payload. Element names follow the
+// pain.001 / pacs.008 / pacs.003 vocabulary; the first occurrence wins, which
+// matches single-transaction messages and the first transaction of a batch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaymentDetails {
+    pub debtor_name: Option<String>,
+    pub debtor_account: Option<String>,
+    pub debtor_agent: Option<String>,
+    pub creditor_name: Option<String>,
+    pub creditor_account: Option<String>,
+    pub creditor_agent: Option<String>,
+    pub amount: Option<f64>,
+    pub currency: Option<String>,
+    pub end_to_end_id: Option<String>,
+    pub uetr: Option<String>,
+    pub remittance_information: Option<String>,
+}
+
+impl PaymentDetails {
+    pub fn from_payload(payload: &Value) -> Self {
+        let debtor = find(payload, "Dbtr");
+        let creditor = find(payload, "Cdtr");
+        let (amount, currency) = find_amount(payload);
+
+        Self {
+            debtor_name: debtor.and_then(|party| text(party.get("Nm"))),
+            debtor_account: find(payload, "DbtrAcct").and_then(account_id),
+            debtor_agent: find(payload, "DbtrAgt").and_then(agent_id),
+            creditor_name: creditor.and_then(|party| text(party.get("Nm"))),
+            creditor_account: find(payload, "CdtrAcct").and_then(account_id),
+            creditor_agent: find(payload, "CdtrAgt").and_then(agent_id),
+            amount,
+            currency,
+            end_to_end_id: find(payload, "PmtId").and_then(|id| text(id.get("EndToEndId"))),
+            uetr: find(payload, "PmtId").and_then(|id| text(id.get("UETR"))),
+            remittance_information: find(payload, "RmtInf").and_then(remittance_text),
+        }
+    }
+
+    fn or(self, fallback: &PaymentDetails) -> Self {
+        let fallback = fallback.clone();
+        Self {
+            debtor_name: self.debtor_name.or(fallback.debtor_name),
+            debtor_account: self.debtor_account.or(fallback.debtor_account),
+            debtor_agent: self.debtor_agent.or(fallback.debtor_agent),
+            creditor_name: self.creditor_name.or(fallback.creditor_name),
+            creditor_account: self.creditor_account.or(fallback.creditor_account),
+            creditor_agent: self.creditor_agent.or(fallback.creditor_agent),
+            amount: self.amount.or(fallback.amount),
+            currency: self.currency.or(fallback.currency),
+            end_to_end_id: self.end_to_end_id.or(fallback.end_to_end_id),
+            uetr: self.uetr.or(fallback.uetr),
+            remittance_information: self.remittance_information.or(fallback.remittance_information),
+        }
+    }
+
+    pub fn reference(&self) -> Option<&str> {
+        self.end_to_end_id
+            .as_deref()
+            .filter(|id| *id != "NOTPROVIDED")
+            .or(self.remittance_information.as_deref())
+    }
+}
+
+pub const TRANSACTION_ELEMENTS: &[&str] = &["CdtTrfTxInf", "DrctDbtTxInf"];
+
+// One credit transfer or direct debit transaction of a message. Elements the
+// transaction leaves out are taken from the block around it, such as the
+// PmtInf carrying the debtor of a pain.001 batch.
+#[derive(Debug, Clone)]
+pub struct TransactionDetails<'a> {
+    pub path: String,
+    pub transaction: &'a Value,
+    pub block_path: String,
+    pub block: Value,
+    pub details: PaymentDetails,
+}
+
+impl<'a> TransactionDetails<'a> {
+    // Matches inside the transaction first, then in its enclosing block, with
+    // pointers relative to the whole message.
+    pub fn locate_all(&self, element: &str) -> Vec<(String, &Value)> {
+        let own = locate_all(self.transaction, element)
+            .into_iter()
+            .map(|(path, found)| (format!("{}{}", self.path, path), found));
+        let inherited = locate_all(&self.block, element)
+            .into_iter()
+            .map(|(path, found)| (format!("{}{}", self.block_path, path), found));
+        own.chain(inherited).collect()
+    }
+
+    pub fn find(&self, element: &str) -> Option<&Value> {
+        find(self.transaction, element).or_else(|| find(&self.block, element))
+    }
+}
+
+// Every transaction of the message; a message without transaction elements
+// is treated as a single transaction.
+pub fn transactions(payload: &Value) -> Vec<TransactionDetails<'_>> {
+    let mut found = Vec::new();
+    for element in TRANSACTION_ELEMENTS {
+        for (path, value) in locate_all(payload, element) {
+            let block_path = path.rsplit_once('/').map_or(String::new(), |(parent, _)| parent.to_string());
+            let block = match payload.pointer(&block_path) {
+                Some(Value::Object(fields)) => Value::Object(
+                    fields
+                        .iter()
+                        .filter(|(key, _)| !TRANSACTION_ELEMENTS.contains(&key.as_str()))
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect(),
+                ),
+                _ => Value::Null,
+            };
+            let inherited = PaymentDetails::from_payload(&block);
+            let items: Vec<(String, &Value)> = match value {
+                Value::Array(items) => items.iter().enumerate().map(|(i, item)| (format!("{}/{}", path, i), item)).collect(),
+                other => vec![(path.clone(), other)],
+            };
+            for (path, transaction) in items {
+                found.push(TransactionDetails {
+                    details: PaymentDetails::from_payload(transaction).or(&inherited),
+                    path,
+                    transaction,
+                    block_path: block_path.clone(),
+                    block: block.clone(),
+                });
+            }
+        }
+    }
+    if found.is_empty() {
+        found.push(TransactionDetails {
+            path: String::new(),
+            transaction: payload,
+            block_path: String::new(),
+            block: Value::Null,
+            details: PaymentDetails::from_payload(payload),
+        });
+    }
+    found
+}
+
+pub fn find<'a>(value: &'a Value, element: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(fields) => fields
+            .get(element)
+            .or_else(|| fields.values().find_map(|child| find(child, element))),
+        Value::Array(items) => items.iter().find_map(|item| find(item, element)),
+        _ => None,
+    }
+}
+
+pub fn find_all<'a>(value: &'a Value, element: &str) -> Vec<&'a Value> {
+    locate_all(value, element).into_iter().map(|(_, found)| found).collect()
+}
+
+// Same as find_all but also returns the JSON pointer of every match.
+pub fn locate_all<'a>(value: &'a Value, element: &str) -> Vec<(String, &'a Value)> {
+    let mut found = Vec::new();
+    locate_into(value, element, String::new(), &mut found);
+    found
+}
+
+fn locate_into<'a>(value: &'a Value, element: &str, path: String, found: &mut Vec<(String, &'a Value)>) {
+    match value {
+        Value::Object(fields) => {
+            for (key, child) in fields {
+                let child_path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                if key == element {
+                    found.push((child_path, child));
+                } else {
+                    locate_into(child, element, child_path, found);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                locate_into(item, element, format!("{}/{}", path, index), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn account_id(account: &Value) -> Option<String> {
+    let id = account.get("Id")?;
+    text(id.get("IBAN")).or_else(|| id.get("Othr").and_then(|other| text(other.get("Id"))))
+}
+
+fn agent_id(agent: &Value) -> Option<String> {
+    let institution = agent.get("FinInstnId")?;
+    text(institution.get("BICFI")).or_else(|| text(institution.get("BIC")))
+}
+
+fn remittance_text(remittance: &Value) -> Option<String> {
+    match remittance.get("Ustrd")? {
+        Value::Array(lines) => {
+            let joined = lines.iter().filter_map(|line| text(Some(line))).collect::<Vec<_>>().join(" ");
+            Some(joined).filter(|text| !text.is_empty())
+        }
+        other => text(Some(other)),
+    }
+}
+
+fn find_amount(payload: &Value) -> (Option<f64>, Option<String>) {
+    let amount = ["IntrBkSttlmAmt", "InstdAmt", "Amt"]
+        .iter()
+        .find_map(|element| find(payload, element))
+        .map(|amount| amount.get("InstdAmt").unwrap_or(amount));
+
+    match amount {
+        Some(Value::Number(number)) => (number.as_f64(), None),
+        Some(Value::String(text)) => (text.parse().ok(), None),
+        Some(Value::Object(fields)) => {
+            let value = ["value", "$value", "Value"]
+                .iter()
+                .find_map(|key| fields.get(*key))
+                .and_then(|value| match value {
+                    Value::Number(number) => number.as_f64(),
+                    Value::String(text) => text.parse().ok(),
+                    _ => None,
+                });
+            (value, text(fields.get("Ccy")))
+        }
+        _ => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn extracts_parties_amount_and_reference() {
+        let payload = json!({
+            "CdtTrfTxInf": {
+                "PmtId": { "EndToEndId": "INV-42" },
+                "IntrBkSttlmAmt": { "Ccy": "EUR", "value": 125.5 },
+                "Dbtr": { "Nm": "Alice" },
+                "DbtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } },
+                "Cdtr": { "Nm": "Bob" },
+                "CdtrAcct": { "Id": { "IBAN": "FR1420041010050500013M02606" } },
+                "CdtrAgt": { "FinInstnId": { "BICFI": "BNPAFRPP" } },
+                "RmtInf": { "Ustrd": ["Invoice", "42"] }
+            }
+        });
+
+        let details = PaymentDetails::from_payload(&payload);
+
+        assert_eq!(details.debtor_name.as_deref(), Some("Alice"));
+        assert_eq!(details.creditor_account.as_deref(), Some("FR1420041010050500013M02606"));
+        assert_eq!(details.creditor_agent.as_deref(), Some("BNPAFRPP"));
+        assert_eq!(details.amount, Some(125.5));
+        assert_eq!(details.currency.as_deref(), Some("EUR"));
+        assert_eq!(details.reference(), Some("INV-42"));
+        assert_eq!(details.remittance_information.as_deref(), Some("Invoice 42"));
+    }
+
+    #[test]
+    fn splits_batches_and_inherits_payment_level_parties() {
+        let payload = json!({
+            "PmtInf": [
+                {
+                    "DbtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } },
+                    "CdtTrfTxInf": [
+                        { "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 10.0 } } },
+                        { "Amt": { "InstdAmt": { "Ccy": "USD", "Value": 20.0 } }, "Cdtr": { "Nm": "Bob" } }
+                    ]
+                },
+                {
+                    "DbtrAcct": { "Id": { "IBAN": "GB82WEST12345698765432" } },
+                    "CdtTrfTxInf": { "Amt": { "InstdAmt": { "Ccy": "GBP", "Value": 30.0 } } }
+                }
+            ]
+        });
+
+        let found = transactions(&payload);
+
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[1].path, "/PmtInf/0/CdtTrfTxInf/1");
+        assert_eq!(found[1].details.currency.as_deref(), Some("USD"));
+        assert_eq!(found[1].details.creditor_name.as_deref(), Some("Bob"));
+        assert_eq!(found[2].details.debtor_account.as_deref(), Some("GB82WEST12345698765432"));
+        assert_eq!(found[2].locate_all("IBAN")[0].0, "/PmtInf/1/DbtrAcct/Id/IBAN");
+    }
+}
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// Subset of the ISO 20022 ExternalStatusReason1Code list used for both API
+// error responses and pacs.002 rejections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReasonCode {
+    AC01,
+    AC03,
+    AC04,
+    AC06,
+    AG01,
+    AG03,
+    AM01,
+    AM02,
+    AM03,
+    AM05,
+    AM12,
+    AM14,
+    CH16,
+    CH21,
+    DT01,
+    FF01,
+    FR01,
+    MS03,
+    NARR,
+    RC01,
+    RR04,
+}
+
+impl ReasonCode {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ReasonCode::AC01 => "AC01",
+            ReasonCode::AC03 => "AC03",
+            ReasonCode::AC04 => "AC04",
+            ReasonCode::AC06 => "AC06",
+            ReasonCode::AG01 => "AG01",
+            ReasonCode::AG03 => "AG03",
+            ReasonCode::AM01 => "AM01",
+            ReasonCode::AM02 => "AM02",
+            ReasonCode::AM03 => "AM03",
+            ReasonCode::AM05 => "AM05",
+            ReasonCode::AM12 => "AM12",
+            ReasonCode::AM14 => "AM14",
+            ReasonCode::CH16 => "CH16",
+            ReasonCode::CH21 => "CH21",
+            ReasonCode::DT01 => "DT01",
+            ReasonCode::FF01 => "FF01",
+            ReasonCode::FR01 => "FR01",
+            ReasonCode::MS03 => "MS03",
+            ReasonCode::NARR => "NARR",
+            ReasonCode::RC01 => "RC01",
+            ReasonCode::RR04 => "RR04",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ReasonCode::AC01 => "Incorrect account number",
+            ReasonCode::AC03 => "Invalid creditor account number",
+            ReasonCode::AC04 => "Closed account number",
+            ReasonCode::AC06 => "Blocked account",
+            ReasonCode::AG01 => "Transaction forbidden",
+            ReasonCode::AG03 => "Transaction not supported",
+            ReasonCode::AM01 => "Zero amount",
+            ReasonCode::AM02 => "Not allowed amount",
+            ReasonCode::AM03 => "Not allowed currency",
+            ReasonCode::AM05 => "Duplication",
+            ReasonCode::AM12 => "Invalid amount",
+            ReasonCode::AM14 => "Amount exceeds agreed limit",
+            ReasonCode::CH16 => "Element content formally incorrect",
+            ReasonCode::CH21 => "Required compulsory element missing",
+            ReasonCode::DT01 => "Invalid date",
+            ReasonCode::FF01 => "Invalid file format",
+            ReasonCode::FR01 => "Fraud",
+            ReasonCode::MS03 => "Reason not specified",
+            ReasonCode::NARR => "Narrative",
+            ReasonCode::RC01 => "Bank identifier incorrect",
+            ReasonCode::RR04 => "Regulatory reason",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xpath: Option<String>,
+    pub rule: String,
+    pub message: String,
+    pub reason_code: ReasonCode,
+}
+
+impl Violation {
+    pub fn new(rule: &str, pointer: Option<String>, reason_code: ReasonCode, message: impl Into<String>) -> Self {
+        Self {
+            xpath: pointer.as_deref().map(pointer_to_xpath),
+            pointer,
+            rule: rule.to_string(),
+            message: message.into(),
+            reason_code,
+        }
+    }
+
+    // pacs.002 StsRsnInf block for this violation.
+    pub fn status_reason_information(&self) -> Value {
+        let mut additional = vec![self.message.clone()];
+        if let Some(xpath) = &self.xpath {
+            additional.push(xpath.clone());
+        }
+        json!({
+            "Rsn": { "Cd": self.reason_code.code() },
+            "AddtlInf": additional
+                .iter()
+                .map(|line| line.chars().take(105).collect::<String>())
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+// "/CdtTrfTxInf/0/Dbtr/Nm" becomes "/CdtTrfTxInf[1]/Dbtr/Nm".
+pub fn pointer_to_xpath(pointer: &str) -> String {
+    let mut xpath = String::new();
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        match segment.parse::<usize>() {
+            Ok(index) if !xpath.is_empty() => xpath.push_str(&format!("[{}]", index + 1)),
+            _ => {
+                xpath.push('/');
+                xpath.push_str(&segment);
+            }
+        }
+    }
+    if xpath.is_empty() {
+        "/".to_string()
+    } else {
+        xpath
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_json_pointer_to_xpath() {
+        assert_eq!(pointer_to_xpath("/CdtTrfTxInf/0/Dbtr/Nm"), "/CdtTrfTxInf[1]/Dbtr/Nm");
+        assert_eq!(pointer_to_xpath(""), "/");
+    }
+
+    #[test]
+    fn serializes_reason_code_as_iso_code() {
+        let violation = Violation::new("identifier", Some("/DbtrAcct/Id/IBAN".to_string()), ReasonCode::AC01, "bad");
+        let json = serde_json::to_value(&violation).unwrap();
+
+        assert_eq!(json["reason_code"], "AC01");
+        assert_eq!(violation.status_reason_information()["Rsn"]["Cd"], "AC01");
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::Utc;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::domain::event::PaymentEventKind;
+use crate::domain::payment::{PaymentResponse, PaymentStatus, TransactionReference};
+use crate::domain::payment_details::{find, find_all};
+use crate::domain::status_reason::Violation;
+
+// The message a pacs.002 reports on.
+#[derive(Debug, Clone)]
+pub struct OriginalMessage {
+    pub message_id: String,
+    pub message_type: String,
+}
+
+// ISO 20022 ExternalPaymentTransactionStatus1Code for where intake left the
+// payment: accepted after technical validation, pending review, or rejected.
+pub fn transaction_status(status: PaymentStatus) -> &'static str {
+    match status {
+        PaymentStatus::Held | PaymentStatus::PendingApproval => "PDNG",
+        PaymentStatus::Rejected | PaymentStatus::Cancelled | PaymentStatus::Returned => "RJCT",
+        PaymentStatus::Sent | PaymentStatus::Settled => "ACSC",
+        PaymentStatus::Received | PaymentStatus::Validated | PaymentStatus::Accepted => "ACTC",
+    }
+}
+
+// One TxInfAndSts per transaction of the stored payment, each quoting the
+// identifiers the payment ended up with.
+pub fn accepted(original: &OriginalMessage, response: &PaymentResponse) -> Value {
+    let reasons: Vec<Value> = response
+        .flags
+        .iter()
+        .map(|flag| json!({ "AddtlInf": [flag.reason.chars().take(105).collect::<String>()] }))
+        .collect();
+    let status = transaction_status(response.status);
+    let mut references = response.transactions.clone();
+    if references.is_empty() {
+        references.push(TransactionReference::default());
+    }
+    let transactions = references
+        .into_iter()
+        .map(|mut reference| {
+            // Transactions without a PmtId of their own go under the payment's UETR.
+            reference.uetr.get_or_insert_with(|| response.uetr.to_string());
+            transaction(&reference, status, reasons.clone())
+        })
+        .collect();
+    report(original, transactions)
+}
+
+// The whole message is rejected, so every transaction in it is reported as
+// RJCT with the violations found inside it and those about the message as a
+// whole. `payload` is None when the message could not be parsed.
+pub fn rejected(original: &OriginalMessage, violations: &[Violation], payload: Option<&Value>) -> Value {
+    let references = payload.map(TransactionReference::all).unwrap_or_default();
+    let within = |reference: &TransactionReference, violation: &Violation| {
+        !reference.path.is_empty()
+            && violation
+                .pointer
+                .as_deref()
+                .is_some_and(|pointer| pointer == reference.path || pointer.starts_with(&format!("{}/", reference.path)))
+    };
+    let transactions = match references.as_slice() {
+        [] => vec![transaction(
+            &TransactionReference::default(),
+            "RJCT",
+            violations.iter().map(Violation::status_reason_information).collect(),
+        )],
+        references => references
+            .iter()
+            .map(|reference| {
+                let reasons = violations
+                    .iter()
+                    .filter(|violation| {
+                        within(reference, violation) || !references.iter().any(|other| within(other, violation))
+                    })
+                    .map(Violation::status_reason_information)
+                    .collect();
+                transaction(reference, "RJCT", reasons)
+            })
+            .collect(),
+    };
+    report(original, transactions)
+}
+
+// The transactions of a pacs.002 received from the next party, each with the
+// UETR it reports on when it quotes one. A report without TxInfAndSts is
+// taken as one status for the whole original message.
+pub fn received(report: &Value) -> Vec<(Option<Uuid>, PaymentEventKind)> {
+    let text = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+    let message_id = text(report.get("GrpHdr").and_then(|header| header.get("MsgId")));
+    let group_status = text(report.get("OrgnlGrpInfAndSts").and_then(|group| group.get("GrpSts")));
+
+    let mut transactions: Vec<&Value> = find_all(report, "TxInfAndSts")
+        .into_iter()
+        .flat_map(|found| match found {
+            Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        })
+        .collect();
+    if transactions.is_empty() {
+        transactions.push(report);
+    }
+    transactions
+        .into_iter()
+        .map(|transaction| {
+            let reason = find(transaction, "StsRsnInf").map(|reasons| match reasons {
+                Value::Array(items) => items.first().unwrap_or(reasons),
+                other => other,
+            });
+            let additional_information = reason.and_then(|reason| match reason.get("AddtlInf") {
+                Some(Value::Array(lines)) => Some(lines.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" ")),
+                other => text(other),
+            });
+            let uetr = text(transaction.get("OrgnlUETR")).and_then(|uetr| Uuid::parse_str(&uetr).ok());
+            let event = PaymentEventKind::StatusReportReceived {
+                message_id: message_id.clone(),
+                transaction_status: text(transaction.get("TxSts"))
+                    .or_else(|| group_status.clone())
+                    .unwrap_or_else(|| "NOTPROVIDED".to_string()),
+                reason_code: reason.and_then(|reason| text(find(reason, "Cd"))),
+                additional_information,
+            };
+            (uetr, event)
+        })
+        .collect()
+}
+
+fn transaction(reference: &TransactionReference, status: &str, reasons: Vec<Value>) -> Value {
+    let mut transaction = json!({ "TxSts": status });
+    if let Some(end_to_end_id) = &reference.end_to_end_id {
+        transaction["OrgnlEndToEndId"] = json!(end_to_end_id);
+    }
+    if let Some(transaction_id) = &reference.transaction_id {
+        transaction["OrgnlTxId"] = json!(transaction_id);
+    }
+    if let Some(uetr) = &reference.uetr {
+        transaction["OrgnlUETR"] = json!(uetr);
+    }
+    if !reasons.is_empty() {
+        transaction["StsRsnInf"] = Value::Array(reasons);
+    }
+    transaction
+}
+
+fn report(original: &OriginalMessage, transactions: Vec<Value>) -> Value {
+    json!({
+        "GrpHdr": {
+            "MsgId": Uuid::new_v4().simple().to_string(),
+            "CreDtTm": Utc::now().to_rfc3339(),
+        },
+        "OrgnlGrpInfAndSts": {
+            "OrgnlMsgId": original.message_id,
+            "OrgnlMsgNmId": original.message_type,
+        },
+        "TxInfAndSts": transactions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::status_reason::ReasonCode;
+
+    #[test]
+    fn rejection_carries_every_violation() {
+        let original = OriginalMessage {
+            message_id: "MSG-1".to_string(),
+            message_type: "pacs.008".to_string(),
+        };
+        let violations = vec![
+            Violation::new("identifier", Some("/DbtrAcct/Id/IBAN".to_string()), ReasonCode::AC01, "bad IBAN"),
+            Violation::new("amount", None, ReasonCode::AM12, "bad amount"),
+        ];
+
+        let report = rejected(&original, &violations, None);
+        let transaction = &report["TxInfAndSts"][0];
+        assert_eq!(report["OrgnlGrpInfAndSts"]["OrgnlMsgId"], "MSG-1");
+        assert_eq!(transaction["TxSts"], "RJCT");
+        assert_eq!(transaction["StsRsnInf"][0]["Rsn"]["Cd"], "AC01");
+        assert_eq!(transaction["StsRsnInf"][1]["Rsn"]["Cd"], "AM12");
+    }
+
+    #[test]
+    fn reads_every_transaction_of_a_received_report() {
+        let uetr = Uuid::new_v4();
+        let report = json!({
+            "GrpHdr": { "MsgId": "STS-1" },
+            "OrgnlGrpInfAndSts": { "OrgnlMsgId": "MSG-1", "GrpSts": "PART" },
+            "TxInfAndSts": [
+                { "OrgnlUETR": uetr.to_string(), "TxSts": "ACSC" },
+                {
+                    "OrgnlEndToEndId": "E2E-2",
+                    "TxSts": "RJCT",
+                    "StsRsnInf": [{ "Rsn": { "Cd": "AC04" }, "AddtlInf": ["Account", "closed"] }]
+                }
+            ]
+        });
+
+        let statuses = received(&report);
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].0, Some(uetr));
+        assert_eq!(statuses[1].0, None);
+        match &statuses[1].1 {
+            PaymentEventKind::StatusReportReceived {
+                message_id,
+                transaction_status,
+                reason_code,
+                additional_information,
+            } => {
+                assert_eq!(message_id.as_deref(), Some("STS-1"));
+                assert_eq!(transaction_status, "RJCT");
+                assert_eq!(reason_code.as_deref(), Some("AC04"));
+                assert_eq!(additional_information.as_deref(), Some("Account closed"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use serde_json::Value;
+use uuid::{Uuid, Version};
+
+use crate::error::ValidationError;
+
+// ISO 20022 carries the UETR inside every PmtId block; status reports and
+// cancellation requests reference the original one via OrgnlUETR.
+const PAYMENT_ID_ELEMENT: &str = "PmtId";
+const UETR_ELEMENT: &str = "UETR";
+
+pub fn generate() -> Uuid {
+    Uuid::new_v4()
+}
+
+pub fn parse(value: &str) -> Result<Uuid, ValidationError> {
+    let uetr = Uuid::parse_str(value)
+        .map_err(|_| ValidationError::SchemaViolation(format!("UETR {} is not a valid UUID", value)))?;
+    ensure_v4(&uetr)?;
+    Ok(uetr)
+}
+
+pub fn ensure_v4(uetr: &Uuid) -> Result<(), ValidationError> {
+    if uetr.get_version() != Some(Version::Random) {
+        return Err(ValidationError::SchemaViolation(format!("UETR {} is not a UUIDv4", uetr)));
+    }
+    Ok(())
+}
+
+// The first UETR in the message, which the payment is known by. Every
+// transaction has its own UETR, so one may not appear twice.
+pub fn extract(payload: &Value) -> Result<Option<Uuid>, ValidationError> {
+    let mut found: Vec<Uuid> = Vec::new();
+    for candidate in collect(payload) {
+        let uetr = parse(candidate)?;
+        if found.contains(&uetr) {
+            return Err(ValidationError::SchemaViolation(format!(
+                "UETR {} is used by more than one transaction",
+                uetr
+            )));
+        }
+        found.push(uetr);
+    }
+    Ok(found.first().copied())
+}
+
+// Gives every PmtId without a UETR one of its own: `uetr` for the first, if
+// the message does not carry it already, and fresh ones for the rest.
+pub fn stamp(payload: &mut Value, uetr: &Uuid) {
+    let mut next = if collect(payload).contains(&uetr.to_string().as_str()) {
+        None
+    } else {
+        Some(*uetr)
+    };
+    stamp_missing(payload, &mut || next.take().unwrap_or_else(generate));
+}
+
+fn stamp_missing(payload: &mut Value, issue: &mut dyn FnMut() -> Uuid) {
+    match payload {
+        Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                if key == PAYMENT_ID_ELEMENT {
+                    if let Value::Object(payment_id) = value {
+                        if !payment_id.contains_key(UETR_ELEMENT) {
+                            payment_id.insert(UETR_ELEMENT.to_string(), Value::String(issue().to_string()));
+                        }
+                    }
+                } else {
+                    stamp_missing(value, issue);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| stamp_missing(item, issue)),
+        _ => {}
+    }
+}
+
+fn collect(payload: &Value) -> Vec<&str> {
+    let mut values = Vec::new();
+    match payload {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                if key == PAYMENT_ID_ELEMENT {
+                    if let Some(Value::String(uetr)) = value.get(UETR_ELEMENT) {
+                        values.push(uetr.as_str());
+                    }
+                } else {
+                    values.extend(collect(value));
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| values.extend(collect(item))),
+        _ => {}
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stamps_a_distinct_uetr_into_every_transaction() {
+        let uetr = generate();
+        let kept = generate();
+        let mut payload = json!({
+            "CdtTrfTxInf": [
+                { "PmtId": { "EndToEndId": "E2E-1" } },
+                { "PmtId": { "EndToEndId": "E2E-2" } },
+                { "PmtId": { "EndToEndId": "E2E-3", "UETR": kept.to_string() } }
+            ]
+        });
+
+        stamp(&mut payload, &uetr);
+
+        let transactions = &payload["CdtTrfTxInf"];
+        assert_eq!(extract(&payload).unwrap(), Some(uetr));
+        assert_eq!(transactions[0]["PmtId"]["UETR"], uetr.to_string());
+        assert_eq!(transactions[2]["PmtId"]["UETR"], kept.to_string());
+        let second = parse(transactions[1]["PmtId"]["UETR"].as_str().unwrap()).unwrap();
+        assert!(second != uetr && second != kept);
+    }
+
+    #[test]
+    fn keeps_distinct_uetrs_and_rejects_repeated_ones() {
+        let (first, second) = (generate(), generate());
+        let payload = json!({
+            "CdtTrfTxInf": [
+                { "PmtId": { "UETR": first.to_string() } },
+                { "PmtId": { "UETR": second.to_string() } }
+            ]
+        });
+        assert_eq!(extract(&payload).unwrap(), Some(first));
+
+        let repeated = json!({
+            "CdtTrfTxInf": [
+                { "PmtId": { "UETR": first.to_string() } },
+                { "PmtId": { "UETR": first.to_string() } }
+            ]
+        });
+        assert!(extract(&repeated).is_err());
+    }
+
+    #[test]
+    fn rejects_non_v4_uetr() {
+        assert!(parse("00000000-0000-1000-8000-000000000000").is_err());
+    }
+}
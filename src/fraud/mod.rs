@@ -0,0 +1,5 @@
+pub mod rules_scorer;
+pub mod scorer;
+
+pub use rules_scorer::RulesBasedScorer;
+pub use scorer::{FraudScore, FraudScorer, FraudSettings};
@@ -0,0 +1,250 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Timelike, Utc};
+use serde::Deserialize;
+
+use crate::domain::payment::{Payment, PaymentRequest, PaymentStatus};
+use crate::domain::payment_details::{transactions, PaymentDetails};
+use crate::error::FraudError;
+use crate::fraud::scorer::{FraudScore, FraudScorer};
+use crate::infrastructure::database::fraud_profiles::SenderProfileRepository;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RulesScorerSettings {
+    #[serde(default = "default_new_beneficiary_weight")]
+    pub new_beneficiary_weight: f64,
+    #[serde(default = "default_unusual_amount_weight")]
+    pub unusual_amount_weight: f64,
+    // An amount is unusual above this multiple of the sender's average.
+    #[serde(default = "default_unusual_amount_factor")]
+    pub unusual_amount_factor: f64,
+    // Payments needed before the sender's average is trusted.
+    #[serde(default = "default_min_history")]
+    pub min_history: u64,
+    #[serde(default = "default_unusual_hour_weight")]
+    pub unusual_hour_weight: f64,
+    // UTC hours, start inclusive and end exclusive; may wrap past midnight.
+    #[serde(default)]
+    pub unusual_hours_start: u32,
+    #[serde(default = "default_unusual_hours_end")]
+    pub unusual_hours_end: u32,
+}
+
+fn default_new_beneficiary_weight() -> f64 {
+    30.0
+}
+
+fn default_unusual_amount_weight() -> f64 {
+    40.0
+}
+
+fn default_unusual_amount_factor() -> f64 {
+    3.0
+}
+
+fn default_min_history() -> u64 {
+    5
+}
+
+fn default_unusual_hour_weight() -> f64 {
+    20.0
+}
+
+fn default_unusual_hours_end() -> u32 {
+    6
+}
+
+impl Default for RulesScorerSettings {
+    fn default() -> Self {
+        Self {
+            new_beneficiary_weight: default_new_beneficiary_weight(),
+            unusual_amount_weight: default_unusual_amount_weight(),
+            unusual_amount_factor: default_unusual_amount_factor(),
+            min_history: default_min_history(),
+            unusual_hour_weight: default_unusual_hour_weight(),
+            unusual_hours_start: 0,
+            unusual_hours_end: default_unusual_hours_end(),
+        }
+    }
+}
+
+impl RulesScorerSettings {
+    fn is_unusual_hour(&self, hour: u32) -> bool {
+        if self.unusual_hours_start <= self.unusual_hours_end {
+            hour >= self.unusual_hours_start && hour < self.unusual_hours_end
+        } else {
+            hour >= self.unusual_hours_start || hour < self.unusual_hours_end
+        }
+    }
+}
+
+// Profiles live behind a repository so every instance scores against the
+// same sender history and it survives restarts.
+pub struct RulesBasedScorer {
+    settings: RulesScorerSettings,
+    profiles: Box<dyn SenderProfileRepository>,
+}
+
+impl RulesBasedScorer {
+    pub fn new(settings: RulesScorerSettings, profiles: Box<dyn SenderProfileRepository>) -> Self {
+        Self { settings, profiles }
+    }
+
+    // Every transaction of a batch is considered: any new beneficiary and the
+    // largest amount count, each indicator once per request.
+    pub async fn score_at(&self, request: &PaymentRequest, now: DateTime<Utc>) -> Result<FraudScore, FraudError> {
+        let transactions: Vec<PaymentDetails> = transactions(&request.message_payload)
+            .into_iter()
+            .map(|transaction| transaction.details)
+            .collect();
+        let asked: Vec<String> = transactions.iter().filter_map(beneficiary).map(str::to_string).collect();
+        let profile = self
+            .profiles
+            .profile(&request.sender_id, &asked)
+            .await
+            .map_err(|e| FraudError::Unavailable(e.to_string()))?;
+        let profile = profile.as_ref();
+
+        let mut score = 0.0;
+        let mut reasons = Vec::new();
+
+        let mut new_beneficiaries: Vec<&str> = Vec::new();
+        for beneficiary in transactions.iter().filter_map(beneficiary) {
+            if profile.is_none_or(|profile| !profile.beneficiaries.contains(beneficiary))
+                && !new_beneficiaries.contains(&beneficiary)
+            {
+                new_beneficiaries.push(beneficiary);
+            }
+        }
+        if !new_beneficiaries.is_empty() {
+            score += self.settings.new_beneficiary_weight;
+            reasons.push(format!("first payment to beneficiary {}", new_beneficiaries.join(", ")));
+        }
+
+        let largest = transactions.iter().filter_map(|details| details.amount).reduce(f64::max);
+        if let (Some(amount), Some(profile)) = (largest, profile) {
+            let limit = profile.mean_amount * self.settings.unusual_amount_factor;
+            if profile.payments >= self.settings.min_history && amount > limit {
+                score += self.settings.unusual_amount_weight;
+                reasons.push(format!(
+                    "amount {:.2} is more than {} times the sender's average of {:.2}",
+                    amount, self.settings.unusual_amount_factor, profile.mean_amount
+                ));
+            }
+        }
+
+        if self.settings.is_unusual_hour(now.hour()) {
+            score += self.settings.unusual_hour_weight;
+            reasons.push(format!("submitted at an unusual hour ({:02}:00 UTC)", now.hour()));
+        }
+
+        Ok(FraudScore {
+            score: score.min(100.0),
+            reasons,
+        })
+    }
+}
+
+#[async_trait]
+impl FraudScorer for RulesBasedScorer {
+    async fn score(&self, request: &PaymentRequest) -> Result<FraudScore, FraudError> {
+        self.score_at(request, Utc::now()).await
+    }
+
+    async fn record(&self, payment: &Payment) -> Result<(), FraudError> {
+        if payment.status == PaymentStatus::Rejected {
+            return Ok(());
+        }
+
+        let mut beneficiaries = Vec::new();
+        let mut amounts = Vec::new();
+        for transaction in transactions(&payment.request.message_payload) {
+            let details = transaction.details;
+            if let Some(beneficiary) = beneficiary(&details) {
+                beneficiaries.push(beneficiary.to_string());
+            }
+            amounts.extend(details.amount);
+        }
+        self.profiles
+            .record(&payment.request.sender_id, &beneficiaries, &amounts)
+            .await
+            .map_err(|e| FraudError::Unavailable(e.to_string()))
+    }
+}
+
+fn beneficiary(details: &PaymentDetails) -> Option<&str> {
+    details.creditor_account.as_deref().or(details.creditor_name.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::PaymentType;
+    use crate::infrastructure::database::fraud_profiles::InMemorySenderProfileRepository;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn request(amount: f64, creditor_iban: &str) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::RealTimePayment,
+            message_payload: json!({
+                "CdtTrfTxInf": {
+                    "IntrBkSttlmAmt": { "Ccy": "EUR", "Value": amount },
+                    "CdtrAcct": { "Id": { "IBAN": creditor_iban } }
+                }
+            }),
+            sender_id: "sender".to_string(),
+            request_id: Uuid::new_v4().to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn scores_new_beneficiary_unusual_amount_and_hour() {
+        let scorer = RulesBasedScorer::new(RulesScorerSettings::default(), Box::new(InMemorySenderProfileRepository::new()));
+        for _ in 0..5 {
+            let payment = Payment::new(request(100.0, "DE89370400440532013000"), Uuid::new_v4(), Vec::new(), Vec::new());
+            scorer.record(&payment).await.unwrap();
+        }
+        let midday = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let night = Utc.with_ymd_and_hms(2024, 3, 1, 3, 0, 0).unwrap();
+
+        let usual = scorer.score_at(&request(120.0, "DE89370400440532013000"), midday).await.unwrap();
+        assert_eq!(usual.score, 0.0);
+
+        let risky = scorer.score_at(&request(5_000.0, "FR1420041010050500013M02606"), night).await.unwrap();
+        assert_eq!(risky.score, 90.0);
+        assert_eq!(risky.reasons.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn scores_every_transaction_of_a_batch() {
+        let scorer = RulesBasedScorer::new(RulesScorerSettings::default(), Box::new(InMemorySenderProfileRepository::new()));
+        for _ in 0..5 {
+            let payment = Payment::new(request(100.0, "DE89370400440532013000"), Uuid::new_v4(), Vec::new(), Vec::new());
+            scorer.record(&payment).await.unwrap();
+        }
+        let midday = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut batch = request(100.0, "DE89370400440532013000");
+        batch.message_payload = json!({
+            "CdtTrfTxInf": [
+                {
+                    "IntrBkSttlmAmt": { "Ccy": "EUR", "Value": 100.0 },
+                    "CdtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } }
+                },
+                {
+                    "IntrBkSttlmAmt": { "Ccy": "EUR", "Value": 5_000.0 },
+                    "CdtrAcct": { "Id": { "IBAN": "FR1420041010050500013M02606" } }
+                }
+            ]
+        });
+
+        let scored = scorer.score_at(&batch, midday).await.unwrap();
+
+        assert_eq!(scored.score, 70.0);
+        assert!(scored.reasons[0].contains("FR1420041010050500013M02606"));
+    }
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::domain::payment::{FraudAssessment, FraudDecision, Payment, PaymentRequest};
+use crate::error::FraudError;
+use crate::fraud::rules_scorer::RulesScorerSettings;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FraudSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_review_threshold")]
+    pub review_threshold: f64,
+    #[serde(default = "default_reject_threshold")]
+    pub reject_threshold: f64,
+    // Decision applied when the scorer misses its time budget.
+    #[serde(default = "default_fallback")]
+    pub on_timeout: FraudDecision,
+    // Decision applied when the scorer fails outright.
+    #[serde(default = "default_fallback")]
+    pub on_error: FraudDecision,
+    #[serde(default)]
+    pub rules: RulesScorerSettings,
+}
+
+fn default_timeout_ms() -> u64 {
+    200
+}
+
+fn default_review_threshold() -> f64 {
+    50.0
+}
+
+fn default_reject_threshold() -> f64 {
+    80.0
+}
+
+fn default_fallback() -> FraudDecision {
+    FraudDecision::Review
+}
+
+impl Default for FraudSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_ms: default_timeout_ms(),
+            review_threshold: default_review_threshold(),
+            reject_threshold: default_reject_threshold(),
+            on_timeout: default_fallback(),
+            on_error: default_fallback(),
+            rules: RulesScorerSettings::default(),
+        }
+    }
+}
+
+impl FraudSettings {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+
+    pub fn fallback(&self, error: &FraudError) -> FraudDecision {
+        match error {
+            FraudError::Timeout(_) => self.on_timeout,
+            FraudError::Unavailable(_) => self.on_error,
+        }
+    }
+
+    pub fn decision(&self, score: f64) -> FraudDecision {
+        if score >= self.reject_threshold {
+            FraudDecision::Reject
+        } else if score >= self.review_threshold {
+            FraudDecision::Review
+        } else {
+            FraudDecision::Accept
+        }
+    }
+}
+
+// Scores run from 0 (no risk indicators) to 100.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FraudScore {
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+#[async_trait]
+pub trait FraudScorer: Send + Sync {
+    async fn score(&self, request: &PaymentRequest) -> Result<FraudScore, FraudError>;
+
+    // Feeds accepted payments back so behavioural scorers can learn what is
+    // normal for a sender.
+    async fn record(&self, _payment: &Payment) -> Result<(), FraudError> {
+        Ok(())
+    }
+}
+
+pub async fn assess(scorer: &dyn FraudScorer, settings: &FraudSettings, request: &PaymentRequest) -> FraudAssessment {
+    let result = match tokio::time::timeout(settings.timeout(), scorer.score(request)).await {
+        Ok(result) => result,
+        Err(_) => Err(FraudError::Timeout(settings.timeout_ms)),
+    };
+
+    match result {
+        Ok(scored) => {
+            let score = scored.score.clamp(0.0, 100.0);
+            FraudAssessment {
+                score: Some(score),
+                decision: settings.decision(score),
+                reasons: scored.reasons,
+            }
+        }
+        Err(e) => {
+            let decision = settings.fallback(&e);
+            warn!(request_id = %request.request_id, "Fraud scoring fell back to {:?}: {}", decision, e);
+            FraudAssessment {
+                score: None,
+                decision,
+                reasons: vec![e.to_string()],
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::PaymentType;
+    use serde_json::json;
+
+    struct SlowScorer;
+
+    #[async_trait]
+    impl FraudScorer for SlowScorer {
+        async fn score(&self, _request: &PaymentRequest) -> Result<FraudScore, FraudError> {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(FraudScore {
+                score: 0.0,
+                reasons: Vec::new(),
+            })
+        }
+    }
+
+    struct FailingScorer;
+
+    #[async_trait]
+    impl FraudScorer for FailingScorer {
+        async fn score(&self, _request: &PaymentRequest) -> Result<FraudScore, FraudError> {
+            Err(FraudError::Unavailable("model server down".to_string()))
+        }
+    }
+
+    fn request() -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::RealTimePayment,
+            message_payload: json!({}),
+            sender_id: "sender".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_fallback_when_budget_is_exceeded() {
+        let settings = FraudSettings {
+            enabled: true,
+            timeout_ms: 5,
+            on_timeout: FraudDecision::Reject,
+            ..FraudSettings::default()
+        };
+
+        let assessment = assess(&SlowScorer, &settings, &request()).await;
+
+        assert_eq!(assessment.score, None);
+        assert_eq!(assessment.decision, FraudDecision::Reject);
+    }
+
+    #[tokio::test]
+    async fn scorer_failures_use_their_own_fallback() {
+        let settings = FraudSettings {
+            enabled: true,
+            on_timeout: FraudDecision::Accept,
+            on_error: FraudDecision::Reject,
+            ..FraudSettings::default()
+        };
+
+        let assessment = assess(&FailingScorer, &settings, &request()).await;
+
+        assert_eq!(assessment.decision, FraudDecision::Reject);
+    }
+
+    #[test]
+    fn maps_scores_onto_thresholds() {
+        let settings = FraudSettings::default();
+
+        assert_eq!(settings.decision(10.0), FraudDecision::Accept);
+        assert_eq!(settings.decision(50.0), FraudDecision::Review);
+        assert_eq!(settings.decision(95.0), FraudDecision::Reject);
+    }
+}
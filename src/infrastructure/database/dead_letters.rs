@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::domain::dead_letter::{DeadLetter, DeadLetterAction, DeadLetterQuery, DeadLetterStatus};
+use crate::error::RepositoryError;
+
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync {
+    async fn capture(&self, letter: DeadLetter) -> Result<(), RepositoryError>;
+    // Oldest first.
+    async fn list(&self, query: &DeadLetterQuery) -> Result<Vec<DeadLetter>, RepositoryError>;
+    async fn get(&self, id: &Uuid) -> Result<Option<DeadLetter>, RepositoryError>;
+    async fn record_action(&self, action: DeadLetterAction) -> Result<(), RepositoryError>;
+    // Moves the letter from `from` to `to` and records the action that did
+    // it, if any, atomically. Returns false when it was no longer in `from`.
+    async fn transition(
+        &self,
+        id: &Uuid,
+        from: DeadLetterStatus,
+        to: DeadLetterStatus,
+        action: Option<DeadLetterAction>,
+    ) -> Result<bool, RepositoryError>;
+    async fn resolve(
+        &self,
+        id: &Uuid,
+        status: DeadLetterStatus,
+        action: DeadLetterAction,
+    ) -> Result<bool, RepositoryError> {
+        self.transition(id, DeadLetterStatus::Pending, status, Some(action)).await
+    }
+    async fn actions(&self, id: &Uuid) -> Result<Vec<DeadLetterAction>, RepositoryError>;
+}
+
+#[derive(Default)]
+pub struct InMemoryDeadLetterStore {
+    letters: Mutex<Vec<DeadLetter>>,
+    actions: Mutex<Vec<DeadLetterAction>>,
+}
+
+impl InMemoryDeadLetterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for InMemoryDeadLetterStore {
+    async fn capture(&self, letter: DeadLetter) -> Result<(), RepositoryError> {
+        self.letters.lock().await.push(letter);
+        Ok(())
+    }
+
+    async fn list(&self, query: &DeadLetterQuery) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let mut letters: Vec<DeadLetter> = self
+            .letters
+            .lock()
+            .await
+            .iter()
+            .filter(|letter| query.queue.as_ref().is_none_or(|queue| &letter.queue == queue))
+            .filter(|letter| query.status.is_none_or(|status| letter.status == status))
+            .cloned()
+            .collect();
+        letters.sort_by_key(|letter| letter.dead_lettered_at);
+        Ok(letters)
+    }
+
+    async fn get(&self, id: &Uuid) -> Result<Option<DeadLetter>, RepositoryError> {
+        Ok(self.letters.lock().await.iter().find(|letter| letter.id == *id).cloned())
+    }
+
+    async fn record_action(&self, action: DeadLetterAction) -> Result<(), RepositoryError> {
+        self.actions.lock().await.push(action);
+        Ok(())
+    }
+
+    async fn transition(
+        &self,
+        id: &Uuid,
+        from: DeadLetterStatus,
+        to: DeadLetterStatus,
+        action: Option<DeadLetterAction>,
+    ) -> Result<bool, RepositoryError> {
+        let mut letters = self.letters.lock().await;
+        let letter = letters
+            .iter_mut()
+            .find(|letter| letter.id == *id)
+            .ok_or(RepositoryError::NotFound(*id))?;
+        if letter.status != from {
+            return Ok(false);
+        }
+        letter.status = to;
+        if let Some(action) = action {
+            self.actions.lock().await.push(action);
+        }
+        Ok(true)
+    }
+
+    async fn actions(&self, id: &Uuid) -> Result<Vec<DeadLetterAction>, RepositoryError> {
+        let mut actions: Vec<DeadLetterAction> = self
+            .actions
+            .lock()
+            .await
+            .iter()
+            .filter(|action| action.dead_letter_id == *id)
+            .cloned()
+            .collect();
+        actions.sort_by_key(|action| action.at);
+        Ok(actions)
+    }
+}
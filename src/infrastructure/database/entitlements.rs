@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::domain::entitlement::AccountEntitlement;
+use crate::error::RepositoryError;
+
+#[async_trait]
+pub trait EntitlementRepository: Send + Sync {
+    async fn list_for_sender(&self, sender_id: &str) -> Result<Vec<AccountEntitlement>, RepositoryError>;
+    async fn find(&self, sender_id: &str, account: &str) -> Result<Option<AccountEntitlement>, RepositoryError>;
+    async fn upsert(&self, entitlement: AccountEntitlement) -> Result<(), RepositoryError>;
+    async fn revoke(&self, sender_id: &str, account: &str) -> Result<bool, RepositoryError>;
+}
+
+#[derive(Default)]
+pub struct InMemoryEntitlementRepository {
+    entitlements: Mutex<HashMap<(String, String), AccountEntitlement>>,
+}
+
+impl InMemoryEntitlementRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl EntitlementRepository for InMemoryEntitlementRepository {
+    async fn list_for_sender(&self, sender_id: &str) -> Result<Vec<AccountEntitlement>, RepositoryError> {
+        let entitlements = self.entitlements.lock().await;
+        let mut found: Vec<AccountEntitlement> = entitlements
+            .values()
+            .filter(|entitlement| entitlement.sender_id == sender_id)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.account.cmp(&b.account));
+        Ok(found)
+    }
+
+    async fn find(&self, sender_id: &str, account: &str) -> Result<Option<AccountEntitlement>, RepositoryError> {
+        let entitlements = self.entitlements.lock().await;
+        Ok(entitlements.get(&(sender_id.to_string(), account.to_string())).cloned())
+    }
+
+    async fn upsert(&self, entitlement: AccountEntitlement) -> Result<(), RepositoryError> {
+        let key = (entitlement.sender_id.clone(), entitlement.account.clone());
+        self.entitlements.lock().await.insert(key, entitlement);
+        Ok(())
+    }
+
+    async fn revoke(&self, sender_id: &str, account: &str) -> Result<bool, RepositoryError> {
+        let key = (sender_id.to_string(), account.to_string());
+        Ok(self.entitlements.lock().await.remove(&key).is_some())
+    }
+}
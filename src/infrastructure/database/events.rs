@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::domain::event::{PaymentEvent, PaymentEventKind};
+use crate::error::RepositoryError;
+
+// Append-only: events are never updated or removed once stored.
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    // Stores the events after the payment's existing ones, in the order given,
+    // and returns them with their ids and sequence numbers.
+    async fn append(
+        &self,
+        payment_id: &Uuid,
+        occurred_at: DateTime<Utc>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<Vec<PaymentEvent>, RepositoryError>;
+    async fn events(&self, payment_id: &Uuid) -> Result<Vec<PaymentEvent>, RepositoryError>;
+}
+
+#[derive(Default)]
+pub struct InMemoryEventStore {
+    events: Mutex<HashMap<Uuid, Vec<PaymentEvent>>>,
+}
+
+impl InMemoryEventStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl EventStore for InMemoryEventStore {
+    async fn append(
+        &self,
+        payment_id: &Uuid,
+        occurred_at: DateTime<Utc>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<Vec<PaymentEvent>, RepositoryError> {
+        let mut store = self.events.lock().await;
+        let stored = store.entry(*payment_id).or_default();
+        let appended: Vec<PaymentEvent> = events
+            .into_iter()
+            .enumerate()
+            .map(|(offset, kind)| PaymentEvent {
+                id: Uuid::new_v4(),
+                payment_id: *payment_id,
+                sequence: (stored.len() + offset + 1) as u64,
+                occurred_at,
+                kind,
+            })
+            .collect();
+        stored.extend(appended.iter().cloned());
+        Ok(appended)
+    }
+
+    async fn events(&self, payment_id: &Uuid) -> Result<Vec<PaymentEvent>, RepositoryError> {
+        Ok(self.events.lock().await.get(payment_id).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn numbers_events_per_payment() {
+        let store = InMemoryEventStore::new();
+        let payment_id = Uuid::new_v4();
+        let published = |key: &str| PaymentEventKind::Published {
+            routing_key: key.to_string(),
+        };
+
+        store
+            .append(&payment_id, Utc::now(), vec![published("payment.received"), published("payment.held")])
+            .await
+            .unwrap();
+        let appended = store.append(&payment_id, Utc::now(), vec![published("payment.rejected")]).await.unwrap();
+        store.append(&Uuid::new_v4(), Utc::now(), vec![published("payment.received")]).await.unwrap();
+
+        assert_eq!(appended[0].sequence, 3);
+        let events = store.events(&payment_id).await.unwrap();
+        assert_eq!(events.iter().map(|event| event.sequence).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+}
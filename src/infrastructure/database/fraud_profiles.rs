@@ -0,0 +1,61 @@
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::error::RepositoryError;
+
+// What the fraud rules know about a sender. A lookup only returns the
+// beneficiaries it asked about, so scoring never loads a sender's history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SenderProfile {
+    pub beneficiaries: HashSet<String>,
+    pub payments: u64,
+    pub mean_amount: f64,
+}
+
+#[async_trait]
+pub trait SenderProfileRepository: Send + Sync {
+    // None when nothing has been recorded for the sender yet.
+    async fn profile(&self, sender_id: &str, beneficiaries: &[String]) -> Result<Option<SenderProfile>, RepositoryError>;
+    // Adds a stored payment's beneficiaries and transaction amounts.
+    async fn record(&self, sender_id: &str, beneficiaries: &[String], amounts: &[f64]) -> Result<(), RepositoryError>;
+}
+
+#[derive(Default)]
+pub struct InMemorySenderProfileRepository {
+    profiles: Mutex<HashMap<String, SenderProfile>>,
+}
+
+impl InMemorySenderProfileRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SenderProfileRepository for InMemorySenderProfileRepository {
+    async fn profile(&self, sender_id: &str, beneficiaries: &[String]) -> Result<Option<SenderProfile>, RepositoryError> {
+        let profiles = self.profiles.lock().await;
+        Ok(profiles.get(sender_id).map(|profile| SenderProfile {
+            beneficiaries: beneficiaries
+                .iter()
+                .filter(|beneficiary| profile.beneficiaries.contains(*beneficiary))
+                .cloned()
+                .collect(),
+            payments: profile.payments,
+            mean_amount: profile.mean_amount,
+        }))
+    }
+
+    async fn record(&self, sender_id: &str, beneficiaries: &[String], amounts: &[f64]) -> Result<(), RepositoryError> {
+        let mut profiles = self.profiles.lock().await;
+        let profile = profiles.entry(sender_id.to_string()).or_default();
+        profile.beneficiaries.extend(beneficiaries.iter().cloned());
+        for amount in amounts {
+            profile.payments += 1;
+            profile.mean_amount += (amount - profile.mean_amount) / profile.payments as f64;
+        }
+        Ok(())
+    }
+}
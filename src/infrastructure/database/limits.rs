@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::error::RepositoryError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitPeriod {
+    Day,
+    Month,
+}
+
+// One usage counter: a limit rule, the sender or account it is applied to,
+// and the first day of the period being counted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UsageKey {
+    pub rule: String,
+    pub subject: String,
+    pub period: LimitPeriod,
+    pub period_start: NaiveDate,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Usage {
+    pub amount: Decimal,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct LimitCharge {
+    pub key: UsageKey,
+    pub amount: Decimal,
+    // Payments the charge counts, one per transaction.
+    pub count: u64,
+    pub max_amount: Option<Decimal>,
+    pub max_count: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LimitBreach {
+    pub charge: LimitCharge,
+    pub used: Usage,
+}
+
+#[async_trait]
+pub trait LimitRepository: Send + Sync {
+    // All-or-nothing: either every charge fits and all counters move, or none
+    // do and the first breached charge is returned.
+    async fn reserve(&self, charges: &[LimitCharge]) -> Result<Option<LimitBreach>, RepositoryError>;
+    async fn release(&self, charges: &[LimitCharge]) -> Result<(), RepositoryError>;
+    async fn usage(&self, key: &UsageKey) -> Result<Usage, RepositoryError>;
+}
+
+#[derive(Default)]
+pub struct InMemoryLimitRepository {
+    usage: Mutex<HashMap<UsageKey, Usage>>,
+}
+
+impl InMemoryLimitRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LimitRepository for InMemoryLimitRepository {
+    async fn reserve(&self, charges: &[LimitCharge]) -> Result<Option<LimitBreach>, RepositoryError> {
+        let mut usage = self.usage.lock().await;
+
+        for charge in charges {
+            let used = usage.get(&charge.key).copied().unwrap_or_default();
+            let amount_exceeded = charge.max_amount.is_some_and(|max| used.amount + charge.amount > max);
+            let count_exceeded = charge.max_count.is_some_and(|max| used.count + charge.count > max);
+            if amount_exceeded || count_exceeded {
+                return Ok(Some(LimitBreach {
+                    charge: charge.clone(),
+                    used,
+                }));
+            }
+        }
+
+        for charge in charges {
+            let used = usage.entry(charge.key.clone()).or_default();
+            used.amount += charge.amount;
+            used.count += charge.count;
+        }
+        Ok(None)
+    }
+
+    async fn release(&self, charges: &[LimitCharge]) -> Result<(), RepositoryError> {
+        let mut usage = self.usage.lock().await;
+        for charge in charges {
+            if let Some(used) = usage.get_mut(&charge.key) {
+                used.amount = (used.amount - charge.amount).max(Decimal::ZERO);
+                used.count = used.count.saturating_sub(charge.count);
+            }
+        }
+        Ok(())
+    }
+
+    async fn usage(&self, key: &UsageKey) -> Result<Usage, RepositoryError> {
+        Ok(self.usage.lock().await.get(key).copied().unwrap_or_default())
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use sqlx::PgPool;
+
+use crate::error::MigrationError;
+
+// Compiled into the binary so a deployment always carries the schema it was
+// built against.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationState {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+}
+
+struct AppliedMigration {
+    version: i64,
+    success: bool,
+    checksum: Vec<u8>,
+}
+
+pub fn latest_version() -> Option<i64> {
+    known().map(|migration| migration.version).max()
+}
+
+fn known() -> impl Iterator<Item = &'static sqlx::migrate::Migration> {
+    MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+}
+
+async fn applied(pool: &PgPool) -> Result<Vec<AppliedMigration>, MigrationError> {
+    // Reading the bookkeeping table directly keeps checks side-effect free on
+    // a database that has never been migrated.
+    let exists: Option<String> = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations')::text")
+        .fetch_one(pool)
+        .await?;
+    if exists.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<(i64, bool, Vec<u8>)> =
+        sqlx::query_as("SELECT version, success, checksum FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(version, success, checksum)| AppliedMigration {
+            version,
+            success,
+            checksum,
+        })
+        .collect())
+}
+
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationState>, MigrationError> {
+    let applied: Vec<i64> = applied(pool)
+        .await?
+        .into_iter()
+        .filter(|migration| migration.success)
+        .map(|migration| migration.version)
+        .collect();
+    Ok(known()
+        .map(|migration| MigrationState {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied: applied.contains(&migration.version),
+        })
+        .collect())
+}
+
+// Refuses a database this binary cannot safely use: one migrated by a newer
+// build, one with a half-applied migration, or one whose applied scripts were
+// edited afterwards. Returns the versions still waiting to be applied.
+pub async fn check(pool: &PgPool) -> Result<Vec<i64>, MigrationError> {
+    let known: HashMap<i64, &[u8]> = known()
+        .map(|migration| (migration.version, migration.checksum.as_ref()))
+        .collect();
+    let applied = applied(pool).await?;
+
+    for migration in &applied {
+        if !migration.success {
+            return Err(MigrationError::Dirty(migration.version));
+        }
+        match known.get(&migration.version) {
+            None => {
+                return Err(MigrationError::AheadOfBinary {
+                    version: migration.version,
+                    latest: latest_version().unwrap_or_default(),
+                })
+            }
+            Some(checksum) if *checksum != migration.checksum.as_slice() => {
+                return Err(MigrationError::ChecksumMismatch(migration.version))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut pending: Vec<i64> = known
+        .keys()
+        .filter(|version| !applied.iter().any(|migration| migration.version == **version))
+        .copied()
+        .collect();
+    pending.sort_unstable();
+    Ok(pending)
+}
+
+pub async fn run(pool: &PgPool) -> Result<Vec<i64>, MigrationError> {
+    let pending = check(pool).await?;
+    if !pending.is_empty() {
+        MIGRATOR.run(pool).await.map_err(|e| MigrationError::Failed(e.to_string()))?;
+    }
+    Ok(pending)
+}
+
+// Startup entry point: migrates when allowed to, otherwise insists the schema
+// is already current.
+pub async fn prepare(pool: &PgPool, migrate_on_startup: bool) -> Result<Vec<i64>, MigrationError> {
+    if migrate_on_startup {
+        return run(pool).await;
+    }
+    let pending = check(pool).await?;
+    if pending.is_empty() {
+        Ok(pending)
+    } else {
+        Err(MigrationError::Pending(pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embeds_every_migration() {
+        let versions: Vec<i64> = known().map(|migration| migration.version).collect();
+        assert!(!versions.is_empty());
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(latest_version(), versions.last().copied());
+    }
+}
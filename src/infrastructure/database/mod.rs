@@ -0,0 +1,11 @@
+pub mod dead_letters;
+pub mod entitlements;
+pub mod events;
+pub mod fraud_profiles;
+pub mod limits;
+pub mod migrations;
+pub mod outbox;
+pub mod postgres;
+pub mod repository;
+
+pub use repository::PaymentRepository;
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::domain::payment::Payment;
+use crate::error::{MessagingError, RepositoryError};
+
+// A message to publish once the repository write it accompanies commits.
+#[derive(Debug, Clone)]
+pub struct OutboxMessage {
+    pub payment_id: Uuid,
+    pub uetr: Uuid,
+    pub routing_key: String,
+    pub payload: Value,
+}
+
+impl OutboxMessage {
+    pub fn payment_event(payment: &Payment, routing_key: &str) -> Result<Self, MessagingError> {
+        Ok(Self {
+            payment_id: payment.id,
+            uetr: payment.uetr,
+            routing_key: routing_key.to_string(),
+            payload: serde_json::to_value(payment)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub payment_id: Uuid,
+    pub uetr: Uuid,
+    pub routing_key: String,
+    pub payload: Value,
+    pub created_at: DateTime<Utc>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    // Claims up to `limit` entries that are due at `now` until `until`. Only
+    // the oldest unpublished entry of each payment is ever handed out, so
+    // messages for one payment go out in the order they were written even
+    // with several relays running.
+    async fn claim(
+        &self,
+        limit: usize,
+        now: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<OutboxEntry>, RepositoryError>;
+    async fn mark_published(&self, id: i64, at: DateTime<Utc>) -> Result<(), RepositoryError>;
+    async fn mark_failed(&self, id: i64, error: &str, retry_at: DateTime<Utc>) -> Result<(), RepositoryError>;
+}
+
+struct StoredEntry {
+    entry: OutboxEntry,
+    available_at: DateTime<Utc>,
+    published_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct InMemoryOutboxStore {
+    entries: Mutex<Vec<StoredEntry>>,
+}
+
+impl InMemoryOutboxStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn enqueue(&self, message: OutboxMessage, at: DateTime<Utc>) {
+        let mut entries = self.entries.lock().await;
+        let id = entries.len() as i64 + 1;
+        entries.push(StoredEntry {
+            entry: OutboxEntry {
+                id,
+                payment_id: message.payment_id,
+                uetr: message.uetr,
+                routing_key: message.routing_key,
+                payload: message.payload,
+                created_at: at,
+                attempts: 0,
+                last_error: None,
+            },
+            available_at: at,
+            published_at: None,
+        });
+    }
+
+    pub async fn unpublished(&self) -> usize {
+        self.entries.lock().await.iter().filter(|stored| stored.published_at.is_none()).count()
+    }
+}
+
+#[async_trait]
+impl OutboxStore for InMemoryOutboxStore {
+    async fn claim(
+        &self,
+        limit: usize,
+        now: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<OutboxEntry>, RepositoryError> {
+        let mut entries = self.entries.lock().await;
+        let mut heads: Vec<Uuid> = Vec::new();
+        let mut claimed = Vec::new();
+        for stored in entries.iter_mut().filter(|stored| stored.published_at.is_none()) {
+            let payment_id = stored.entry.payment_id;
+            if heads.contains(&payment_id) {
+                continue;
+            }
+            heads.push(payment_id);
+            if stored.available_at <= now && claimed.len() < limit {
+                stored.available_at = until;
+                claimed.push(stored.entry.clone());
+            }
+        }
+        Ok(claimed)
+    }
+
+    async fn mark_published(&self, id: i64, at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        let mut entries = self.entries.lock().await;
+        if let Some(stored) = entries.iter_mut().find(|stored| stored.entry.id == id) {
+            stored.entry.attempts += 1;
+            stored.published_at = Some(at);
+        }
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: i64, error: &str, retry_at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        let mut entries = self.entries.lock().await;
+        if let Some(stored) = entries.iter_mut().find(|stored| stored.entry.id == id) {
+            stored.entry.attempts += 1;
+            stored.entry.last_error = Some(error.to_string());
+            stored.available_at = retry_at;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1336 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::types::Json;
+use sqlx::{Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::config::DatabaseSettings;
+use crate::domain::dead_letter::{DeadLetter, DeadLetterAction, DeadLetterQuery, DeadLetterStatus};
+use crate::domain::entitlement::AccountEntitlement;
+use crate::domain::event::{PaymentEvent, PaymentEventKind};
+use crate::domain::lifecycle::{self, StatusChange, StatusTransition};
+use crate::domain::payment::{ApprovalRecord, HoldDecision, Payment, PaymentRequest, PaymentStatus};
+use crate::domain::payment_details::{self, PaymentDetails};
+use crate::error::RepositoryError;
+use crate::infrastructure::database::dead_letters::DeadLetterStore;
+use crate::infrastructure::database::entitlements::EntitlementRepository;
+use crate::infrastructure::database::events::EventStore;
+use crate::infrastructure::database::fraud_profiles::{SenderProfile, SenderProfileRepository};
+use crate::infrastructure::database::limits::{LimitBreach, LimitCharge, LimitRepository, Usage, UsageKey};
+use crate::infrastructure::database::outbox::{OutboxEntry, OutboxMessage, OutboxStore};
+use crate::infrastructure::database::repository::PaymentRepository;
+use crate::infrastructure::idempotency::{
+    chrono_duration, IdempotencyKey, IdempotencyOutcome, IdempotencyStore, StoredResponse, DEFAULT_LEASE,
+};
+use crate::monitoring::monitor::{AlertQuery, AlertStore, MonitoringAlert};
+use crate::validation::duplicate::DuplicateRegistry;
+
+const SELECT_PAYMENT: &str = "SELECT id, uetr, message_type, payment_type, sender_id, request_id, channel, \
+     submitted_by, status, message_payload, transliterations, flags, screening_hits, hold_decision, \
+     fraud_assessment, approval, approvals, fingerprint, created_at, updated_at FROM payments";
+
+pub async fn connect(settings: &DatabaseSettings) -> Result<PgPool, RepositoryError> {
+    let pool = PgPoolOptions::new()
+        .max_connections(settings.max_connections)
+        .min_connections(settings.min_connections)
+        .acquire_timeout(settings.acquire_timeout())
+        .idle_timeout(settings.idle_timeout())
+        .connect(&settings.url)
+        .await?;
+    Ok(pool)
+}
+
+pub struct PostgresPaymentRepository {
+    pool: PgPool,
+}
+
+impl PostgresPaymentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn record_transition(
+        tx: &mut Transaction<'_, Postgres>,
+        id: &Uuid,
+        transition: &StatusTransition,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO payment_status_history (payment_id, from_status, status, actor, reason, changed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(id)
+        .bind(transition.from.as_ref().map(to_text).transpose()?)
+        .bind(to_text(&transition.to)?)
+        .bind(&transition.actor)
+        .bind(&transition.reason)
+        .bind(transition.at)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    // Locks the payment, checks the move against its lifecycle and applies it.
+    // Staying in the same status is allowed and leaves no history entry, e.g.
+    // an approval that still needs another approver.
+    async fn transition(
+        tx: &mut Transaction<'_, Postgres>,
+        id: &Uuid,
+        change: StatusChange,
+        at: DateTime<Utc>,
+    ) -> Result<(), RepositoryError> {
+        let row: Option<(String, String)> =
+            sqlx::query_as("SELECT status, payment_type FROM payments WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        let (from, payment_type) = row.ok_or(RepositoryError::NotFound(*id))?;
+        let from: PaymentStatus = from_text(from)?;
+        if from == change.status {
+            return Ok(());
+        }
+        let payment_type = from_text(payment_type)?;
+        if !lifecycle::can_transition(payment_type, from, change.status) {
+            return Err(RepositoryError::IllegalTransition {
+                id: *id,
+                payment_type,
+                from: Some(from),
+                to: change.status,
+            });
+        }
+
+        sqlx::query("UPDATE payments SET status = $2, updated_at = $3 WHERE id = $1")
+            .bind(id)
+            .bind(to_text(&change.status)?)
+            .bind(at)
+            .execute(&mut **tx)
+            .await?;
+        let transition = StatusTransition {
+            from: Some(from),
+            to: change.status,
+            actor: change.actor,
+            reason: change.reason,
+            at,
+        };
+        Self::record_transition(tx, id, &transition).await
+    }
+
+    async fn enqueue(
+        tx: &mut Transaction<'_, Postgres>,
+        outbox: Vec<OutboxMessage>,
+        at: DateTime<Utc>,
+    ) -> Result<(), RepositoryError> {
+        for message in outbox {
+            sqlx::query(
+                "INSERT INTO payment_outbox (payment_id, uetr, routing_key, payload, created_at, available_at) \
+                 VALUES ($1, $2, $3, $4, $5, $5)",
+            )
+            .bind(message.payment_id)
+            .bind(message.uetr)
+            .bind(&message.routing_key)
+            .bind(&message.payload)
+            .bind(at)
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn save_parties(tx: &mut Transaction<'_, Postgres>, payment: &Payment) -> Result<(), RepositoryError> {
+        let details = PaymentDetails::from_payload(&payment.request.message_payload);
+        let parties = [
+            ("debtor", &details.debtor_name, &details.debtor_account, &details.debtor_agent),
+            ("creditor", &details.creditor_name, &details.creditor_account, &details.creditor_agent),
+        ];
+        for (role, name, account, agent) in parties {
+            if name.is_none() && account.is_none() && agent.is_none() {
+                continue;
+            }
+            sqlx::query("INSERT INTO payment_parties (payment_id, role, name, account, agent) VALUES ($1, $2, $3, $4, $5)")
+                .bind(payment.id)
+                .bind(role)
+                .bind(name)
+                .bind(account)
+                .bind(agent)
+                .execute(&mut **tx)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // The unique indexes keep UETRs apart within payments and within
+    // transactions; this keeps a payment's UETR from naming another
+    // payment's transaction and the other way round.
+    async fn ensure_uetrs_unused(tx: &mut Transaction<'_, Postgres>, payment: &Payment) -> Result<(), RepositoryError> {
+        let mut uetrs = vec![payment.uetr.to_string()];
+        uetrs.extend(transactions(&payment.request.message_payload).into_iter().filter_map(|details| details.uetr));
+        let taken: Option<Uuid> = sqlx::query_scalar(
+            "SELECT uetr FROM payments WHERE uetr = ANY($1::UUID[]) \
+             UNION ALL SELECT uetr FROM payment_transactions WHERE uetr = ANY($1::UUID[]) LIMIT 1",
+        )
+        .bind(&uetrs)
+        .fetch_optional(&mut **tx)
+        .await?;
+        match taken {
+            Some(uetr) => Err(RepositoryError::Constraint {
+                constraint: "payment_transactions_uetr_key".to_string(),
+                message: format!("UETR {} already names another payment", uetr),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    async fn save_transactions(tx: &mut Transaction<'_, Postgres>, payment: &Payment) -> Result<(), RepositoryError> {
+        for (sequence, details) in transactions(&payment.request.message_payload).into_iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO payment_transactions (payment_id, sequence, end_to_end_id, amount, currency, \
+                 creditor_name, creditor_account, remittance_information, uetr) \
+                 VALUES ($1, $2, $3, $4::NUMERIC, $5, $6, $7, $8, $9::UUID)",
+            )
+            .bind(payment.id)
+            .bind(sequence as i32)
+            .bind(&details.end_to_end_id)
+            // As text, so the column gets the decimal the message carried.
+            .bind(details.amount.map(|amount| amount.to_string()))
+            .bind(&details.currency)
+            .bind(&details.creditor_name)
+            .bind(&details.creditor_account)
+            .bind(&details.remittance_information)
+            .bind(&details.uetr)
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PaymentRepository for PostgresPaymentRepository {
+    async fn save_payment(
+        &self,
+        payment: Payment,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<(), RepositoryError> {
+        let request = &payment.request;
+        if !lifecycle::can_start(request.payment_type, payment.status) {
+            return Err(RepositoryError::IllegalTransition {
+                id: payment.id,
+                payment_type: request.payment_type,
+                from: None,
+                to: payment.status,
+            });
+        }
+        let mut tx = self.pool.begin().await?;
+        Self::ensure_uetrs_unused(&mut tx, &payment).await?;
+
+        sqlx::query(
+            "INSERT INTO payments (id, uetr, message_type, payment_type, sender_id, request_id, channel, \
+             submitted_by, status, message_payload, transliterations, flags, screening_hits, hold_decision, \
+             fraud_assessment, approval, approvals, fingerprint, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)",
+        )
+        .bind(payment.id)
+        .bind(payment.uetr)
+        .bind(&request.message_type)
+        .bind(to_text(&request.payment_type)?)
+        .bind(&request.sender_id)
+        .bind(&request.request_id)
+        .bind(&request.channel)
+        .bind(&request.submitted_by)
+        .bind(to_text(&payment.status)?)
+        .bind(&request.message_payload)
+        .bind(Json(&payment.transliterations))
+        .bind(Json(&payment.flags))
+        .bind(Json(&payment.screening_hits))
+        .bind(payment.hold_decision.as_ref().map(Json))
+        .bind(payment.fraud_assessment.as_ref().map(Json))
+        .bind(payment.approval.as_ref().map(Json))
+        .bind(Json(&payment.approvals))
+        .bind(&payment.fingerprint)
+        .bind(payment.created_at)
+        .bind(payment.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::save_parties(&mut tx, &payment).await?;
+        Self::save_transactions(&mut tx, &payment).await?;
+        let created = StatusTransition {
+            from: None,
+            to: payment.status,
+            actor: request.submitter().to_string(),
+            reason: None,
+            at: payment.created_at,
+        };
+        Self::record_transition(&mut tx, &payment.id, &created).await?;
+        Self::enqueue(&mut tx, outbox, payment.created_at).await?;
+        PostgresEventStore::insert_events(&mut tx, &payment.id, payment.created_at, events).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_payment(&self, id: &Uuid) -> Result<Option<Payment>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE id = $1", SELECT_PAYMENT))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| payment_from_row(&row)).transpose()
+    }
+
+    async fn get_payment_by_uetr(&self, uetr: &Uuid) -> Result<Option<Payment>, RepositoryError> {
+        // The payment's own UETR or that of any of its transactions. Intake
+        // keeps them unique, so a second match is refused, not guessed at.
+        let rows = sqlx::query(&format!(
+            "{} WHERE uetr = $1 OR id IN (SELECT payment_id FROM payment_transactions WHERE uetr = $1)",
+            SELECT_PAYMENT
+        ))
+            .bind(uetr)
+            .fetch_all(&self.pool)
+            .await?;
+        if rows.len() > 1 {
+            return Err(RepositoryError::Constraint {
+                constraint: "payment_transactions_uetr_key".to_string(),
+                message: format!("UETR {} names {} payments", uetr, rows.len()),
+            });
+        }
+        rows.first().map(payment_from_row).transpose()
+    }
+
+    async fn find_by_request(&self, sender_id: &str, request_id: &str) -> Result<Option<Payment>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE sender_id = $1 AND request_id = $2", SELECT_PAYMENT))
+            .bind(sender_id)
+            .bind(request_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| payment_from_row(&row)).transpose()
+    }
+
+    async fn update_status(&self, id: &Uuid, change: StatusChange) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        Self::transition(&mut tx, id, change, Utc::now()).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn status_history(&self, id: &Uuid) -> Result<Vec<StatusTransition>, RepositoryError> {
+        let rows: Vec<(Option<String>, String, String, Option<String>, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT from_status, status, actor, reason, changed_at FROM payment_status_history \
+             WHERE payment_id = $1 ORDER BY id",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|(from, to, actor, reason, at)| {
+                Ok(StatusTransition {
+                    from: from.map(from_text).transpose()?,
+                    to: from_text(to)?,
+                    actor,
+                    reason,
+                    at,
+                })
+            })
+            .collect()
+    }
+
+    async fn list_by_status(&self, status: PaymentStatus) -> Result<Vec<Payment>, RepositoryError> {
+        let rows = sqlx::query(&format!("{} WHERE status = $1 ORDER BY created_at", SELECT_PAYMENT))
+            .bind(to_text(&status)?)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(payment_from_row).collect()
+    }
+
+    async fn record_hold_decision(
+        &self,
+        id: &Uuid,
+        decision: HoldDecision,
+        status: PaymentStatus,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let current: Option<String> = sqlx::query_scalar("SELECT status FROM payments WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let current = current.ok_or(RepositoryError::NotFound(*id))?;
+        if from_text::<PaymentStatus>(current)? != PaymentStatus::Held {
+            return Ok(false);
+        }
+
+        let change = StatusChange {
+            status,
+            actor: decision.decided_by.clone(),
+            reason: Some(decision.reason.clone()),
+        };
+        Self::transition(&mut tx, id, change, decision.decided_at).await?;
+        sqlx::query("UPDATE payments SET hold_decision = $2, updated_at = $3 WHERE id = $1")
+            .bind(id)
+            .bind(Json(&decision))
+            .bind(decision.decided_at)
+            .execute(&mut *tx)
+            .await?;
+        Self::enqueue(&mut tx, outbox, decision.decided_at).await?;
+        PostgresEventStore::insert_events(&mut tx, id, decision.decided_at, events).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn record_approval(
+        &self,
+        id: &Uuid,
+        approval: ApprovalRecord,
+        recorded: usize,
+        status: PaymentStatus,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let row: Option<(String, Json<Vec<ApprovalRecord>>)> =
+            sqlx::query_as("SELECT status, approvals FROM payments WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let (current, Json(approvals)) = row.ok_or(RepositoryError::NotFound(*id))?;
+        if from_text::<PaymentStatus>(current)? != PaymentStatus::PendingApproval
+            || approvals.len() != recorded
+            || approvals
+                .iter()
+                .any(|record| record.approver.eq_ignore_ascii_case(&approval.approver))
+        {
+            return Ok(false);
+        }
+
+        let change = StatusChange {
+            status,
+            actor: approval.approver.clone(),
+            reason: approval.comment.clone(),
+        };
+        Self::transition(&mut tx, id, change, approval.decided_at).await?;
+        sqlx::query(
+            "UPDATE payments SET approvals = approvals || jsonb_build_array($2::jsonb), updated_at = $3 WHERE id = $1",
+        )
+        .bind(id)
+        .bind(Json(&approval))
+        .bind(approval.decided_at)
+        .execute(&mut *tx)
+        .await?;
+        Self::enqueue(&mut tx, outbox, approval.decided_at).await?;
+        PostgresEventStore::insert_events(&mut tx, id, approval.decided_at, events).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+}
+
+pub struct PostgresOutboxStore {
+    pool: PgPool,
+}
+
+impl PostgresOutboxStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OutboxStore for PostgresOutboxStore {
+    async fn claim(
+        &self,
+        limit: usize,
+        now: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<OutboxEntry>, RepositoryError> {
+        // SKIP LOCKED lets relays share the table; the NOT EXISTS keeps every
+        // payment's later entries back until its oldest one is published.
+        let rows: Vec<(i64, Uuid, Uuid, String, Value, DateTime<Utc>, i32, Option<String>)> = sqlx::query_as(
+            "UPDATE payment_outbox SET available_at = $3 WHERE id IN ( \
+                 SELECT o.id FROM payment_outbox o \
+                 WHERE o.published_at IS NULL AND o.available_at <= $2 \
+                   AND NOT EXISTS ( \
+                       SELECT 1 FROM payment_outbox earlier \
+                       WHERE earlier.payment_id = o.payment_id AND earlier.published_at IS NULL AND earlier.id < o.id) \
+                 ORDER BY o.id LIMIT $1 FOR UPDATE SKIP LOCKED) \
+             RETURNING id, payment_id, uetr, routing_key, payload, created_at, attempts, last_error",
+        )
+        .bind(limit as i64)
+        .bind(now)
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut entries: Vec<OutboxEntry> = rows
+            .into_iter()
+            .map(
+                |(id, payment_id, uetr, routing_key, payload, created_at, attempts, last_error)| OutboxEntry {
+                    id,
+                    payment_id,
+                    uetr,
+                    routing_key,
+                    payload,
+                    created_at,
+                    attempts: attempts as u32,
+                    last_error,
+                },
+            )
+            .collect();
+        entries.sort_by_key(|entry| entry.id);
+        Ok(entries)
+    }
+
+    async fn mark_published(&self, id: i64, at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        sqlx::query("UPDATE payment_outbox SET published_at = $2, attempts = attempts + 1 WHERE id = $1")
+            .bind(id)
+            .bind(at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: i64, error: &str, retry_at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE payment_outbox SET attempts = attempts + 1, last_error = $2, available_at = $3 WHERE id = $1",
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct PostgresEventStore {
+    pool: PgPool,
+}
+
+impl PostgresEventStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Also used by the payment repository to write events in the same
+    // transaction as the change they describe.
+    async fn insert_events(
+        tx: &mut Transaction<'_, Postgres>,
+        payment_id: &Uuid,
+        occurred_at: DateTime<Utc>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<Vec<PaymentEvent>, RepositoryError> {
+        // Serializes writers per payment so sequence numbers stay gapless.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1::text))")
+            .bind(payment_id)
+            .execute(&mut **tx)
+            .await?;
+        let last: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(sequence), 0) FROM payment_events WHERE payment_id = $1")
+            .bind(payment_id)
+            .fetch_one(&mut **tx)
+            .await?;
+
+        let mut appended = Vec::with_capacity(events.len());
+        for (offset, kind) in events.into_iter().enumerate() {
+            let data = serde_json::to_value(&kind).map_err(|e| RepositoryError::Database(e.to_string()))?;
+            let event_type = data.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+            let event = PaymentEvent {
+                id: Uuid::new_v4(),
+                payment_id: *payment_id,
+                sequence: (last + offset as i64 + 1) as u64,
+                occurred_at,
+                kind,
+            };
+            sqlx::query(
+                "INSERT INTO payment_events (id, payment_id, sequence, event_type, data, occurred_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(event.id)
+            .bind(event.payment_id)
+            .bind(event.sequence as i64)
+            .bind(event_type)
+            .bind(data)
+            .bind(event.occurred_at)
+            .execute(&mut **tx)
+            .await?;
+            appended.push(event);
+        }
+        Ok(appended)
+    }
+}
+
+#[async_trait]
+impl EventStore for PostgresEventStore {
+    async fn append(
+        &self,
+        payment_id: &Uuid,
+        occurred_at: DateTime<Utc>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<Vec<PaymentEvent>, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let appended = Self::insert_events(&mut tx, payment_id, occurred_at, events).await?;
+        tx.commit().await?;
+        Ok(appended)
+    }
+
+    async fn events(&self, payment_id: &Uuid) -> Result<Vec<PaymentEvent>, RepositoryError> {
+        let rows: Vec<(Uuid, i64, Json<PaymentEventKind>, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT id, sequence, data, occurred_at FROM payment_events WHERE payment_id = $1 ORDER BY sequence",
+        )
+        .bind(payment_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, sequence, data, occurred_at)| PaymentEvent {
+                id,
+                payment_id: *payment_id,
+                sequence: sequence as u64,
+                occurred_at,
+                kind: data.0,
+            })
+            .collect())
+    }
+}
+
+// Each credit transfer or direct debit transaction in the message; a message
+// without any is stored as a single transaction read from the whole payload.
+fn transactions(payload: &Value) -> Vec<PaymentDetails> {
+    payment_details::transactions(payload)
+        .into_iter()
+        .map(|transaction| transaction.details)
+        .collect()
+}
+
+fn payment_from_row(row: &PgRow) -> Result<Payment, RepositoryError> {
+    let request = PaymentRequest {
+        message_type: row.try_get("message_type")?,
+        payment_type: from_text(row.try_get("payment_type")?)?,
+        message_payload: row.try_get("message_payload")?,
+        sender_id: row.try_get("sender_id")?,
+        request_id: row.try_get("request_id")?,
+        channel: row.try_get("channel")?,
+        uetr: Some(row.try_get("uetr")?),
+        submitted_by: row.try_get("submitted_by")?,
+    };
+    Ok(Payment {
+        id: row.try_get("id")?,
+        uetr: row.try_get("uetr")?,
+        request,
+        status: from_text(row.try_get("status")?)?,
+        transliterations: row.try_get::<Json<_>, _>("transliterations")?.0,
+        flags: row.try_get::<Json<_>, _>("flags")?.0,
+        screening_hits: row.try_get::<Json<_>, _>("screening_hits")?.0,
+        hold_decision: row.try_get::<Option<Json<_>>, _>("hold_decision")?.map(|json| json.0),
+        fraud_assessment: row.try_get::<Option<Json<_>>, _>("fraud_assessment")?.map(|json| json.0),
+        approval: row.try_get::<Option<Json<_>>, _>("approval")?.map(|json| json.0),
+        approvals: row.try_get::<Json<_>, _>("approvals")?.0,
+        fingerprint: row.try_get("fingerprint")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+// Enums are stored under their serde names so the columns stay readable and
+// match what the API returns.
+const SELECT_DEAD_LETTER: &str = "SELECT id, queue, message_id, message_type, correlation_id, reply_to, failure_class, \
+     failure_reason, attempts, payload, dead_lettered_at, status FROM dead_letters";
+
+pub struct PostgresDeadLetterStore {
+    pool: PgPool,
+}
+
+impl PostgresDeadLetterStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn insert_action(
+        executor: impl sqlx::PgExecutor<'_>,
+        action: &DeadLetterAction,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO dead_letter_actions (id, dead_letter_id, action, actor, reason, edited_payload, at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(action.id)
+        .bind(action.dead_letter_id)
+        .bind(to_text(&action.action)?)
+        .bind(&action.actor)
+        .bind(&action.reason)
+        .bind(&action.edited_payload)
+        .bind(action.at)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for PostgresDeadLetterStore {
+    async fn capture(&self, letter: DeadLetter) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO dead_letters (id, queue, message_id, message_type, correlation_id, reply_to, failure_class, \
+             failure_reason, attempts, payload, dead_lettered_at, status) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(letter.id)
+        .bind(&letter.queue)
+        .bind(&letter.message_id)
+        .bind(&letter.message_type)
+        .bind(&letter.correlation_id)
+        .bind(&letter.reply_to)
+        .bind(&letter.failure_class)
+        .bind(&letter.failure_reason)
+        .bind(letter.attempts as i32)
+        .bind(&letter.payload)
+        .bind(letter.dead_lettered_at)
+        .bind(to_text(&letter.status)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list(&self, query: &DeadLetterQuery) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let status = query.status.as_ref().map(to_text).transpose()?;
+        let rows = sqlx::query(&format!(
+            "{} WHERE ($1::text IS NULL OR queue = $1) AND ($2::text IS NULL OR status = $2) \
+             ORDER BY dead_lettered_at",
+            SELECT_DEAD_LETTER
+        ))
+        .bind(&query.queue)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(dead_letter_from_row).collect()
+    }
+
+    async fn get(&self, id: &Uuid) -> Result<Option<DeadLetter>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE id = $1", SELECT_DEAD_LETTER))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(dead_letter_from_row).transpose()
+    }
+
+    async fn record_action(&self, action: DeadLetterAction) -> Result<(), RepositoryError> {
+        Self::insert_action(&self.pool, &action).await
+    }
+
+    async fn transition(
+        &self,
+        id: &Uuid,
+        from: DeadLetterStatus,
+        to: DeadLetterStatus,
+        action: Option<DeadLetterAction>,
+    ) -> Result<bool, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let updated = sqlx::query("UPDATE dead_letters SET status = $2 WHERE id = $1 AND status = $3")
+            .bind(id)
+            .bind(to_text(&to)?)
+            .bind(to_text(&from)?)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if updated == 0 {
+            let exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM dead_letters WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            return match exists {
+                Some(_) => Ok(false),
+                None => Err(RepositoryError::NotFound(*id)),
+            };
+        }
+        if let Some(action) = &action {
+            Self::insert_action(&mut *tx, action).await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn actions(&self, id: &Uuid) -> Result<Vec<DeadLetterAction>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, dead_letter_id, action, actor, reason, edited_payload, at FROM dead_letter_actions \
+             WHERE dead_letter_id = $1 ORDER BY at",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(DeadLetterAction {
+                    id: row.try_get("id")?,
+                    dead_letter_id: row.try_get("dead_letter_id")?,
+                    action: from_text(row.try_get("action")?)?,
+                    actor: row.try_get("actor")?,
+                    reason: row.try_get("reason")?,
+                    edited_payload: row.try_get("edited_payload")?,
+                    at: row.try_get("at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn dead_letter_from_row(row: &PgRow) -> Result<DeadLetter, RepositoryError> {
+    let attempts: i32 = row.try_get("attempts")?;
+    Ok(DeadLetter {
+        id: row.try_get("id")?,
+        queue: row.try_get("queue")?,
+        message_id: row.try_get("message_id")?,
+        message_type: row.try_get("message_type")?,
+        correlation_id: row.try_get("correlation_id")?,
+        reply_to: row.try_get("reply_to")?,
+        failure_class: row.try_get("failure_class")?,
+        failure_reason: row.try_get("failure_reason")?,
+        attempts: attempts.max(0) as u32,
+        payload: row.try_get("payload")?,
+        dead_lettered_at: row.try_get("dead_lettered_at")?,
+        status: from_text(row.try_get("status")?)?,
+    })
+}
+
+pub struct PostgresIdempotencyStore {
+    pool: PgPool,
+    ttl: Duration,
+    lease: Duration,
+}
+
+impl PostgresIdempotencyStore {
+    pub fn new(pool: PgPool, ttl: Duration) -> Self {
+        Self {
+            pool,
+            ttl,
+            lease: DEFAULT_LEASE,
+        }
+    }
+
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for PostgresIdempotencyStore {
+    async fn begin(&self, key: &IdempotencyKey, fingerprint: &str) -> Result<IdempotencyOutcome, RepositoryError> {
+        let now = Utc::now();
+        sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        // Takes the key when it is new, or when the same request finds the
+        // previous attempt's lease lapsed without a response.
+        let taken = sqlx::query(
+            "INSERT INTO idempotency_keys (scope, sender_id, key, fingerprint, locked_until, created_at, expires_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             ON CONFLICT (scope, sender_id, key) DO UPDATE SET locked_until = EXCLUDED.locked_until \
+             WHERE idempotency_keys.fingerprint = EXCLUDED.fingerprint AND idempotency_keys.response IS NULL \
+               AND idempotency_keys.locked_until <= $6",
+        )
+        .bind(&key.scope)
+        .bind(&key.sender_id)
+        .bind(&key.key)
+        .bind(fingerprint)
+        .bind(now + chrono_duration(self.lease)?)
+        .bind(now)
+        .bind(now + chrono_duration(self.ttl)?)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if taken > 0 {
+            return Ok(IdempotencyOutcome::New);
+        }
+
+        let row: Option<(String, Option<i32>, Option<Value>)> = sqlx::query_as(
+            "SELECT fingerprint, status_code, response FROM idempotency_keys \
+             WHERE scope = $1 AND sender_id = $2 AND key = $3",
+        )
+        .bind(&key.scope)
+        .bind(&key.sender_id)
+        .bind(&key.key)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(match row {
+            Some((stored, _, _)) if stored != fingerprint => IdempotencyOutcome::Mismatch,
+            Some((_, Some(status), Some(body))) => IdempotencyOutcome::Replay(StoredResponse {
+                status: status as u16,
+                body,
+            }),
+            // Released between the two statements; the client may retry.
+            _ => IdempotencyOutcome::InProgress,
+        })
+    }
+
+    async fn complete(&self, key: &IdempotencyKey, response: StoredResponse) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET status_code = $4, response = $5, locked_until = NULL \
+             WHERE scope = $1 AND sender_id = $2 AND key = $3",
+        )
+        .bind(&key.scope)
+        .bind(&key.sender_id)
+        .bind(&key.key)
+        .bind(response.status as i32)
+        .bind(&response.body)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn release(&self, key: &IdempotencyKey) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE scope = $1 AND sender_id = $2 AND key = $3 AND response IS NULL",
+        )
+        .bind(&key.scope)
+        .bind(&key.sender_id)
+        .bind(&key.key)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn abandon(&self, key: &IdempotencyKey) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET locked_until = $4 \
+             WHERE scope = $1 AND sender_id = $2 AND key = $3 AND response IS NULL",
+        )
+        .bind(&key.scope)
+        .bind(&key.sender_id)
+        .bind(&key.key)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct PostgresDuplicateRegistry {
+    pool: PgPool,
+    retention: chrono::Duration,
+}
+
+impl PostgresDuplicateRegistry {
+    pub fn new(pool: PgPool, retention: chrono::Duration) -> Self {
+        Self { pool, retention }
+    }
+}
+
+#[async_trait]
+impl DuplicateRegistry for PostgresDuplicateRegistry {
+    async fn find_since(&self, fingerprint: &str, since: DateTime<Utc>) -> Result<Option<Uuid>, RepositoryError> {
+        let original = sqlx::query_scalar(
+            "SELECT payment_id FROM duplicate_fingerprints WHERE fingerprint = $1 AND seen_at >= $2 \
+             ORDER BY seen_at LIMIT 1",
+        )
+        .bind(fingerprint)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(original)
+    }
+
+    async fn record(&self, fingerprint: &str, payment_id: Uuid, at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM duplicate_fingerprints WHERE seen_at < $1")
+            .bind(at - self.retention)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "INSERT INTO duplicate_fingerprints (fingerprint, payment_id, seen_at) VALUES ($1, $2, $3) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(fingerprint)
+        .bind(payment_id)
+        .bind(at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct PostgresEntitlementRepository {
+    pool: PgPool,
+}
+
+impl PostgresEntitlementRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EntitlementRepository for PostgresEntitlementRepository {
+    async fn list_for_sender(&self, sender_id: &str) -> Result<Vec<AccountEntitlement>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT sender_id, account, payment_types, granted_by, updated_at FROM account_entitlements \
+             WHERE sender_id = $1 ORDER BY account",
+        )
+        .bind(sender_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(entitlement_from_row).collect()
+    }
+
+    async fn find(&self, sender_id: &str, account: &str) -> Result<Option<AccountEntitlement>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT sender_id, account, payment_types, granted_by, updated_at FROM account_entitlements \
+             WHERE sender_id = $1 AND account = $2",
+        )
+        .bind(sender_id)
+        .bind(account)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(entitlement_from_row).transpose()
+    }
+
+    async fn upsert(&self, entitlement: AccountEntitlement) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO account_entitlements (sender_id, account, payment_types, granted_by, updated_at) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (sender_id, account) DO UPDATE SET payment_types = EXCLUDED.payment_types, \
+             granted_by = EXCLUDED.granted_by, updated_at = EXCLUDED.updated_at",
+        )
+        .bind(&entitlement.sender_id)
+        .bind(&entitlement.account)
+        .bind(Json(&entitlement.payment_types))
+        .bind(&entitlement.granted_by)
+        .bind(entitlement.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn revoke(&self, sender_id: &str, account: &str) -> Result<bool, RepositoryError> {
+        let revoked = sqlx::query("DELETE FROM account_entitlements WHERE sender_id = $1 AND account = $2")
+            .bind(sender_id)
+            .bind(account)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(revoked > 0)
+    }
+}
+
+fn entitlement_from_row(row: &PgRow) -> Result<AccountEntitlement, RepositoryError> {
+    Ok(AccountEntitlement {
+        sender_id: row.try_get("sender_id")?,
+        account: row.try_get("account")?,
+        payment_types: row.try_get::<Json<_>, _>("payment_types")?.0,
+        granted_by: row.try_get("granted_by")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub struct PostgresLimitRepository {
+    pool: PgPool,
+}
+
+impl PostgresLimitRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LimitRepository for PostgresLimitRepository {
+    async fn reserve(&self, charges: &[LimitCharge]) -> Result<Option<LimitBreach>, RepositoryError> {
+        // Counters are locked in key order so two instances charging the same
+        // subjects cannot deadlock; a breach drops the transaction unchanged.
+        let mut ordered: Vec<&LimitCharge> = charges.iter().collect();
+        ordered.sort_by(|a, b| {
+            (&a.key.rule, &a.key.subject, a.key.period_start, a.key.period as u8)
+                .cmp(&(&b.key.rule, &b.key.subject, b.key.period_start, b.key.period as u8))
+        });
+
+        let mut tx = self.pool.begin().await?;
+        for charge in &ordered {
+            let period = to_text(&charge.key.period)?;
+            sqlx::query(
+                "INSERT INTO limit_usage (rule, subject, period, period_start) VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(&charge.key.rule)
+            .bind(&charge.key.subject)
+            .bind(&period)
+            .bind(charge.key.period_start)
+            .execute(&mut *tx)
+            .await?;
+            let row = sqlx::query(
+                "SELECT amount, count FROM limit_usage \
+                 WHERE rule = $1 AND subject = $2 AND period = $3 AND period_start = $4 FOR UPDATE",
+            )
+            .bind(&charge.key.rule)
+            .bind(&charge.key.subject)
+            .bind(&period)
+            .bind(charge.key.period_start)
+            .fetch_one(&mut *tx)
+            .await?;
+            let used = usage_from_row(&row)?;
+            let amount_exceeded = charge.max_amount.is_some_and(|max| used.amount + charge.amount > max);
+            let count_exceeded = charge.max_count.is_some_and(|max| used.count + charge.count > max);
+            if amount_exceeded || count_exceeded {
+                return Ok(Some(LimitBreach {
+                    charge: (*charge).clone(),
+                    used,
+                }));
+            }
+        }
+
+        for charge in &ordered {
+            sqlx::query(
+                "UPDATE limit_usage SET amount = amount + $5, count = count + $6 \
+                 WHERE rule = $1 AND subject = $2 AND period = $3 AND period_start = $4",
+            )
+            .bind(&charge.key.rule)
+            .bind(&charge.key.subject)
+            .bind(to_text(&charge.key.period)?)
+            .bind(charge.key.period_start)
+            .bind(charge.amount)
+            .bind(charge.count as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(None)
+    }
+
+    async fn release(&self, charges: &[LimitCharge]) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        for charge in charges {
+            sqlx::query(
+                "UPDATE limit_usage SET amount = GREATEST(amount - $5, 0), \
+                 count = GREATEST(count - $6, 0) \
+                 WHERE rule = $1 AND subject = $2 AND period = $3 AND period_start = $4",
+            )
+            .bind(&charge.key.rule)
+            .bind(&charge.key.subject)
+            .bind(to_text(&charge.key.period)?)
+            .bind(charge.key.period_start)
+            .bind(charge.amount)
+            .bind(charge.count as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn usage(&self, key: &UsageKey) -> Result<Usage, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT amount, count FROM limit_usage \
+             WHERE rule = $1 AND subject = $2 AND period = $3 AND period_start = $4",
+        )
+        .bind(&key.rule)
+        .bind(&key.subject)
+        .bind(to_text(&key.period)?)
+        .bind(key.period_start)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(usage_from_row).transpose().map(Option::unwrap_or_default)
+    }
+}
+
+fn usage_from_row(row: &PgRow) -> Result<Usage, RepositoryError> {
+    Ok(Usage {
+        amount: row.try_get("amount")?,
+        count: row.try_get::<i64, _>("count")?.max(0) as u64,
+    })
+}
+
+pub struct PostgresSenderProfileRepository {
+    pool: PgPool,
+}
+
+impl PostgresSenderProfileRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SenderProfileRepository for PostgresSenderProfileRepository {
+    async fn profile(&self, sender_id: &str, beneficiaries: &[String]) -> Result<Option<SenderProfile>, RepositoryError> {
+        let row = sqlx::query("SELECT payments, total_amount FROM fraud_sender_profiles WHERE sender_id = $1")
+            .bind(sender_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let known: Vec<String> = sqlx::query_scalar(
+            "SELECT beneficiary FROM fraud_sender_beneficiaries WHERE sender_id = $1 AND beneficiary = ANY($2)",
+        )
+        .bind(sender_id)
+        .bind(beneficiaries)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let payments = row.try_get::<i64, _>("payments")?.max(0) as u64;
+        let total: f64 = row.try_get("total_amount")?;
+        Ok(Some(SenderProfile {
+            beneficiaries: known.into_iter().collect(),
+            payments,
+            mean_amount: if payments == 0 { 0.0 } else { total / payments as f64 },
+        }))
+    }
+
+    // Counters are added to in place, so instances recording payments of the
+    // same sender at once do not lose each other's updates.
+    async fn record(&self, sender_id: &str, beneficiaries: &[String], amounts: &[f64]) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO fraud_sender_profiles (sender_id, payments, total_amount) VALUES ($1, $2, $3) \
+             ON CONFLICT (sender_id) DO UPDATE SET payments = fraud_sender_profiles.payments + EXCLUDED.payments, \
+             total_amount = fraud_sender_profiles.total_amount + EXCLUDED.total_amount",
+        )
+        .bind(sender_id)
+        .bind(amounts.len() as i64)
+        .bind(amounts.iter().sum::<f64>())
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO fraud_sender_beneficiaries (sender_id, beneficiary) SELECT $1, UNNEST($2) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(sender_id)
+        .bind(beneficiaries)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+pub struct PostgresAlertStore {
+    pool: PgPool,
+}
+
+impl PostgresAlertStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AlertStore for PostgresAlertStore {
+    async fn raise(&self, alert: &MonitoringAlert, since: DateTime<Utc>) -> Result<bool, RepositoryError> {
+        // The advisory lock serialises instances raising the same party and
+        // rule, so the window check and the insert cannot interleave.
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1 || '/' || $2))")
+            .bind(&alert.party)
+            .bind(&alert.rule)
+            .execute(&mut *tx)
+            .await?;
+        let raised: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM monitoring_alerts WHERE party = $1 AND rule = $2 AND raised_at >= $3 LIMIT 1",
+        )
+        .bind(&alert.party)
+        .bind(&alert.rule)
+        .bind(since)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if raised.is_some() {
+            return Ok(false);
+        }
+        sqlx::query(
+            "INSERT INTO monitoring_alerts (id, rule, party, reason, transactions, raised_at) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(alert.id)
+        .bind(&alert.rule)
+        .bind(&alert.party)
+        .bind(&alert.reason)
+        .bind(Json(&alert.transactions))
+        .bind(alert.raised_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn find(&self, query: &AlertQuery) -> Result<Vec<MonitoringAlert>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, rule, party, reason, transactions, raised_at FROM monitoring_alerts \
+             WHERE ($1::text IS NULL OR party = $1) AND ($2::text IS NULL OR rule = $2) \
+             AND ($3::timestamptz IS NULL OR raised_at >= $3) ORDER BY raised_at DESC",
+        )
+        .bind(&query.party)
+        .bind(&query.rule)
+        .bind(query.since)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(alert_from_row).collect()
+    }
+
+    async fn get(&self, id: &Uuid) -> Result<Option<MonitoringAlert>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, rule, party, reason, transactions, raised_at FROM monitoring_alerts WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(alert_from_row).transpose()
+    }
+
+    async fn prune(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let pruned = sqlx::query("DELETE FROM monitoring_alerts WHERE raised_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(pruned)
+    }
+}
+
+fn alert_from_row(row: &PgRow) -> Result<MonitoringAlert, RepositoryError> {
+    Ok(MonitoringAlert {
+        id: row.try_get("id")?,
+        rule: row.try_get("rule")?,
+        party: row.try_get("party")?,
+        reason: row.try_get("reason")?,
+        transactions: row.try_get::<Json<_>, _>("transactions")?.0,
+        raised_at: row.try_get("raised_at")?,
+    })
+}
+
+fn to_text<T: Serialize>(value: &T) -> Result<String, RepositoryError> {
+    match serde_json::to_value(value) {
+        Ok(Value::String(text)) => Ok(text),
+        Ok(other) => Err(RepositoryError::Database(format!("expected a unit variant, got {}", other))),
+        Err(e) => Err(RepositoryError::Database(e.to_string())),
+    }
+}
+
+fn from_text<T: DeserializeOwned>(text: String) -> Result<T, RepositoryError> {
+    serde_json::from_value(Value::String(text)).map_err(|e| RepositoryError::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn splits_batches_into_transactions() {
+        let payload = json!({
+            "PmtInf": {
+                "DbtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } },
+                "CdtTrfTxInf": [
+                    { "PmtId": { "EndToEndId": "E2E-1" }, "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 10.0 } } },
+                    { "PmtId": { "EndToEndId": "E2E-2" }, "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 20.0 } } }
+                ]
+            }
+        });
+
+        let found = transactions(&payload);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].end_to_end_id.as_deref(), Some("E2E-2"));
+        assert_eq!(found[1].amount, Some(20.0));
+        assert_eq!(to_text(&PaymentStatus::PendingApproval).unwrap(), "PendingApproval");
+        assert_eq!(from_text::<PaymentStatus>("Held".to_string()).unwrap(), PaymentStatus::Held);
+    }
+}
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::event::PaymentEventKind;
+use crate::domain::lifecycle::{StatusChange, StatusTransition};
+use crate::domain::payment::{ApprovalRecord, HoldDecision, Payment, PaymentStatus};
+use crate::error::RepositoryError;
+use crate::infrastructure::database::outbox::OutboxMessage;
+
+// Implementations reject status changes the payment type's lifecycle does not
+// allow with `RepositoryError::IllegalTransition` and keep every accepted one.
+// Outbox messages passed to a write are stored atomically with it and later
+// published by the outbox relay; events passed to a write are appended to the
+// payment's timeline in the same way.
+#[async_trait]
+pub trait PaymentRepository: Send + Sync {
+    async fn save_payment(
+        &self,
+        payment: Payment,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<(), RepositoryError>;
+    async fn get_payment(&self, id: &Uuid) -> Result<Option<Payment>, RepositoryError>;
+    async fn get_payment_by_uetr(&self, uetr: &Uuid) -> Result<Option<Payment>, RepositoryError>;
+    async fn find_by_request(&self, sender_id: &str, request_id: &str) -> Result<Option<Payment>, RepositoryError>;
+    async fn update_status(&self, id: &Uuid, change: StatusChange) -> Result<(), RepositoryError>;
+    async fn status_history(&self, id: &Uuid) -> Result<Vec<StatusTransition>, RepositoryError>;
+    async fn list_by_status(&self, status: PaymentStatus) -> Result<Vec<Payment>, RepositoryError>;
+    // Records a hold decision provided the payment, checked under its row
+    // lock, is still held. Returns false when another decision got in first.
+    async fn record_hold_decision(
+        &self,
+        id: &Uuid,
+        decision: HoldDecision,
+        status: PaymentStatus,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError>;
+    // Appends an approval decision provided the payment, checked under its
+    // row lock, is still pending approval with exactly `recorded` decisions
+    // and none from this approver. Returns false when another decision got
+    // in first, so the caller can decide again on the current state.
+    async fn record_approval(
+        &self,
+        id: &Uuid,
+        approval: ApprovalRecord,
+        recorded: usize,
+        status: PaymentStatus,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError>;
+}
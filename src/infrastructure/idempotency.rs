@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::error::RepositoryError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey {
+    pub scope: String,
+    pub sender_id: String,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+#[derive(Debug)]
+pub enum IdempotencyOutcome {
+    New,
+    Replay(StoredResponse),
+    InProgress,
+    Mismatch,
+}
+
+pub const DEFAULT_LEASE: Duration = Duration::from_secs(60);
+
+// `begin` takes the key with a lease for the attempt. While the lease holds,
+// repeats see InProgress; once it lapses without a response (the instance
+// died, or the attempt was abandoned) a repeat with the same fingerprint
+// takes the key over. A different fingerprint is a Mismatch until the key
+// expires.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    async fn begin(&self, key: &IdempotencyKey, fingerprint: &str) -> Result<IdempotencyOutcome, RepositoryError>;
+    async fn complete(&self, key: &IdempotencyKey, response: StoredResponse) -> Result<(), RepositoryError>;
+    // Forgets the attempt entirely; only for attempts that stored nothing.
+    async fn release(&self, key: &IdempotencyKey) -> Result<(), RepositoryError>;
+    // Ends the lease but keeps the fingerprint, for attempts whose effect is
+    // unknown: a retry of the same request may run it again, which the
+    // payment service answers from the stored payment.
+    async fn abandon(&self, key: &IdempotencyKey) -> Result<(), RepositoryError>;
+}
+
+pub(crate) fn chrono_duration(duration: Duration) -> Result<chrono::Duration, RepositoryError> {
+    chrono::Duration::from_std(duration).map_err(|e| RepositoryError::Database(e.to_string()))
+}
+
+struct Entry {
+    fingerprint: String,
+    response: Option<StoredResponse>,
+    locked_until: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+pub struct InMemoryIdempotencyStore {
+    ttl: Duration,
+    lease: Duration,
+    entries: Mutex<HashMap<IdempotencyKey, Entry>>,
+}
+
+impl InMemoryIdempotencyStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            lease: DEFAULT_LEASE,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    async fn begin(&self, key: &IdempotencyKey, fingerprint: &str) -> Result<IdempotencyOutcome, RepositoryError> {
+        let now = Utc::now();
+        let mut entries = self.entries.lock().await;
+        entries.retain(|_, entry| entry.expires_at > now);
+
+        let locked_until = now + chrono_duration(self.lease)?;
+        if let Some(entry) = entries.get_mut(key) {
+            if entry.fingerprint != fingerprint {
+                return Ok(IdempotencyOutcome::Mismatch);
+            }
+            return Ok(match &entry.response {
+                Some(response) => IdempotencyOutcome::Replay(response.clone()),
+                None if entry.locked_until > now => IdempotencyOutcome::InProgress,
+                None => {
+                    entry.locked_until = locked_until;
+                    IdempotencyOutcome::New
+                }
+            });
+        }
+
+        entries.insert(
+            key.clone(),
+            Entry {
+                fingerprint: fingerprint.to_string(),
+                response: None,
+                locked_until,
+                expires_at: now + chrono_duration(self.ttl)?,
+            },
+        );
+        Ok(IdempotencyOutcome::New)
+    }
+
+    async fn complete(&self, key: &IdempotencyKey, response: StoredResponse) -> Result<(), RepositoryError> {
+        let mut entries = self.entries.lock().await;
+        if let Some(entry) = entries.get_mut(key) {
+            entry.response = Some(response);
+        }
+        Ok(())
+    }
+
+    async fn release(&self, key: &IdempotencyKey) -> Result<(), RepositoryError> {
+        let mut entries = self.entries.lock().await;
+        if entries.get(key).is_some_and(|entry| entry.response.is_none()) {
+            entries.remove(key);
+        }
+        Ok(())
+    }
+
+    async fn abandon(&self, key: &IdempotencyKey) -> Result<(), RepositoryError> {
+        if let Some(entry) = self.entries.lock().await.get_mut(key) {
+            entry.locked_until = Utc::now();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key() -> IdempotencyKey {
+        IdempotencyKey {
+            scope: "payments".to_string(),
+            sender_id: "sender".to_string(),
+            key: "abc".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_completed_response_for_identical_body() {
+        let store = InMemoryIdempotencyStore::new(Duration::from_secs(60));
+        assert!(matches!(store.begin(&key(), "f1").await.unwrap(), IdempotencyOutcome::New));
+        assert!(matches!(store.begin(&key(), "f1").await.unwrap(), IdempotencyOutcome::InProgress));
+
+        store
+            .complete(&key(), StoredResponse { status: 200, body: json!({ "ok": true }) })
+            .await
+            .unwrap();
+
+        match store.begin(&key(), "f1").await.unwrap() {
+            IdempotencyOutcome::Replay(response) => assert_eq!(response.body, json!({ "ok": true })),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        assert!(matches!(store.begin(&key(), "f2").await.unwrap(), IdempotencyOutcome::Mismatch));
+    }
+
+    #[tokio::test]
+    async fn expired_keys_are_forgotten() {
+        let store = InMemoryIdempotencyStore::new(Duration::from_secs(0));
+        store.begin(&key(), "f1").await.unwrap();
+        assert!(matches!(store.begin(&key(), "f2").await.unwrap(), IdempotencyOutcome::New));
+    }
+
+    #[tokio::test]
+    async fn abandoned_attempts_can_be_retried_with_the_same_body_only() {
+        let store = InMemoryIdempotencyStore::new(Duration::from_secs(60));
+        store.begin(&key(), "f1").await.unwrap();
+        store.abandon(&key()).await.unwrap();
+
+        assert!(matches!(store.begin(&key(), "f2").await.unwrap(), IdempotencyOutcome::Mismatch));
+        assert!(matches!(store.begin(&key(), "f1").await.unwrap(), IdempotencyOutcome::New));
+        assert!(matches!(store.begin(&key(), "f1").await.unwrap(), IdempotencyOutcome::InProgress));
+    }
+
+    #[tokio::test]
+    async fn lapsed_leases_are_taken_over() {
+        let store = InMemoryIdempotencyStore::new(Duration::from_secs(60)).with_lease(Duration::from_secs(0));
+        store.begin(&key(), "f1").await.unwrap();
+        assert!(matches!(store.begin(&key(), "f1").await.unwrap(), IdempotencyOutcome::New));
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use lapin::message::Delivery;
+use lapin::options::{BasicAckOptions, BasicConsumeOptions, BasicNackOptions};
+use lapin::types::FieldTable;
+use lapin::{Connection, ConnectionProperties};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::MessagingSettings;
+use crate::domain::dead_letter::{DeadLetter, DeadLetterStatus};
+use crate::error::MessagingError;
+use crate::infrastructure::database::dead_letters::DeadLetterStore;
+use crate::infrastructure::messaging::retry::{self, FAILED_AT_HEADER, FAILURE_CLASS_HEADER, FAILURE_REASON_HEADER, ORIGINAL_QUEUE_HEADER};
+
+#[async_trait]
+pub trait DeadLetterQueue: Send + Sync {
+    // Puts the message back on the queue it failed on, as a fresh delivery.
+    async fn resubmit(&self, letter: &DeadLetter, payload: &[u8]) -> Result<(), MessagingError>;
+}
+
+// Moves messages off the dead-letter queues into the dead-letter store, where
+// operators can search and act on them; a queue can only be read in order.
+pub struct DeadLetterCollector {
+    settings: MessagingSettings,
+    store: Arc<dyn DeadLetterStore>,
+}
+
+impl DeadLetterCollector {
+    pub fn new(settings: MessagingSettings, store: Arc<dyn DeadLetterStore>) -> Self {
+        Self { settings, store }
+    }
+
+    pub fn spawn(self: Arc<Self>) {
+        for inbound in &self.settings.inbound {
+            let collector = self.clone();
+            let queue = inbound.queue.clone();
+            tokio::spawn(async move { collector.collect(queue).await });
+        }
+    }
+
+    async fn collect(&self, queue: String) {
+        let mut attempt = 0;
+        loop {
+            match self.collect_until_closed(&queue).await {
+                Ok(()) => {
+                    attempt = 0;
+                    warn!(queue = %queue, "Broker closed the dead-letter consumer");
+                }
+                Err(e) => warn!(queue = %queue, "Dead-letter collector failed: {}", e),
+            }
+            tokio::time::sleep(self.settings.reconnect_delay(attempt)).await;
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    async fn collect_until_closed(&self, queue: &str) -> Result<(), MessagingError> {
+        let dead_letter_queue = retry::dead_letter_queue(queue);
+        let name = format!("iso20022-payment-processor:{}", dead_letter_queue);
+        let connection =
+            Connection::connect(&self.settings.url, ConnectionProperties::default().with_connection_name(name.into()))
+                .await?;
+        let channel = connection.create_channel().await?;
+        retry::declare_dead_letter_queue(&channel, queue).await?;
+        let mut deliveries = channel
+            .basic_consume(
+                &dead_letter_queue,
+                "dead-letter-collector",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+        info!(queue = %dead_letter_queue, "Collecting dead letters");
+
+        while let Some(delivery) = deliveries.next().await {
+            let delivery = delivery?;
+            let letter = dead_letter(queue, &delivery);
+            match self.store.capture(letter).await {
+                Ok(()) => delivery.acker.ack(BasicAckOptions::default()).await?,
+                Err(e) => {
+                    error!(queue = %dead_letter_queue, "Failed to store dead letter: {:?}", e);
+                    let requeue = BasicNackOptions {
+                        requeue: true,
+                        ..BasicNackOptions::default()
+                    };
+                    delivery.acker.nack(requeue).await?;
+                    tokio::time::sleep(self.settings.reconnect_delay(0)).await;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn dead_letter(queue: &str, delivery: &Delivery) -> DeadLetter {
+    let properties = &delivery.properties;
+    let headers = properties.headers().clone().unwrap_or_default();
+    let text = |value: &Option<lapin::types::ShortString>| value.as_ref().map(|value| value.as_str().to_string());
+    let dead_lettered_at = retry::header_text(&headers, FAILED_AT_HEADER)
+        .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+
+    DeadLetter {
+        id: Uuid::new_v4(),
+        queue: retry::header_text(&headers, ORIGINAL_QUEUE_HEADER).unwrap_or_else(|| queue.to_string()),
+        message_id: text(properties.message_id()),
+        message_type: text(properties.kind()),
+        correlation_id: text(properties.correlation_id()),
+        reply_to: text(properties.reply_to()),
+        failure_class: retry::header_text(&headers, FAILURE_CLASS_HEADER),
+        failure_reason: retry::header_text(&headers, FAILURE_REASON_HEADER),
+        attempts: retry::attempts(properties),
+        payload: String::from_utf8_lossy(&delivery.data).into_owned(),
+        dead_lettered_at,
+        status: DeadLetterStatus::Pending,
+    }
+}
@@ -0,0 +1,540 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::{Header, Headers, OwnedHeaders, OwnedMessage};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::{Message, Offset, TopicPartitionList};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::config::MessagingSettings;
+use crate::error::MessagingError;
+use crate::infrastructure::messaging::message_publisher::with_uetr;
+use crate::infrastructure::messaging::retry::FailureClass;
+use crate::infrastructure::messaging::MessagePublisher;
+use crate::service::PaymentService;
+
+pub const ROUTING_KEY_HEADER: &str = "routing-key";
+pub const CONTENT_TYPE_HEADER: &str = "content-type";
+pub const MESSAGE_ID_HEADER: &str = "message-id";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KafkaSettings {
+    #[serde(default = "default_brokers")]
+    pub brokers: String,
+    // All events share one topic so that ordering per payment only depends
+    // on the key; consumers filter on the routing-key header.
+    #[serde(default = "default_topic")]
+    pub topic: String,
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+    #[serde(default = "default_group_id")]
+    pub group_id: String,
+    // How long the producer keeps retrying a message before reporting it as
+    // failed to the outbox relay.
+    #[serde(default = "default_delivery_timeout_ms")]
+    pub delivery_timeout_ms: u64,
+    // Routing key, as found in the header, of the pacs.002 status reports
+    // other parties put on the topic.
+    #[serde(default = "default_status_report_routing_key")]
+    pub status_report_routing_key: String,
+    // Passed to librdkafka as-is, e.g. security.protocol or sasl.mechanisms.
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+}
+
+fn default_brokers() -> String {
+    "localhost:9092".to_string()
+}
+
+fn default_topic() -> String {
+    "payments".to_string()
+}
+
+fn default_client_id() -> String {
+    "iso20022-payment-processor".to_string()
+}
+
+fn default_group_id() -> String {
+    "iso20022-payment-processor".to_string()
+}
+
+fn default_delivery_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_status_report_routing_key() -> String {
+    "payment.status_report".to_string()
+}
+
+impl Default for KafkaSettings {
+    fn default() -> Self {
+        Self {
+            brokers: default_brokers(),
+            topic: default_topic(),
+            client_id: default_client_id(),
+            group_id: default_group_id(),
+            delivery_timeout_ms: default_delivery_timeout_ms(),
+            status_report_routing_key: default_status_report_routing_key(),
+            properties: HashMap::new(),
+        }
+    }
+}
+
+impl KafkaSettings {
+    pub fn delivery_timeout(&self) -> Duration {
+        Duration::from_millis(self.delivery_timeout_ms)
+    }
+
+    pub fn producer_config(&self) -> ClientConfig {
+        let mut config = self.client_config();
+        // Set after the configured properties so they cannot be weakened:
+        // the broker de-duplicates the producer's own retries and keeps them
+        // in order, and a message only counts as written once all in-sync
+        // replicas have it.
+        config
+            .set("enable.idempotence", "true")
+            .set("acks", "all")
+            .set("max.in.flight.requests.per.connection", "5")
+            .set("message.timeout.ms", self.delivery_timeout_ms.to_string())
+            // Same key-to-partition mapping as the Java client.
+            .set("partitioner", "murmur2_random");
+        config
+    }
+
+    pub fn consumer_config(&self) -> ClientConfig {
+        let mut config = self.client_config();
+        // Offsets are committed by hand once an event has been handled.
+        config
+            .set("group.id", &self.group_id)
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "earliest");
+        config
+    }
+
+    fn client_config(&self) -> ClientConfig {
+        let mut config = ClientConfig::new();
+        for (key, value) in &self.properties {
+            config.set(key, value);
+        }
+        config
+            .set("bootstrap.servers", &self.brokers)
+            .set("client.id", &self.client_id);
+        config
+    }
+}
+
+// Publishes to the configured topic. Payment events are keyed by payment id,
+// so all events of a payment land on one partition and are read back in the
+// order the outbox relay sent them.
+pub struct KafkaPublisher {
+    settings: KafkaSettings,
+    routing_key_prefix: String,
+    producer: FutureProducer,
+}
+
+impl KafkaPublisher {
+    pub fn new(settings: &MessagingSettings) -> Result<Self, MessagingError> {
+        let producer = settings.kafka.producer_config().create()?;
+        Ok(Self {
+            settings: settings.kafka.clone(),
+            routing_key_prefix: settings.routing_key_prefix.clone(),
+            producer,
+        })
+    }
+
+    async fn send(&self, key: Option<&str>, routing_key: &str, message: &Value) -> Result<(), MessagingError> {
+        let payload = serde_json::to_vec(message)?;
+        let routing_key = format!("{}{}", self.routing_key_prefix, routing_key);
+        let message_id = Uuid::new_v4().to_string();
+        let headers = OwnedHeaders::new()
+            .insert(Header {
+                key: ROUTING_KEY_HEADER,
+                value: Some(routing_key.as_str()),
+            })
+            .insert(Header {
+                key: CONTENT_TYPE_HEADER,
+                value: Some("application/json"),
+            })
+            .insert(Header {
+                key: MESSAGE_ID_HEADER,
+                value: Some(message_id.as_str()),
+            });
+
+        let mut record = FutureRecord::to(&self.settings.topic).payload(&payload).headers(headers);
+        if let Some(key) = key {
+            record = record.key(key);
+        }
+        match self.producer.send(record, self.settings.delivery_timeout()).await {
+            Ok((partition, offset)) => {
+                debug!(topic = %self.settings.topic, routing_key = %routing_key, partition, offset, "Published message");
+                Ok(())
+            }
+            Err((e, _)) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl MessagePublisher for KafkaPublisher {
+    async fn publish_message(&self, routing_key: &str, message: Value) -> Result<(), MessagingError> {
+        self.send(None, routing_key, &message).await
+    }
+
+    async fn publish_payment_event(
+        &self,
+        routing_key: &str,
+        payment_id: &Uuid,
+        uetr: &Uuid,
+        message: Value,
+    ) -> Result<(), MessagingError> {
+        let key = payment_id.to_string();
+        self.send(Some(&key), routing_key, &with_uetr(message, uetr)).await
+    }
+}
+
+// An event as read back from the topic.
+#[derive(Debug, Clone)]
+pub struct ConsumedEvent {
+    pub key: Option<String>,
+    pub routing_key: Option<String>,
+    pub message_id: Option<String>,
+    pub payload: Value,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+impl ConsumedEvent {
+    pub fn decode(message: &OwnedMessage) -> Result<Self, MessagingError> {
+        let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+        let header = |name: &str| {
+            message
+                .headers()
+                .and_then(|headers| headers.iter().find(|header| header.key == name))
+                .and_then(|header| header.value)
+                .map(text)
+        };
+
+        Ok(Self {
+            key: message.key().map(text),
+            routing_key: header(ROUTING_KEY_HEADER),
+            message_id: header(MESSAGE_ID_HEADER),
+            payload: serde_json::from_slice(message.payload().unwrap_or_default())?,
+            partition: message.partition(),
+            offset: message.offset(),
+        })
+    }
+}
+
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    async fn handle(&self, event: ConsumedEvent) -> Result<(), MessagingError>;
+}
+
+// Records the status reports found on the topic on their payments' timelines.
+// Everything else there is this service's own events and is passed over.
+pub struct StatusReportHandler {
+    routing_key: String,
+    service: Arc<dyn PaymentService>,
+}
+
+impl StatusReportHandler {
+    pub fn new(settings: &KafkaSettings, service: Arc<dyn PaymentService>) -> Self {
+        Self {
+            routing_key: settings.status_report_routing_key.clone(),
+            service,
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for StatusReportHandler {
+    async fn handle(&self, event: ConsumedEvent) -> Result<(), MessagingError> {
+        if event.routing_key.as_deref() != Some(self.routing_key.as_str()) {
+            return Ok(());
+        }
+        let recorded = self
+            .service
+            .record_status_report(&event.payload)
+            .await
+            .map_err(|e| MessagingError::Handler {
+                transient: FailureClass::of(&e) == FailureClass::Transient,
+                reason: e.to_string(),
+            })?;
+        debug!(partition = event.partition, offset = event.offset, recorded, "Handled status report");
+        Ok(())
+    }
+}
+
+// Reads the events topic as part of the configured consumer group. Events
+// are handled one at a time and an offset is only committed once its event
+// has been handled, so delivery is at least once and a payment's events are
+// never seen out of order.
+pub struct KafkaEventConsumer {
+    settings: MessagingSettings,
+    consumer: StreamConsumer,
+    handler: Arc<dyn EventHandler>,
+}
+
+impl KafkaEventConsumer {
+    pub fn new(settings: MessagingSettings, handler: Arc<dyn EventHandler>) -> Result<Self, MessagingError> {
+        let consumer: StreamConsumer = settings.kafka.consumer_config().create()?;
+        consumer.subscribe(&[settings.kafka.topic.as_str()])?;
+        info!(topic = %settings.kafka.topic, group = %settings.kafka.group_id, "Consuming payment events");
+        Ok(Self {
+            settings,
+            consumer,
+            handler,
+        })
+    }
+
+    pub fn spawn(self: Arc<Self>) {
+        tokio::spawn(async move { self.run().await });
+    }
+
+    pub async fn run(&self) {
+        loop {
+            self.consume_one().await;
+        }
+    }
+
+    // Waits for the next event and handles it.
+    pub async fn consume_one(&self) {
+        let message = match self.consumer.recv().await {
+            Ok(message) => message.detach(),
+            Err(e) => {
+                warn!(topic = %self.settings.kafka.topic, "Failed to read from Kafka: {}", e);
+                tokio::time::sleep(self.settings.reconnect_delay(0)).await;
+                return;
+            }
+        };
+
+        match ConsumedEvent::decode(&message) {
+            Ok(event) => self.dispatch(event).await,
+            Err(e) => error!(
+                partition = message.partition(),
+                offset = message.offset(),
+                "Skipping event that is not JSON: {}",
+                e
+            ),
+        }
+        self.commit(&message);
+    }
+
+    // Holds the partition until the handler succeeds, so the next event of
+    // the same payment waits; only an error retrying cannot fix is skipped.
+    async fn dispatch(&self, event: ConsumedEvent) {
+        let mut attempt = 0;
+        loop {
+            match self.handler.handle(event.clone()).await {
+                Ok(()) => return,
+                Err(e) if e.is_transient() => {
+                    let delay = self.settings.reconnect_delay(attempt);
+                    warn!(
+                        partition = event.partition,
+                        offset = event.offset,
+                        attempt = attempt + 1,
+                        delay_ms = delay.as_millis() as u64,
+                        "Failed to handle event: {}",
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt = attempt.saturating_add(1);
+                }
+                Err(e) => {
+                    error!(partition = event.partition, offset = event.offset, "Skipping event: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn commit(&self, message: &OwnedMessage) {
+        // The committed offset is the next one to read.
+        let mut offsets = TopicPartitionList::new();
+        let result = offsets
+            .add_partition_offset(message.topic(), message.partition(), Offset::Offset(message.offset() + 1))
+            .and_then(|()| self.consumer.commit(&offsets, CommitMode::Async));
+        if let Err(e) = result {
+            // The event will be handled again after a rebalance.
+            warn!(partition = message.partition(), offset = message.offset(), "Failed to commit offset: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::event::PaymentTimeline;
+    use crate::domain::payment::{
+        ApprovalRecord, ApprovalRequest, HeldPaymentResponse, HoldDecisionRequest, PaymentRequest, PaymentResponse,
+        PaymentStatus, PendingApprovalResponse,
+    };
+    use crate::error::{RepositoryError, ServiceError};
+    use crate::service::limits::{HeadroomQuery, LimitHeadroom};
+    use rdkafka::Timestamp;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    // Records the status reports it is given, or fails them as a database
+    // outage while the flag is set.
+    #[derive(Default)]
+    struct StubService {
+        status_reports: Mutex<Vec<Value>>,
+        unavailable: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl PaymentService for StubService {
+        async fn process_payment(&self, _request: PaymentRequest) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_status(&self, _payment_id: &Uuid) -> Result<PaymentStatus, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_payment_by_uetr(&self, _uetr: &Uuid) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn list_held(&self) -> Result<Vec<HeldPaymentResponse>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn release_payment(
+            &self,
+            _payment_id: &Uuid,
+            _decided_by: &str,
+            _decision: HoldDecisionRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn reject_payment(
+            &self,
+            _payment_id: &Uuid,
+            _decided_by: &str,
+            _decision: HoldDecisionRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn list_pending_approval(&self) -> Result<Vec<PendingApprovalResponse>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_limit_headroom(&self, _query: &HeadroomQuery) -> Result<Vec<LimitHeadroom>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_approvals(&self, _payment_id: &Uuid) -> Result<Vec<ApprovalRecord>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_events(&self, _payment_id: &Uuid) -> Result<PaymentTimeline, ServiceError> {
+            unreachable!()
+        }
+
+        async fn record_status_report(&self, report: &Value) -> Result<usize, ServiceError> {
+            if *self.unavailable.lock().unwrap() {
+                return Err(RepositoryError::Database("connection refused".to_string()).into());
+            }
+            self.status_reports.lock().unwrap().push(report.clone());
+            Ok(1)
+        }
+
+        async fn approve_payment(
+            &self,
+            _payment_id: &Uuid,
+            _approver: &str,
+            _approval: ApprovalRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn reject_approval(
+            &self,
+            _payment_id: &Uuid,
+            _approver: &str,
+            _approval: ApprovalRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+    }
+
+    fn event(routing_key: &str, payload: Value) -> ConsumedEvent {
+        ConsumedEvent {
+            key: None,
+            routing_key: Some(routing_key.to_string()),
+            message_id: None,
+            payload,
+            partition: 0,
+            offset: 7,
+        }
+    }
+
+    #[test]
+    fn configured_properties_cannot_turn_off_idempotence() {
+        let mut settings = KafkaSettings::default();
+        settings.properties.insert("enable.idempotence".to_string(), "false".to_string());
+        settings.properties.insert("acks".to_string(), "1".to_string());
+        settings.properties.insert("security.protocol".to_string(), "SASL_SSL".to_string());
+
+        let config = settings.producer_config();
+        assert_eq!(config.get("enable.idempotence"), Some("true"));
+        assert_eq!(config.get("acks"), Some("all"));
+        assert_eq!(config.get("security.protocol"), Some("SASL_SSL"));
+        assert_eq!(config.get("bootstrap.servers"), Some("localhost:9092"));
+
+        let config = settings.consumer_config();
+        assert_eq!(config.get("enable.auto.commit"), Some("false"));
+        assert_eq!(config.get("group.id"), Some("iso20022-payment-processor"));
+    }
+
+    #[test]
+    fn decodes_key_headers_and_payload() {
+        let payment_id = Uuid::new_v4();
+        let headers = OwnedHeaders::new().insert(Header {
+            key: ROUTING_KEY_HEADER,
+            value: Some("payment.received"),
+        });
+        let message = OwnedMessage::new(
+            Some(json!({ "id": payment_id }).to_string().into_bytes()),
+            Some(payment_id.to_string().into_bytes()),
+            "payments".to_string(),
+            Timestamp::NotAvailable,
+            3,
+            42,
+            Some(headers),
+        );
+
+        let event = ConsumedEvent::decode(&message).unwrap();
+        assert_eq!(event.key, Some(payment_id.to_string()));
+        assert_eq!(event.routing_key.as_deref(), Some("payment.received"));
+        assert_eq!(event.message_id, None);
+        assert_eq!(event.payload["id"], json!(payment_id));
+        assert_eq!((event.partition, event.offset), (3, 42));
+    }
+
+    #[tokio::test]
+    async fn records_status_reports_and_passes_over_own_events() {
+        let service = Arc::new(StubService::default());
+        let handler = StatusReportHandler::new(&KafkaSettings::default(), service.clone());
+        let report = json!({ "GrpHdr": { "MsgId": "REPORT-1" } });
+
+        handler.handle(event("payment.received", json!({ "id": 1 }))).await.unwrap();
+        handler.handle(event("payment.status_report", report.clone())).await.unwrap();
+        assert_eq!(*service.status_reports.lock().unwrap(), vec![report.clone()]);
+
+        *service.unavailable.lock().unwrap() = true;
+        let err = handler.handle(event("payment.status_report", report)).await.unwrap_err();
+        assert!(err.is_transient());
+    }
+}
@@ -1,14 +1,293 @@
 use async_trait::async_trait;
+use chrono::Utc;
+use lapin::options::{BasicPublishOptions, ConfirmSelectOptions, ExchangeDeclareOptions};
+use lapin::publisher_confirm::Confirmation;
+use lapin::types::FieldTable;
+use lapin::{BasicProperties, Channel, Connection, ConnectionProperties, ExchangeKind};
 use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use uuid::Uuid;
 
+use crate::config::MessagingSettings;
+use crate::domain::dead_letter::DeadLetter;
 use crate::error::MessagingError;
+use crate::infrastructure::messaging::dead_letters::DeadLetterQueue;
+
+// AMQP delivery mode 2: the broker writes the message to disk.
+const PERSISTENT: u8 = 2;
 
 #[async_trait]
 pub trait MessagePublisher: Send + Sync {
     async fn publish_message(&self, routing_key: &str, message: Value) -> Result<(), MessagingError>;
+
+    // `payment_id` lets a backend keep one payment's events in order.
+    async fn publish_payment_event(
+        &self,
+        routing_key: &str,
+        _payment_id: &Uuid,
+        uetr: &Uuid,
+        message: Value,
+    ) -> Result<(), MessagingError> {
+        self.publish_message(routing_key, with_uetr(message, uetr)).await
+    }
 }
 
+pub(crate) fn with_uetr(mut message: Value, uetr: &Uuid) -> Value {
+    if let Value::Object(fields) = &mut message {
+        fields.insert("uetr".to_string(), Value::String(uetr.to_string()));
+    }
+    message
+}
+
+// Publishes to the configured exchange with publisher confirms, so a message
+// only counts as sent once the broker has taken responsibility for it. The
+// connection is opened on first use and reopened, with backoff, whenever the
+// connection or channel is found closed.
 pub struct RabbitMQPublisher {
-    connection: lapin::Connection,
-    channel: lapin::Channel,
+    settings: MessagingSettings,
+    link: Mutex<Option<Link>>,
+}
+
+struct Link {
+    connection: Connection,
+    channel: Channel,
+}
+
+impl RabbitMQPublisher {
+    pub fn new(settings: MessagingSettings) -> Self {
+        Self {
+            settings,
+            link: Mutex::new(None),
+        }
+    }
+
+    async fn channel(&self) -> Result<Channel, MessagingError> {
+        let mut link = self.link.lock().await;
+        if let Some(current) = link.as_mut() {
+            if current.connection.status().connected() {
+                if current.channel.status().connected() {
+                    return Ok(current.channel.clone());
+                }
+                warn!(exchange = %self.settings.exchange, "Broker channel closed, opening a new one");
+                match self.open_channel(&current.connection).await {
+                    Ok(channel) => {
+                        current.channel = channel.clone();
+                        return Ok(channel);
+                    }
+                    Err(e) => warn!("Failed to reopen broker channel: {}", e),
+                }
+            }
+        }
+
+        *link = None;
+        let mut attempt = 0;
+        loop {
+            match self.open().await {
+                Ok(opened) => {
+                    let channel = opened.channel.clone();
+                    *link = Some(opened);
+                    if attempt > 0 {
+                        info!(attempts = attempt + 1, "Reconnected to broker");
+                    }
+                    return Ok(channel);
+                }
+                Err(e) if attempt + 1 < self.settings.reconnect_attempts => {
+                    let delay = self.settings.reconnect_delay(attempt);
+                    warn!(attempt = attempt + 1, delay_ms = delay.as_millis() as u64, "Broker connection failed: {}", e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn open(&self) -> Result<Link, MessagingError> {
+        let properties = ConnectionProperties::default().with_connection_name("iso20022-payment-processor".into());
+        let connection = Connection::connect(&self.settings.url, properties).await?;
+        let channel = self.open_channel(&connection).await?;
+        Ok(Link { connection, channel })
+    }
+
+    async fn open_channel(&self, connection: &Connection) -> Result<Channel, MessagingError> {
+        let channel = connection.create_channel().await?;
+        channel.confirm_select(ConfirmSelectOptions::default()).await?;
+        channel
+            .exchange_declare(
+                &self.settings.exchange,
+                exchange_kind(&self.settings.exchange_kind),
+                ExchangeDeclareOptions {
+                    durable: true,
+                    ..ExchangeDeclareOptions::default()
+                },
+                FieldTable::default(),
+            )
+            .await?;
+        Ok(channel)
+    }
+
+    // Drops the link after a connection-level failure so the next publish
+    // reconnects instead of reusing a half-closed channel.
+    async fn reset(&self, error: &MessagingError) {
+        if matches!(error, MessagingError::Connection(_) | MessagingError::Channel(_)) {
+            *self.link.lock().await = None;
+        }
+    }
+
+}
+
+#[async_trait]
+impl MessagePublisher for RabbitMQPublisher {
+    async fn publish_message(&self, routing_key: &str, message: Value) -> Result<(), MessagingError> {
+        let payload = serde_json::to_vec(&message)?;
+        let routing_key = format!("{}{}", self.settings.routing_key_prefix, routing_key);
+        let result = match self.channel().await {
+            Ok(channel) => {
+                let exchange = &self.settings.exchange;
+                publish_confirmed(&channel, &self.settings, exchange, &routing_key, &payload, json_properties()).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &result {
+            self.reset(e).await;
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl DeadLetterQueue for RabbitMQPublisher {
+    async fn resubmit(&self, letter: &DeadLetter, payload: &[u8]) -> Result<(), MessagingError> {
+        // Without the failure headers the message starts over with a full
+        // set of retries.
+        let mut properties = json_properties();
+        if let Some(message_id) = &letter.message_id {
+            properties = properties.with_message_id(message_id.clone().into());
+        }
+        if let Some(message_type) = &letter.message_type {
+            properties = properties.with_type(message_type.clone().into());
+        }
+        if let Some(correlation_id) = &letter.correlation_id {
+            properties = properties.with_correlation_id(correlation_id.clone().into());
+        }
+        if let Some(reply_to) = &letter.reply_to {
+            properties = properties.with_reply_to(reply_to.clone().into());
+        }
+
+        let result = match self.channel().await {
+            // The default exchange routes by queue name.
+            Ok(channel) => publish_confirmed(&channel, &self.settings, "", &letter.queue, payload, properties).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &result {
+            self.reset(e).await;
+        }
+        result
+    }
+}
+
+pub(crate) fn json_properties() -> BasicProperties {
+    BasicProperties::default()
+        .with_content_type("application/json".into())
+        .with_delivery_mode(PERSISTENT)
+        .with_message_id(Uuid::new_v4().to_string().into())
+        .with_timestamp(Utc::now().timestamp() as u64)
+}
+
+// Publishes on a channel in confirm mode and waits for the broker's verdict.
+pub(crate) async fn publish_confirmed(
+    channel: &Channel,
+    settings: &MessagingSettings,
+    exchange: &str,
+    routing_key: &str,
+    payload: &[u8],
+    properties: BasicProperties,
+) -> Result<(), MessagingError> {
+    let confirm = channel
+        .basic_publish(
+            exchange,
+            routing_key,
+            BasicPublishOptions {
+                mandatory: settings.mandatory,
+                ..BasicPublishOptions::default()
+            },
+            payload,
+            properties,
+        )
+        .await?;
+    let confirmation = tokio::time::timeout(settings.confirm_timeout(), confirm)
+        .await
+        .map_err(|_| MessagingError::ConfirmTimeout {
+            routing_key: routing_key.to_string(),
+            timeout_ms: settings.confirm_timeout_ms,
+        })??;
+
+    match confirmation {
+        Confirmation::Ack(Some(returned)) => Err(MessagingError::Unroutable {
+            routing_key: routing_key.to_string(),
+            reply_text: returned.reply_text.to_string(),
+        }),
+        Confirmation::Ack(None) | Confirmation::NotRequested => Ok(()),
+        Confirmation::Nack(_) => Err(MessagingError::Nacked {
+            routing_key: routing_key.to_string(),
+        }),
+    }
+}
+
+fn exchange_kind(kind: &str) -> ExchangeKind {
+    match kind {
+        "direct" => ExchangeKind::Direct,
+        "fanout" => ExchangeKind::Fanout,
+        "headers" => ExchangeKind::Headers,
+        "topic" => ExchangeKind::Topic,
+        other => ExchangeKind::Custom(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MessagingBackend;
+    use crate::infrastructure::messaging::kafka::KafkaSettings;
+    use crate::infrastructure::messaging::retry::RetrySettings;
+    use std::sync::Arc;
+
+    #[test]
+    fn maps_broker_failures_to_typed_errors() {
+        let io = lapin::Error::IOError(Arc::new(std::io::Error::from(std::io::ErrorKind::ConnectionReset)));
+        assert!(matches!(MessagingError::from(io), MessagingError::Connection(_)));
+
+        let closed = lapin::Error::InvalidChannelState(lapin::ChannelState::Closed);
+        let error = MessagingError::from(closed);
+        assert!(matches!(error, MessagingError::Channel(_)));
+        assert!(error.is_transient());
+
+        let unroutable = MessagingError::Unroutable {
+            routing_key: "payment.received".to_string(),
+            reply_text: "NO_ROUTE".to_string(),
+        };
+        assert!(!unroutable.is_transient());
+    }
+
+    #[test]
+    fn reconnect_backoff_is_capped() {
+        let settings = MessagingSettings {
+            backend: MessagingBackend::RabbitMq,
+            url: "amqp://localhost".to_string(),
+            exchange: "payments".to_string(),
+            exchange_kind: "topic".to_string(),
+            routing_key_prefix: String::new(),
+            mandatory: true,
+            confirm_timeout_ms: 5_000,
+            reconnect_attempts: 5,
+            reconnect_initial_delay_ms: 200,
+            reconnect_max_delay_ms: 1_000,
+            inbound: Vec::new(),
+            retry: RetrySettings::default(),
+            kafka: KafkaSettings::default(),
+        };
+        let delays: Vec<u64> = (0..5).map(|attempt| settings.reconnect_delay(attempt).as_millis() as u64).collect();
+        assert_eq!(delays, vec![200, 400, 800, 1_000, 1_000]);
+    }
 }
@@ -0,0 +1,8 @@
+pub mod dead_letters;
+pub mod kafka;
+pub mod message_publisher;
+pub mod outbox_relay;
+pub mod payment_consumer;
+pub mod retry;
+
+pub use message_publisher::MessagePublisher;
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::{error, warn};
+
+use crate::domain::event::PaymentEventKind;
+use crate::error::RepositoryError;
+use crate::infrastructure::database::events::EventStore;
+use crate::infrastructure::database::outbox::OutboxStore;
+use crate::infrastructure::messaging::MessagePublisher;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutboxSettings {
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    // How long a relay may hold a claimed entry before another relay may
+    // pick it up again, e.g. after a crash mid-publish.
+    #[serde(default = "default_claim_timeout_seconds")]
+    pub claim_timeout_seconds: u64,
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_poll_interval_ms() -> u64 {
+    500
+}
+
+fn default_claim_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1_000
+}
+
+impl Default for OutboxSettings {
+    fn default() -> Self {
+        Self {
+            batch_size: default_batch_size(),
+            poll_interval_ms: default_poll_interval_ms(),
+            claim_timeout_seconds: default_claim_timeout_seconds(),
+            retry_delay_ms: default_retry_delay_ms(),
+        }
+    }
+}
+
+// Drains the outbox to the broker. Delivery is at least once: a crash between
+// publishing and marking the entry sends it again after the claim expires, so
+// consumers must tolerate duplicates (the UETR identifies the payment).
+pub struct OutboxRelay {
+    store: Arc<dyn OutboxStore>,
+    publisher: Arc<dyn MessagePublisher>,
+    settings: OutboxSettings,
+    events: Option<Arc<dyn EventStore>>,
+}
+
+impl OutboxRelay {
+    pub fn new(store: Arc<dyn OutboxStore>, publisher: Arc<dyn MessagePublisher>, settings: OutboxSettings) -> Self {
+        Self {
+            store,
+            publisher,
+            settings,
+            events: None,
+        }
+    }
+
+    pub fn with_events(mut self, events: Arc<dyn EventStore>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    // Publishes one batch and returns how many entries went out.
+    pub async fn relay_once(&self) -> Result<usize, RepositoryError> {
+        let now = Utc::now();
+        let until = now + chrono::Duration::seconds(self.settings.claim_timeout_seconds as i64);
+        let entries = self.store.claim(self.settings.batch_size, now, until).await?;
+
+        let mut published = 0;
+        for entry in entries {
+            let result = self
+                .publisher
+                .publish_payment_event(&entry.routing_key, &entry.payment_id, &entry.uetr, entry.payload.clone())
+                .await;
+            match result {
+                Ok(()) => {
+                    let at = Utc::now();
+                    self.store.mark_published(entry.id, at).await?;
+                    published += 1;
+                    if let Some(events) = &self.events {
+                        let event = PaymentEventKind::Published {
+                            routing_key: entry.routing_key.clone(),
+                        };
+                        if let Err(e) = events.append(&entry.payment_id, at, vec![event]).await {
+                            error!(payment_id = %entry.payment_id, "Failed to record payment events: {:?}", e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        outbox_id = entry.id,
+                        payment_id = %entry.payment_id,
+                        routing_key = %entry.routing_key,
+                        attempts = entry.attempts + 1,
+                        "Failed to publish outbox entry: {}",
+                        e
+                    );
+                    let retry_at = Utc::now() + chrono::Duration::milliseconds(self.settings.retry_delay_ms as i64);
+                    self.store.mark_failed(entry.id, &e.to_string(), retry_at).await?;
+                }
+            }
+        }
+        Ok(published)
+    }
+
+    pub async fn run(self) {
+        loop {
+            match self.relay_once().await {
+                Ok(published) if published > 0 => continue,
+                Ok(_) => {}
+                Err(e) => error!("Outbox relay failed: {:?}", e),
+            }
+            tokio::time::sleep(Duration::from_millis(self.settings.poll_interval_ms)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MessagingError;
+    use crate::infrastructure::database::outbox::{InMemoryOutboxStore, OutboxMessage};
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    // Fails the first publish of every routing key listed in `fail_once`.
+    #[derive(Default)]
+    struct RecordingPublisher {
+        published: Mutex<Vec<String>>,
+        fail_once: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl MessagePublisher for RecordingPublisher {
+        async fn publish_message(&self, routing_key: &str, _message: Value) -> Result<(), MessagingError> {
+            let mut fail_once = self.fail_once.lock().unwrap();
+            if let Some(position) = fail_once.iter().position(|key| key == routing_key) {
+                fail_once.remove(position);
+                return Err(MessagingError::PublishFailed("broker unavailable".to_string()));
+            }
+            self.published.lock().unwrap().push(routing_key.to_string());
+            Ok(())
+        }
+    }
+
+    fn message(payment_id: Uuid, routing_key: &str) -> OutboxMessage {
+        OutboxMessage {
+            payment_id,
+            uetr: Uuid::new_v4(),
+            routing_key: routing_key.to_string(),
+            payload: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_per_payment_order_across_failures() {
+        let store = Arc::new(InMemoryOutboxStore::new());
+        let publisher = Arc::new(RecordingPublisher::default());
+        publisher.fail_once.lock().unwrap().push("a.held".to_string());
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let at = Utc::now() - chrono::Duration::seconds(5);
+        for (payment_id, key) in [(a, "a.held"), (b, "b.received"), (a, "a.received")] {
+            store.enqueue(message(payment_id, key), at).await;
+        }
+        let relay = OutboxRelay::new(
+            store.clone(),
+            publisher.clone(),
+            OutboxSettings {
+                retry_delay_ms: 0,
+                ..OutboxSettings::default()
+            },
+        );
+
+        // a.held fails, and a.received must wait behind it.
+        assert_eq!(relay.relay_once().await.unwrap(), 1);
+        assert_eq!(*publisher.published.lock().unwrap(), vec!["b.received"]);
+
+        while relay.relay_once().await.unwrap() > 0 {}
+        assert_eq!(*publisher.published.lock().unwrap(), vec!["b.received", "a.held", "a.received"]);
+        assert_eq!(store.unpublished().await, 0);
+    }
+}
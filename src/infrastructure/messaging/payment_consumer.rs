@@ -0,0 +1,539 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use lapin::message::Delivery;
+use lapin::options::{
+    BasicAckOptions, BasicConsumeOptions, BasicNackOptions, BasicQosOptions, ConfirmSelectOptions, QueueDeclareOptions,
+};
+use lapin::types::FieldTable;
+use lapin::{Channel, Connection, ConnectionProperties};
+use metrics::increment_counter;
+use serde_json::Value;
+use tracing::{error, info, warn};
+
+use crate::config::{InboundQueue, MessagingSettings};
+use crate::domain::payment::PaymentRequest;
+use crate::domain::payment_details::find;
+use crate::domain::status_reason::{ReasonCode, Violation};
+use crate::domain::status_report::{self, OriginalMessage};
+use crate::error::{MessagingError, ServiceError};
+use crate::infrastructure::messaging::message_publisher::{json_properties, publish_confirmed};
+use crate::infrastructure::messaging::retry::{self, Failure, FailureClass, Route};
+use crate::service::PaymentService;
+
+const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+// What the consumer needs from an AMQP delivery.
+#[derive(Debug, Clone, Default)]
+pub struct InboundMessage {
+    pub body: Vec<u8>,
+    pub message_id: Option<String>,
+    pub message_type: Option<String>,
+    pub reply_to: Option<String>,
+    pub correlation_id: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Disposition {
+    // The outcome is final: send the pacs.002, then acknowledge.
+    Reply { report: Value, reply_to: Option<String> },
+    // Handled and nothing to answer, e.g. a status report from the next party.
+    Ack,
+    // Nothing was stored; retry or dead-letter according to the failure class.
+    Fail(Failure),
+}
+
+// Feeds ISO messages from upstream queues through the same pipeline as
+// `POST /payments`, using the message id as the request id. The payments
+// table allows one payment per sender and request id, and the service answers
+// a repeat of a stored request with the stored payment, so a redelivered
+// message is reported on again instead of creating a second payment. A
+// delivery is acknowledged once its report is confirmed by the broker or it
+// has been moved to a retry or dead-letter queue.
+pub struct PaymentConsumer {
+    settings: MessagingSettings,
+    service: Arc<dyn PaymentService>,
+}
+
+impl PaymentConsumer {
+    pub fn new(settings: MessagingSettings, service: Arc<dyn PaymentService>) -> Self {
+        Self { settings, service }
+    }
+
+    pub fn spawn(self: Arc<Self>) {
+        for inbound in self.settings.inbound.clone() {
+            let consumer = self.clone();
+            tokio::spawn(async move { consumer.consume(inbound).await });
+        }
+    }
+
+    pub async fn handle(&self, inbound: &InboundQueue, message: InboundMessage) -> Disposition {
+        let reply_to = message.reply_to.clone().or_else(|| inbound.reply_queue.clone());
+        let message_type = message.message_type.clone().or_else(|| inbound.message_type.clone());
+        let mut original = OriginalMessage {
+            message_id: message.message_id.clone().unwrap_or_else(|| NOT_PROVIDED.to_string()),
+            message_type: message_type.clone().unwrap_or_else(|| NOT_PROVIDED.to_string()),
+        };
+        let reject = |original: &OriginalMessage, violation: Violation, payload: Option<&Value>| Disposition::Reply {
+            report: status_report::rejected(original, &[violation], payload),
+            reply_to: reply_to.clone(),
+        };
+
+        let payload: Value = match serde_json::from_slice(&message.body) {
+            Ok(payload) => payload,
+            Err(e) => {
+                let violation = Violation::new("schema", None, ReasonCode::FF01, format!("Message is not valid JSON: {}", e));
+                return reject(&original, violation, None);
+            }
+        };
+        // Status reports go on the timeline of the payments they report on;
+        // they are not payments themselves and get no report back.
+        if message_type.as_deref().is_some_and(|message_type| message_type.starts_with("pacs.002")) {
+            return match self.service.record_status_report(&payload).await {
+                Ok(_) => Disposition::Ack,
+                Err(e) => Disposition::Fail(Failure::new(FailureClass::of(&e), e.to_string())),
+            };
+        }
+        let message_id = message
+            .message_id
+            .clone()
+            .or_else(|| find(&payload, "MsgId").and_then(Value::as_str).map(str::to_string));
+        let (message_id, message_type) = match (message_id, message_type) {
+            (Some(message_id), Some(message_type)) => (message_id, message_type),
+            (message_id, _) => {
+                original.message_id = message_id.unwrap_or_else(|| NOT_PROVIDED.to_string());
+                let violation = Violation::new(
+                    "envelope",
+                    None,
+                    ReasonCode::CH21,
+                    "Message needs a message id (AMQP message-id or GrpHdr/MsgId) and a message type",
+                );
+                return reject(&original, violation, Some(&payload));
+            }
+        };
+        original.message_id = message_id.clone();
+
+        let request = PaymentRequest {
+            message_type,
+            payment_type: inbound.payment_type,
+            message_payload: payload.clone(),
+            sender_id: inbound.sender_id.clone(),
+            request_id: message_id.clone(),
+            channel: Some(inbound.channel.clone()),
+            uetr: None,
+            submitted_by: None,
+        };
+        let report = match self.service.process_payment(request).await {
+            Ok(response) => status_report::accepted(&original, &response),
+            Err(ServiceError::Validation(e)) | Err(ServiceError::BusinessRule(e)) => {
+                status_report::rejected(&original, &e.violations(), Some(&payload))
+            }
+            Err(e) => return Disposition::Fail(Failure::new(FailureClass::of(&e), e.to_string())),
+        };
+        Disposition::Reply { report, reply_to }
+    }
+
+    async fn consume(&self, inbound: InboundQueue) {
+        let mut attempt = 0;
+        loop {
+            match self.consume_until_closed(&inbound).await {
+                Ok(()) => {
+                    attempt = 0;
+                    warn!(queue = %inbound.queue, "Broker closed the consumer");
+                }
+                Err(e) => warn!(queue = %inbound.queue, "Payment consumer failed: {}", e),
+            }
+            tokio::time::sleep(self.settings.reconnect_delay(attempt)).await;
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    async fn consume_until_closed(&self, inbound: &InboundQueue) -> Result<(), MessagingError> {
+        let name = format!("iso20022-payment-processor:{}", inbound.queue);
+        let connection = Connection::connect(&self.settings.url, ConnectionProperties::default().with_connection_name(name.into())).await?;
+        let channel = connection.create_channel().await?;
+        channel.basic_qos(inbound.prefetch, BasicQosOptions::default()).await?;
+        channel.confirm_select(ConfirmSelectOptions::default()).await?;
+        channel
+            .queue_declare(
+                &inbound.queue,
+                QueueDeclareOptions {
+                    durable: true,
+                    ..QueueDeclareOptions::default()
+                },
+                FieldTable::default(),
+            )
+            .await?;
+        retry::declare_dead_letter_queue(&channel, &inbound.queue).await?;
+        let mut deliveries = channel
+            .basic_consume(
+                &inbound.queue,
+                "payment-consumer",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+        info!(queue = %inbound.queue, sender_id = %inbound.sender_id, "Consuming payment messages");
+
+        while let Some(delivery) = deliveries.next().await {
+            self.dispatch(&channel, inbound, delivery?).await?;
+        }
+        Ok(())
+    }
+
+    async fn dispatch(&self, channel: &Channel, inbound: &InboundQueue, delivery: Delivery) -> Result<(), MessagingError> {
+        let text = |value: &Option<lapin::types::ShortString>| value.as_ref().map(|value| value.as_str().to_string());
+        let message = InboundMessage {
+            body: delivery.data.clone(),
+            message_id: text(delivery.properties.message_id()),
+            message_type: text(delivery.properties.kind()),
+            reply_to: text(delivery.properties.reply_to()),
+            correlation_id: text(delivery.properties.correlation_id()),
+        };
+        let correlation_id = message.correlation_id.clone().or_else(|| message.message_id.clone());
+
+        match self.handle(inbound, message).await {
+            Disposition::Reply { report, reply_to } => {
+                match reply_to {
+                    Some(reply_to) => {
+                        let mut properties = json_properties();
+                        if let Some(correlation_id) = correlation_id {
+                            properties = properties.with_correlation_id(correlation_id.into());
+                        }
+                        let payload = serde_json::to_vec(&report)?;
+                        // Publishing to the default exchange routes by queue name.
+                        let sent = publish_confirmed(channel, &self.settings, "", &reply_to, &payload, properties).await;
+                        // The payment is stored either way; a retried or
+                        // replayed message gets the stored report again.
+                        if let Err(e) = sent {
+                            let reason = format!("Failed to send status report to {}: {}", reply_to, e);
+                            let failure = Failure::new(FailureClass::of_messaging(&e), reason);
+                            return self.fail(channel, inbound, &delivery, failure).await;
+                        }
+                    }
+                    None => warn!(queue = %inbound.queue, "No reply queue for status report"),
+                }
+                delivery.acker.ack(BasicAckOptions::default()).await?;
+            }
+            Disposition::Ack => delivery.acker.ack(BasicAckOptions::default()).await?,
+            Disposition::Fail(failure) => return self.fail(channel, inbound, &delivery, failure).await,
+        }
+        Ok(())
+    }
+
+    // Moves a failed delivery to a retry queue or the dead-letter queue with
+    // the failure recorded in its headers, then acknowledges the original.
+    async fn fail(
+        &self,
+        channel: &Channel,
+        inbound: &InboundQueue,
+        delivery: &Delivery,
+        failure: Failure,
+    ) -> Result<(), MessagingError> {
+        let attempt = retry::attempts(&delivery.properties) + 1;
+        let route = self.settings.retry.route(failure.class, attempt);
+        let properties = retry::failed_properties(&delivery.properties, &inbound.queue, &failure, attempt);
+        let destination = match &route {
+            Route::Retry { delay } => retry::declare_retry_queue(channel, &inbound.queue, *delay).await,
+            Route::DeadLetter => Ok(retry::dead_letter_queue(&inbound.queue)),
+        };
+        let routed = match &destination {
+            Ok(destination) => publish_confirmed(channel, &self.settings, "", destination, &delivery.data, properties).await,
+            Err(_) => Ok(()),
+        };
+
+        match destination.and(routed) {
+            Ok(()) => {
+                let class = failure.class.as_str();
+                match route {
+                    Route::Retry { delay } => {
+                        increment_counter!("payment_consumer_retries_total", "queue" => inbound.queue.clone(), "class" => class);
+                        warn!(
+                            queue = %inbound.queue,
+                            class,
+                            attempt,
+                            delay_ms = delay.as_millis() as u64,
+                            "Retrying payment message: {}",
+                            failure.reason
+                        );
+                    }
+                    Route::DeadLetter => {
+                        increment_counter!("payment_consumer_dead_letters_total", "queue" => inbound.queue.clone(), "class" => class);
+                        error!(queue = %inbound.queue, class, attempt, "Dead-lettered payment message: {}", failure.reason);
+                    }
+                }
+                delivery.acker.ack(BasicAckOptions::default()).await?;
+            }
+            // Nowhere to park it; hand it back to the broker rather than lose it.
+            Err(e) => {
+                error!(queue = %inbound.queue, "Failed to route failed payment message: {}", e);
+                delivery.acker.nack(requeue()).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn requeue() -> BasicNackOptions {
+    BasicNackOptions {
+        requeue: true,
+        ..BasicNackOptions::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::event::PaymentTimeline;
+    use crate::domain::payment::{
+        ApprovalRecord, ApprovalRequest, HeldPaymentResponse, HoldDecisionRequest, Payment, PaymentResponse,
+        PaymentStatus, PaymentType, PendingApprovalResponse,
+    };
+    use crate::error::{RepositoryError, ValidationError};
+    use crate::service::limits::{HeadroomQuery, LimitHeadroom};
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    // Accepts every payment except those whose payload carries "reject" (the
+    // pointer of the violation, if a string), or
+    // "unavailable" for as long as the flag is set. Like the real service, a
+    // request id it has stored gets the stored payment back.
+    #[derive(Default)]
+    struct StubService {
+        processed: Mutex<Vec<PaymentRequest>>,
+        stored: Mutex<HashMap<String, Payment>>,
+        status_reports: Mutex<Vec<Value>>,
+        unavailable: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl PaymentService for StubService {
+        async fn process_payment(&self, request: PaymentRequest) -> Result<PaymentResponse, ServiceError> {
+            if *self.unavailable.lock().unwrap() {
+                return Err(RepositoryError::Database("connection refused".to_string()).into());
+            }
+            if let Some(reject) = request.message_payload.get("reject") {
+                return Err(ValidationError::RuleViolation {
+                    rule: "identifier".to_string(),
+                    path: Some(reject.as_str().unwrap_or("/DbtrAcct/Id/IBAN").to_string()),
+                    reason_code: ReasonCode::AC01,
+                    message: "invalid IBAN".to_string(),
+                }
+                .into());
+            }
+            let mut stored = self.stored.lock().unwrap();
+            if let Some(payment) = stored.get(&request.request_id) {
+                return Ok(PaymentResponse::from(payment));
+            }
+            self.processed.lock().unwrap().push(request.clone());
+            let payment = Payment::new(request.clone(), Uuid::new_v4(), Vec::new(), Vec::new());
+            let response = PaymentResponse::from(&payment);
+            stored.insert(request.request_id, payment);
+            Ok(response)
+        }
+
+        async fn get_status(&self, _payment_id: &Uuid) -> Result<PaymentStatus, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_payment_by_uetr(&self, _uetr: &Uuid) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn list_held(&self) -> Result<Vec<HeldPaymentResponse>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn release_payment(
+            &self,
+            _payment_id: &Uuid,
+            _decided_by: &str,
+            _decision: HoldDecisionRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn reject_payment(
+            &self,
+            _payment_id: &Uuid,
+            _decided_by: &str,
+            _decision: HoldDecisionRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn list_pending_approval(&self) -> Result<Vec<PendingApprovalResponse>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_limit_headroom(&self, _query: &HeadroomQuery) -> Result<Vec<LimitHeadroom>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_approvals(&self, _payment_id: &Uuid) -> Result<Vec<ApprovalRecord>, ServiceError> {
+            unreachable!()
+        }
+
+        async fn get_events(&self, _payment_id: &Uuid) -> Result<PaymentTimeline, ServiceError> {
+            unreachable!()
+        }
+
+        async fn record_status_report(&self, report: &Value) -> Result<usize, ServiceError> {
+            self.status_reports.lock().unwrap().push(report.clone());
+            Ok(1)
+        }
+
+        async fn approve_payment(
+            &self,
+            _payment_id: &Uuid,
+            _approver: &str,
+            _approval: ApprovalRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+
+        async fn reject_approval(
+            &self,
+            _payment_id: &Uuid,
+            _approver: &str,
+            _approval: ApprovalRequest,
+        ) -> Result<PaymentResponse, ServiceError> {
+            unreachable!()
+        }
+    }
+
+    fn inbound() -> InboundQueue {
+        InboundQueue {
+            queue: "upstream.core-banking".to_string(),
+            sender_id: "core-banking".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_type: Some("pacs.008".to_string()),
+            reply_queue: Some("upstream.core-banking.reports".to_string()),
+            channel: "amqp".to_string(),
+            prefetch: 10,
+        }
+    }
+
+    fn consumer(service: Arc<StubService>) -> PaymentConsumer {
+        let settings: MessagingSettings = serde_json::from_value(json!({ "url": "amqp://localhost" })).unwrap();
+        PaymentConsumer::new(settings, service)
+    }
+
+    fn message(body: Value) -> InboundMessage {
+        InboundMessage {
+            body: serde_json::to_vec(&body).unwrap(),
+            ..InboundMessage::default()
+        }
+    }
+
+    fn report(disposition: Disposition) -> Value {
+        match disposition {
+            Disposition::Reply { report, reply_to } => {
+                assert_eq!(reply_to.as_deref(), Some("upstream.core-banking.reports"));
+                report
+            }
+            other => panic!("expected a report, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn redelivery_replays_the_report_without_a_second_payment() {
+        let service = Arc::new(StubService::default());
+        let consumer = consumer(service.clone());
+        let body = json!({ "GrpHdr": { "MsgId": "MSG-1" }, "CdtTrfTxInf": [] });
+
+        let first = report(consumer.handle(&inbound(), message(body.clone())).await);
+        let again = report(consumer.handle(&inbound(), message(body)).await);
+
+        assert_eq!(first["TxInfAndSts"][0]["TxSts"], "ACTC");
+        assert_eq!(first["TxInfAndSts"], again["TxInfAndSts"]);
+        let processed = service.processed.lock().unwrap();
+        assert_eq!(processed.len(), 1);
+        assert_eq!(processed[0].request_id, "MSG-1");
+        assert_eq!(processed[0].sender_id, "core-banking");
+    }
+
+    #[tokio::test]
+    async fn rejects_with_status_reasons() {
+        let consumer = consumer(Arc::new(StubService::default()));
+
+        let rejected = report(consumer.handle(&inbound(), message(json!({ "GrpHdr": { "MsgId": "MSG-2" }, "reject": true }))).await);
+        assert_eq!(rejected["TxInfAndSts"][0]["TxSts"], "RJCT");
+        assert_eq!(rejected["TxInfAndSts"][0]["StsRsnInf"][0]["Rsn"]["Cd"], "AC01");
+
+        let anonymous = report(consumer.handle(&inbound(), message(json!({ "CdtTrfTxInf": [] }))).await);
+        assert_eq!(anonymous["OrgnlGrpInfAndSts"]["OrgnlMsgId"], "NOTPROVIDED");
+        assert_eq!(anonymous["TxInfAndSts"][0]["StsRsnInf"][0]["Rsn"]["Cd"], "CH21");
+    }
+
+    #[tokio::test]
+    async fn transient_failures_leave_the_message_retryable() {
+        let service = Arc::new(StubService::default());
+        *service.unavailable.lock().unwrap() = true;
+        let consumer = consumer(service.clone());
+        let body = json!({ "GrpHdr": { "MsgId": "MSG-3" } });
+
+        match consumer.handle(&inbound(), message(body.clone())).await {
+            Disposition::Fail(failure) => assert_eq!(failure.class, FailureClass::Transient),
+            other => panic!("expected a failure, got {:?}", other),
+        }
+
+        *service.unavailable.lock().unwrap() = false;
+        let retried = report(consumer.handle(&inbound(), message(body)).await);
+        assert_eq!(retried["TxInfAndSts"][0]["TxSts"], "ACTC");
+    }
+
+    #[tokio::test]
+    async fn reports_every_transaction() {
+        let consumer = consumer(Arc::new(StubService::default()));
+        let uetr = Uuid::new_v4().to_string();
+        let transactions = json!([
+            { "PmtId": { "EndToEndId": "E2E-1", "TxId": "TX-1", "UETR": uetr } },
+            { "PmtId": { "EndToEndId": "E2E-2", "TxId": "TX-2" }, "DbtrAcct": { "Id": { "IBAN": "XX" } } }
+        ]);
+
+        let accepted = report(
+            consumer
+                .handle(&inbound(), message(json!({ "GrpHdr": { "MsgId": "MSG-4" }, "CdtTrfTxInf": transactions })))
+                .await,
+        );
+        let entries = accepted["TxInfAndSts"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["OrgnlEndToEndId"], "E2E-1");
+        assert_eq!(entries[0]["OrgnlTxId"], "TX-1");
+        assert_eq!(entries[0]["OrgnlUETR"], uetr.as_str());
+        assert_eq!(entries[1]["OrgnlTxId"], "TX-2");
+
+        let rejected = report(
+            consumer
+                .handle(
+                    &inbound(),
+                    message(json!({ "GrpHdr": { "MsgId": "MSG-5" }, "CdtTrfTxInf": transactions, "reject": "/CdtTrfTxInf/1/DbtrAcct/Id/IBAN" })),
+                )
+                .await,
+        );
+        let entries = rejected["TxInfAndSts"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["OrgnlUETR"], uetr.as_str());
+        assert_eq!(entries[0]["TxSts"], "RJCT");
+        // The violation points into the second transaction only.
+        assert!(entries[0].get("StsRsnInf").is_none());
+        assert_eq!(entries[1]["StsRsnInf"][0]["Rsn"]["Cd"], "AC01");
+    }
+
+    #[tokio::test]
+    async fn records_status_reports_without_answering_them() {
+        let service = Arc::new(StubService::default());
+        let consumer = consumer(service.clone());
+        let mut status_reports = inbound();
+        status_reports.message_type = Some("pacs.002.001.10".to_string());
+        let body = json!({ "GrpHdr": { "MsgId": "STS-1" }, "TxInfAndSts": [{ "TxSts": "ACSC" }] });
+
+        assert!(matches!(consumer.handle(&status_reports, message(body.clone())).await, Disposition::Ack));
+        assert_eq!(*service.status_reports.lock().unwrap(), vec![body]);
+        assert!(service.processed.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use lapin::options::QueueDeclareOptions;
+use lapin::types::{AMQPValue, FieldTable, LongString};
+use lapin::{BasicProperties, Channel};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MessagingError, RepositoryError, ServiceError};
+
+pub const ATTEMPTS_HEADER: &str = "x-attempts";
+pub const FAILURE_CLASS_HEADER: &str = "x-failure-class";
+pub const FAILURE_REASON_HEADER: &str = "x-failure-reason";
+pub const ORIGINAL_QUEUE_HEADER: &str = "x-original-queue";
+pub const FAILED_AT_HEADER: &str = "x-failed-at";
+
+// Broker header values are kept short enough to stay readable in tooling.
+const MAX_REASON_LENGTH: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureClass {
+    // Infrastructure trouble that should clear up by itself.
+    Transient,
+    // The payment is not in a state the message can be applied to yet.
+    Business,
+    // Retrying cannot help; straight to the dead-letter queue.
+    Poison,
+}
+
+impl FailureClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureClass::Transient => "transient",
+            FailureClass::Business => "business",
+            FailureClass::Poison => "poison",
+        }
+    }
+
+    pub fn of(error: &ServiceError) -> Self {
+        match error {
+            ServiceError::Repository(RepositoryError::Database(_)) => FailureClass::Transient,
+            ServiceError::Messaging(e) => Self::of_messaging(e),
+            ServiceError::NotFound(_)
+            | ServiceError::InvalidState { .. }
+            | ServiceError::Repository(RepositoryError::NotFound(_))
+            | ServiceError::Repository(RepositoryError::IllegalTransition { .. }) => FailureClass::Business,
+            ServiceError::Validation(_)
+            | ServiceError::BusinessRule(_)
+            | ServiceError::Repository(RepositoryError::Constraint { .. }) => FailureClass::Poison,
+        }
+    }
+
+    pub fn of_messaging(error: &MessagingError) -> Self {
+        if error.is_transient() {
+            FailureClass::Transient
+        } else {
+            FailureClass::Poison
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub class: FailureClass,
+    pub reason: String,
+}
+
+impl Failure {
+    pub fn new(class: FailureClass, reason: impl Into<String>) -> Self {
+        Self {
+            class,
+            reason: reason.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetryPolicy {
+    // Deliveries in total, the first one included; 1 means no retries.
+    pub max_attempts: u32,
+    #[serde(default = "default_initial_delay_ms")]
+    pub initial_delay_ms: u64,
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_initial_delay_ms() -> u64 {
+    1_000
+}
+
+fn default_max_delay_ms() -> u64 {
+    5 * 60 * 1_000
+}
+
+impl RetryPolicy {
+    // Doubles per failed attempt, starting at 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(32);
+        Duration::from_millis(self.initial_delay_ms.saturating_mul(1u64 << exponent).min(self.max_delay_ms))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetrySettings {
+    #[serde(default = "default_transient")]
+    pub transient: RetryPolicy,
+    #[serde(default = "default_business")]
+    pub business: RetryPolicy,
+    #[serde(default = "default_poison")]
+    pub poison: RetryPolicy,
+}
+
+fn default_transient() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 6,
+        initial_delay_ms: 1_000,
+        max_delay_ms: 5 * 60 * 1_000,
+    }
+}
+
+fn default_business() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        initial_delay_ms: 30_000,
+        max_delay_ms: 10 * 60 * 1_000,
+    }
+}
+
+fn default_poison() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 1,
+        initial_delay_ms: default_initial_delay_ms(),
+        max_delay_ms: default_max_delay_ms(),
+    }
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            transient: default_transient(),
+            business: default_business(),
+            poison: default_poison(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    Retry { delay: Duration },
+    DeadLetter,
+}
+
+impl RetrySettings {
+    pub fn policy(&self, class: FailureClass) -> &RetryPolicy {
+        match class {
+            FailureClass::Transient => &self.transient,
+            FailureClass::Business => &self.business,
+            FailureClass::Poison => &self.poison,
+        }
+    }
+
+    // Where a message goes after its `attempt`-th delivery failed.
+    pub fn route(&self, class: FailureClass, attempt: u32) -> Route {
+        let policy = self.policy(class);
+        if attempt >= policy.max_attempts {
+            Route::DeadLetter
+        } else {
+            Route::Retry {
+                delay: policy.delay(attempt),
+            }
+        }
+    }
+}
+
+pub fn dead_letter_queue(queue: &str) -> String {
+    format!("{}.dlq", queue)
+}
+
+pub fn retry_queue(queue: &str, delay: Duration) -> String {
+    format!("{}.retry.{}", queue, delay.as_millis())
+}
+
+// Failed deliveries so far, as recorded by earlier retries.
+pub fn attempts(properties: &BasicProperties) -> u32 {
+    let value = properties
+        .headers()
+        .as_ref()
+        .and_then(|headers| header(headers, ATTEMPTS_HEADER).cloned());
+    match value {
+        Some(AMQPValue::LongLongInt(attempts)) => attempts.max(0) as u32,
+        Some(AMQPValue::LongInt(attempts)) => attempts.max(0) as u32,
+        Some(AMQPValue::LongUInt(attempts)) => attempts,
+        Some(AMQPValue::ShortInt(attempts)) => attempts.max(0) as u32,
+        Some(AMQPValue::ShortUInt(attempts)) => attempts as u32,
+        _ => 0,
+    }
+}
+
+pub fn header<'a>(headers: &'a FieldTable, name: &str) -> Option<&'a AMQPValue> {
+    headers.inner().iter().find(|(key, _)| key.as_str() == name).map(|(_, value)| value)
+}
+
+pub fn header_text(headers: &FieldTable, name: &str) -> Option<String> {
+    match header(headers, name)? {
+        AMQPValue::LongString(value) => Some(String::from_utf8_lossy(value.as_bytes()).into_owned()),
+        AMQPValue::ShortString(value) => Some(value.as_str().to_string()),
+        _ => None,
+    }
+}
+
+// The original properties with the failure recorded on top, so the retried
+// or dead-lettered copy keeps its message id, type and reply-to.
+pub fn failed_properties(properties: &BasicProperties, queue: &str, failure: &Failure, attempt: u32) -> BasicProperties {
+    let mut headers = properties.headers().clone().unwrap_or_default();
+    let reason: String = failure.reason.chars().take(MAX_REASON_LENGTH).collect();
+    headers.insert(ATTEMPTS_HEADER.into(), AMQPValue::LongLongInt(attempt as i64));
+    headers.insert(FAILURE_CLASS_HEADER.into(), text(failure.class.as_str()));
+    headers.insert(FAILURE_REASON_HEADER.into(), text(&reason));
+    headers.insert(ORIGINAL_QUEUE_HEADER.into(), text(queue));
+    headers.insert(FAILED_AT_HEADER.into(), text(&Utc::now().to_rfc3339()));
+    properties.clone().with_headers(headers)
+}
+
+fn text(value: &str) -> AMQPValue {
+    AMQPValue::LongString(LongString::from(value.to_string()))
+}
+
+pub async fn declare_dead_letter_queue(channel: &Channel, queue: &str) -> Result<(), MessagingError> {
+    channel
+        .queue_declare(
+            &dead_letter_queue(queue),
+            QueueDeclareOptions {
+                durable: true,
+                ..QueueDeclareOptions::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+    Ok(())
+}
+
+// A retry queue holds messages for `delay` and then lets the broker
+// dead-letter them back onto the queue they came from.
+pub async fn declare_retry_queue(channel: &Channel, queue: &str, delay: Duration) -> Result<String, MessagingError> {
+    let name = retry_queue(queue, delay);
+    let mut arguments = FieldTable::default();
+    arguments.insert("x-message-ttl".into(), AMQPValue::LongLongInt(delay.as_millis() as i64));
+    arguments.insert("x-dead-letter-exchange".into(), text(""));
+    arguments.insert("x-dead-letter-routing-key".into(), text(queue));
+    channel
+        .queue_declare(
+            &name,
+            QueueDeclareOptions {
+                durable: true,
+                ..QueueDeclareOptions::default()
+            },
+            arguments,
+        )
+        .await?;
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lapin::types::ShortString;
+
+    #[test]
+    fn routes_by_failure_class_and_attempt() {
+        let settings = RetrySettings::default();
+
+        let delays: Vec<Route> = (1..6).map(|attempt| settings.route(FailureClass::Transient, attempt)).collect();
+        assert_eq!(
+            delays,
+            [1, 2, 4, 8, 16]
+                .iter()
+                .map(|seconds| Route::Retry {
+                    delay: Duration::from_secs(*seconds)
+                })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(settings.route(FailureClass::Transient, 6), Route::DeadLetter);
+        assert_eq!(settings.route(FailureClass::Business, 3), Route::DeadLetter);
+        assert_eq!(settings.route(FailureClass::Poison, 1), Route::DeadLetter);
+    }
+
+    #[test]
+    fn classifies_constraint_violations_as_poison() {
+        let unique = RepositoryError::Constraint {
+            constraint: "payments_sender_request_key".to_string(),
+            message: "duplicate key value violates unique constraint".to_string(),
+        };
+        assert_eq!(FailureClass::of(&unique.into()), FailureClass::Poison);
+        let outage = RepositoryError::Database("connection refused".to_string());
+        assert_eq!(FailureClass::of(&outage.into()), FailureClass::Transient);
+    }
+
+    #[test]
+    fn records_failure_in_headers() {
+        let failure = Failure::new(FailureClass::Transient, "database unavailable");
+        let properties = BasicProperties::default().with_message_id(ShortString::from("MSG-1".to_string()));
+
+        let failed = failed_properties(&properties, "upstream.core-banking", &failure, 2);
+        let headers = failed.headers().clone().unwrap();
+        assert_eq!(attempts(&failed), 2);
+        assert_eq!(header_text(&headers, FAILURE_CLASS_HEADER).as_deref(), Some("transient"));
+        assert_eq!(header_text(&headers, FAILURE_REASON_HEADER).as_deref(), Some("database unavailable"));
+        assert_eq!(failed.message_id().as_ref().map(|id| id.as_str()), Some("MSG-1"));
+        assert_eq!(attempts(&properties), 0);
+    }
+}
@@ -0,0 +1,27 @@
+use actix_web::dev::ServiceRequest;
+use uuid::Uuid;
+
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+const MAX_CORRELATION_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    pub static CORRELATION_ID: String;
+}
+
+// Correlation ID of the request currently being handled, if any.
+pub fn current() -> Option<String> {
+    CORRELATION_ID.try_with(|id| id.clone()).ok()
+}
+
+// Honour a caller-supplied ID so traces join up across systems; anything
+// unusable is replaced with a fresh one.
+pub fn from_request(req: &ServiceRequest) -> String {
+    req.headers()
+        .get(CORRELATION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_CORRELATION_ID_LENGTH)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
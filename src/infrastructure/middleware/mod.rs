@@ -0,0 +1,2 @@
+pub mod correlation;
+pub mod request_tracing;
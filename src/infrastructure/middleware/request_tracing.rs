@@ -1,10 +1,16 @@
-use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
 use actix_web::Error;
+use futures::future::{ok, Ready};
 use futures::Future;
 use pin_project_lite::pin_project;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use tracing::{info, Span};
+use tokio::task::futures::TaskLocalFuture;
+use tracing::instrument::Instrumented;
+use tracing::{info, Instrument, Span};
+
+use crate::infrastructure::middleware::correlation::{self, CORRELATION_ID, CORRELATION_ID_HEADER};
 
 pub struct RequestTracing;
 
@@ -22,3 +28,68 @@ where
         ok(RequestTracingMiddleware { service })
     }
 }
+
+pub struct RequestTracingMiddleware<S> {
+    service: S,
+}
+
+impl<S> Service<ServiceRequest> for RequestTracingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Future = RequestTracingFuture<S::Future>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let correlation_id = correlation::from_request(&req);
+        let span = tracing::info_span!(
+            "http_request",
+            method = %req.method(),
+            path = %req.path(),
+            correlation_id = %correlation_id,
+        );
+
+        let inner = CORRELATION_ID.scope(correlation_id.clone(), self.service.call(req).instrument(span.clone()));
+        RequestTracingFuture {
+            inner,
+            correlation_id,
+            span,
+        }
+    }
+}
+
+pin_project! {
+    pub struct RequestTracingFuture<F> {
+        #[pin]
+        inner: TaskLocalFuture<String, Instrumented<F>>,
+        correlation_id: String,
+        span: Span,
+    }
+}
+
+impl<F> Future for RequestTracingFuture<F>
+where
+    F: Future<Output = Result<ServiceResponse, Error>>,
+{
+    type Output = Result<ServiceResponse, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = match this.inner.poll(cx) {
+            Poll::Ready(Ok(response)) => response,
+            other => return other,
+        };
+
+        let _entered = this.span.enter();
+        info!(status = response.status().as_u16(), "Request completed");
+        if let Ok(value) = HeaderValue::from_str(this.correlation_id) {
+            response
+                .headers_mut()
+                .insert(HeaderName::from_static(CORRELATION_ID_HEADER), value);
+        }
+        Poll::Ready(Ok(response))
+    }
+}
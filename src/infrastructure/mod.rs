@@ -0,0 +1,4 @@
+pub mod database;
+pub mod idempotency;
+pub mod messaging;
+pub mod middleware;
@@ -0,0 +1,10 @@
+pub mod api;
+pub mod config;
+pub mod domain;
+pub mod error;
+pub mod fraud;
+pub mod infrastructure;
+pub mod monitoring;
+pub mod screening;
+pub mod service;
+pub mod validation;
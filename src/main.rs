@@ -1,14 +1,39 @@
+use std::sync::Arc;
+
 use actix_web::{web, App, HttpServer};
 use dotenv::dotenv;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use sqlx::PgPool;
 use tracing::info;
 
-mod api;
-mod config;
-mod domain;
-mod error;
-mod infrastructure;
-mod service;
-mod validation;
+use iso20022_payment_processor::api::auth::Authenticator;
+use iso20022_payment_processor::config::{self, MessagingBackend};
+use iso20022_payment_processor::error;
+use iso20022_payment_processor::fraud::RulesBasedScorer;
+use iso20022_payment_processor::infrastructure::database::dead_letters::DeadLetterStore;
+use iso20022_payment_processor::infrastructure::database::entitlements::EntitlementRepository;
+use iso20022_payment_processor::infrastructure::database::postgres::{
+    PostgresAlertStore, PostgresDeadLetterStore, PostgresDuplicateRegistry, PostgresEntitlementRepository,
+    PostgresEventStore, PostgresIdempotencyStore, PostgresLimitRepository, PostgresOutboxStore,
+    PostgresPaymentRepository, PostgresSenderProfileRepository,
+};
+use iso20022_payment_processor::infrastructure::database::{migrations, postgres};
+use iso20022_payment_processor::infrastructure::idempotency::IdempotencyStore;
+use iso20022_payment_processor::infrastructure::messaging::dead_letters::DeadLetterCollector;
+use iso20022_payment_processor::infrastructure::messaging::kafka::{
+    KafkaEventConsumer, KafkaPublisher, StatusReportHandler,
+};
+use iso20022_payment_processor::infrastructure::messaging::message_publisher::RabbitMQPublisher;
+use iso20022_payment_processor::infrastructure::messaging::outbox_relay::OutboxRelay;
+use iso20022_payment_processor::infrastructure::messaging::payment_consumer::PaymentConsumer;
+use iso20022_payment_processor::infrastructure::messaging::MessagePublisher;
+use iso20022_payment_processor::monitoring::{InMemoryTransactionMonitor, TransactionMonitor};
+use iso20022_payment_processor::screening::WatchListScreener;
+use iso20022_payment_processor::service::dead_letters::DeadLetterService;
+use iso20022_payment_processor::service::limits::PaymentLimits;
+use iso20022_payment_processor::service::{PaymentService, PaymentServiceImpl};
+use iso20022_payment_processor::validation::payment_validator::{load_schemas, ISO20022PaymentValidator};
+use iso20022_payment_processor::{api, infrastructure};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -19,19 +44,158 @@ async fn main() -> std::io::Result<()> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
+    let metrics = web::Data::new(
+        PrometheusBuilder::new()
+            .install_recorder()
+            .expect("Failed to install metrics recorder"),
+    );
+
     let config = config::load_config().expect("Failed to load configuration");
-    let app_state = web::Data::new(infrastructure::AppState::new(&config).await);
+    let pool = postgres::connect(&config.database)
+        .await
+        .expect("Failed to connect to the database");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        return migrate(&pool, args.get(1).map(String::as_str)).await;
+    }
+
+    let applied = migrations::prepare(&pool, config.database.migrate_on_startup)
+        .await
+        .expect("Database schema is not usable by this build");
+    if !applied.is_empty() {
+        info!(?applied, "Applied database migrations");
+    }
+
+    // The broker is only reached through the outbox, so the service keeps
+    // taking payments while it is down.
+    let rabbit = Arc::new(RabbitMQPublisher::new(config.messaging.clone()));
+    let publisher: Arc<dyn MessagePublisher> = match config.messaging.backend {
+        MessagingBackend::RabbitMq => rabbit.clone(),
+        MessagingBackend::Kafka => {
+            Arc::new(KafkaPublisher::new(&config.messaging).expect("Failed to create Kafka producer"))
+        }
+    };
+    let events = Arc::new(PostgresEventStore::new(pool.clone()));
+    let relay = OutboxRelay::new(
+        Arc::new(PostgresOutboxStore::new(pool.clone())),
+        publisher,
+        config.outbox.clone(),
+    )
+    .with_events(events.clone());
+    tokio::spawn(relay.run());
+
+    let schemas = load_schemas(&config.validation.schemas).expect("Failed to load message schemas");
+    // The validator checks the same entitlements the admin API maintains.
+    let entitlements: Arc<dyn EntitlementRepository> = Arc::new(PostgresEntitlementRepository::new(pool.clone()));
+    let duplicate_retention = config.validation.duplicates.rules.iter().map(|rule| rule.window_minutes).max();
+    let validator = ISO20022PaymentValidator::from_settings(
+        &config.validation,
+        schemas,
+        Box::new(PostgresDuplicateRegistry::new(
+            pool.clone(),
+            chrono::Duration::minutes(duplicate_retention.unwrap_or_default()),
+        )),
+        entitlements.clone(),
+    )
+    .expect("Invalid validation configuration");
+    let transaction_monitor: Arc<dyn TransactionMonitor> = Arc::new(
+        InMemoryTransactionMonitor::new(config.monitoring.clone())
+            .with_alert_store(Arc::new(PostgresAlertStore::new(pool.clone()))),
+    );
+    let mut service = PaymentServiceImpl::new(Box::new(validator), Box::new(PostgresPaymentRepository::new(pool.clone())))
+        .with_approval(config.approval.clone())
+        .with_events(events)
+        .with_monitor(transaction_monitor.clone());
+    if config.fraud.enabled {
+        let profiles = PostgresSenderProfileRepository::new(pool.clone());
+        let scorer = RulesBasedScorer::new(config.fraud.rules.clone(), Box::new(profiles));
+        service = service.with_fraud_scorer(Box::new(scorer), config.fraud.clone());
+    }
+    if config.limits.enabled {
+        let repository = PostgresLimitRepository::new(pool.clone());
+        service = service.with_limits(PaymentLimits::new(config.limits.clone(), Box::new(repository)));
+    }
+    if config.screening.enabled {
+        let screener = WatchListScreener::load(config.screening.clone()).expect("Failed to load sanctions lists");
+        service = service.with_screener(Box::new(screener));
+    }
+    let payment_service: Arc<dyn PaymentService> = Arc::new(service);
+    let payments: web::Data<dyn PaymentService> = web::Data::from(payment_service.clone());
+    let idempotency_store: web::Data<dyn IdempotencyStore> = web::Data::from(Arc::new(
+        PostgresIdempotencyStore::new(pool.clone(), config.idempotency.ttl()).with_lease(config.idempotency.lease()),
+    ) as Arc<dyn IdempotencyStore>);
+    let entitlements: web::Data<dyn EntitlementRepository> = web::Data::from(entitlements);
+    let transaction_monitor: web::Data<dyn TransactionMonitor> = web::Data::from(transaction_monitor);
+
+    let dead_letter_store: Arc<dyn DeadLetterStore> = Arc::new(PostgresDeadLetterStore::new(pool.clone()));
+    let dead_letters = web::Data::new(DeadLetterService::new(dead_letter_store.clone(), rabbit));
+
+    if config.messaging.backend == MessagingBackend::Kafka {
+        let handler = StatusReportHandler::new(&config.messaging.kafka, payment_service.clone());
+        let consumer = KafkaEventConsumer::new(config.messaging.clone(), Arc::new(handler))
+            .expect("Failed to create Kafka consumer");
+        Arc::new(consumer).spawn();
+    }
+
+    if !config.messaging.inbound.is_empty() {
+        let consumer = PaymentConsumer::new(config.messaging.clone(), payment_service);
+        Arc::new(consumer).spawn();
+        Arc::new(DeadLetterCollector::new(config.messaging.clone(), dead_letter_store)).spawn();
+    }
+
+    let authenticator = web::Data::new(Authenticator::new(config.auth.clone()));
 
     info!("Starting ISO 20022 Payment Processing Service");
 
     HttpServer::new(move || {
         App::new()
-            .app_data(app_state.clone())
+            .app_data(payments.clone())
+            .app_data(idempotency_store.clone())
+            .app_data(transaction_monitor.clone())
+            .app_data(entitlements.clone())
+            .app_data(metrics.clone())
+            .app_data(dead_letters.clone())
+            .app_data(authenticator.clone())
             .wrap(infrastructure::middleware::request_tracing::RequestTracing)
-            .wrap(infrastructure::middleware::error_handling::ErrorHandling)
+            .configure(api::extractor_config)
             .configure(api::payment::config)
+            .configure(api::monitoring::config)
+            .configure(api::entitlements::config)
+            .configure(api::metrics::config)
+            .configure(api::dead_letters::config)
     })
     .bind(("127.0.0.1", config.server.port))?
     .run()
     .await
 }
+
+// `migrate` applies pending migrations, `migrate status` lists them.
+async fn migrate(pool: &PgPool, action: Option<&str>) -> std::io::Result<()> {
+    let to_io = |e: error::MigrationError| std::io::Error::other(e.to_string());
+    match action {
+        None | Some("run") => {
+            let applied = migrations::run(pool).await.map_err(to_io)?;
+            if applied.is_empty() {
+                println!("Database is up to date");
+            }
+            for version in applied {
+                println!("Applied migration {}", version);
+            }
+        }
+        Some("status") => {
+            migrations::check(pool).await.map_err(to_io)?;
+            for migration in migrations::status(pool).await.map_err(to_io)? {
+                let state = if migration.applied { "applied" } else { "pending" };
+                println!("{} {} {}", migration.version, state, migration.description);
+            }
+        }
+        Some(other) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unknown migrate action {:?}, expected run or status", other),
+            ))
+        }
+    }
+    Ok(())
+}
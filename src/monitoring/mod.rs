@@ -0,0 +1,4 @@
+pub mod monitor;
+pub mod rules;
+
+pub use monitor::{AlertStore, InMemoryAlertStore, InMemoryTransactionMonitor, MonitoringSettings, TransactionMonitor};
@@ -0,0 +1,422 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::domain::payment::Payment;
+use crate::domain::payment_details::transactions;
+use crate::error::RepositoryError;
+use crate::monitoring::rules::{default_monitoring_rules, MonitoringRule, ObservedTransaction};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonitoringSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_monitoring_rules")]
+    pub rules: Vec<MonitoringRule>,
+    #[serde(default = "default_beneficiary_memory_days")]
+    pub beneficiary_memory_days: i64,
+    #[serde(default = "default_alert_retention_days")]
+    pub alert_retention_days: i64,
+}
+
+fn default_beneficiary_memory_days() -> i64 {
+    180
+}
+
+fn default_alert_retention_days() -> i64 {
+    365
+}
+
+impl Default for MonitoringSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: default_monitoring_rules(),
+            beneficiary_memory_days: default_beneficiary_memory_days(),
+            alert_retention_days: default_alert_retention_days(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitoringAlert {
+    pub id: Uuid,
+    pub rule: String,
+    pub party: String,
+    pub reason: String,
+    pub transactions: Vec<ObservedTransaction>,
+    pub raised_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AlertQuery {
+    pub party: Option<String>,
+    pub rule: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait TransactionMonitor: Send + Sync {
+    async fn observe(&self, payment: &Payment) -> Result<Vec<MonitoringAlert>, RepositoryError>;
+    async fn alerts(&self, query: &AlertQuery) -> Result<Vec<MonitoringAlert>, RepositoryError>;
+    async fn get_alert(&self, id: &Uuid) -> Result<Option<MonitoringAlert>, RepositoryError>;
+}
+
+// Where raised alerts are kept for the alerts API and for suppressing repeats.
+#[async_trait]
+pub trait AlertStore: Send + Sync {
+    // Stores the alert unless its party already has one for the same rule
+    // raised at or after `since`; returns whether it was stored.
+    async fn raise(&self, alert: &MonitoringAlert, since: DateTime<Utc>) -> Result<bool, RepositoryError>;
+    async fn find(&self, query: &AlertQuery) -> Result<Vec<MonitoringAlert>, RepositoryError>;
+    async fn get(&self, id: &Uuid) -> Result<Option<MonitoringAlert>, RepositoryError>;
+    // Drops alerts raised before `before`; returns how many were removed.
+    async fn prune(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError>;
+}
+
+#[derive(Default)]
+pub struct InMemoryAlertStore {
+    alerts: Mutex<Vec<MonitoringAlert>>,
+}
+
+impl InMemoryAlertStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AlertStore for InMemoryAlertStore {
+    async fn raise(&self, alert: &MonitoringAlert, since: DateTime<Utc>) -> Result<bool, RepositoryError> {
+        let mut alerts = self.alerts.lock().await;
+        if alerts
+            .iter()
+            .rev()
+            .any(|raised| raised.party == alert.party && raised.rule == alert.rule && raised.raised_at >= since)
+        {
+            return Ok(false);
+        }
+        alerts.push(alert.clone());
+        Ok(true)
+    }
+
+    async fn find(&self, query: &AlertQuery) -> Result<Vec<MonitoringAlert>, RepositoryError> {
+        let alerts = self.alerts.lock().await;
+        let mut found: Vec<MonitoringAlert> = alerts
+            .iter()
+            .filter(|alert| query.party.as_ref().is_none_or(|party| &alert.party == party))
+            .filter(|alert| query.rule.as_ref().is_none_or(|rule| &alert.rule == rule))
+            .filter(|alert| query.since.is_none_or(|since| alert.raised_at >= since))
+            .cloned()
+            .collect();
+        found.sort_by_key(|alert| std::cmp::Reverse(alert.raised_at));
+        Ok(found)
+    }
+
+    async fn get(&self, id: &Uuid) -> Result<Option<MonitoringAlert>, RepositoryError> {
+        let alerts = self.alerts.lock().await;
+        Ok(alerts.iter().find(|alert| alert.id == *id).cloned())
+    }
+
+    async fn prune(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let mut alerts = self.alerts.lock().await;
+        let count = alerts.len();
+        alerts.retain(|alert| alert.raised_at >= before);
+        Ok((count - alerts.len()) as u64)
+    }
+}
+
+#[derive(Default)]
+struct PartyActivity {
+    transactions: VecDeque<ObservedTransaction>,
+    first_seen: HashMap<String, DateTime<Utc>>,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    parties: HashMap<String, PartyActivity>,
+    last_pruned: Option<DateTime<Utc>>,
+}
+
+// Keeps each party's recent activity in memory and the alerts it raises in
+// an AlertStore.
+pub struct InMemoryTransactionMonitor {
+    settings: MonitoringSettings,
+    retention: Duration,
+    state: Mutex<MonitorState>,
+    alerts: Arc<dyn AlertStore>,
+}
+
+impl InMemoryTransactionMonitor {
+    pub fn new(settings: MonitoringSettings) -> Self {
+        let retention = settings
+            .rules
+            .iter()
+            .map(MonitoringRule::window)
+            .max()
+            .unwrap_or_else(Duration::zero);
+        Self {
+            settings,
+            retention,
+            state: Mutex::new(MonitorState::default()),
+            alerts: Arc::new(InMemoryAlertStore::new()),
+        }
+    }
+
+    pub fn with_alert_store(mut self, alerts: Arc<dyn AlertStore>) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    // Expired alerts and idle parties are dropped at most once an hour rather
+    // than on every payment.
+    async fn prune(&self, now: DateTime<Utc>) {
+        {
+            let mut state = self.state.lock().await;
+            if state.last_pruned.is_some_and(|last| now - last < Duration::hours(1)) {
+                return;
+            }
+            state.last_pruned = Some(now);
+            state
+                .parties
+                .retain(|_, activity| !activity.transactions.is_empty() || !activity.first_seen.is_empty());
+        }
+        let cutoff = now - Duration::days(self.settings.alert_retention_days);
+        if let Err(e) = self.alerts.prune(cutoff).await {
+            warn!("Failed to prune monitoring alerts: {:?}", e);
+        }
+    }
+}
+
+// One observation per transaction, so a batch counts as the payments in it.
+fn observed(payment: &Payment) -> Vec<ObservedTransaction> {
+    transactions(&payment.request.message_payload)
+        .into_iter()
+        .map(|transaction| {
+            let details = transaction.details;
+            ObservedTransaction {
+                payment_id: payment.id,
+                uetr: details
+                    .uetr
+                    .as_deref()
+                    .and_then(|uetr| Uuid::parse_str(uetr).ok())
+                    .unwrap_or(payment.uetr),
+                amount: details.amount,
+                currency: details.currency,
+                beneficiary: details.creditor_account.or(details.creditor_name),
+                observed_at: payment.created_at,
+            }
+        })
+        .collect()
+}
+
+#[async_trait]
+impl TransactionMonitor for InMemoryTransactionMonitor {
+    async fn observe(&self, payment: &Payment) -> Result<Vec<MonitoringAlert>, RepositoryError> {
+        if !self.settings.enabled {
+            return Ok(Vec::new());
+        }
+
+        let party = payment.request.sender_id.clone();
+        let now = payment.created_at;
+        self.prune(now).await;
+
+        // Rules are evaluated under the lock; the alert store is only written
+        // once it is released.
+        let mut matches = Vec::new();
+        {
+            let mut state = self.state.lock().await;
+            let activity = state.parties.entry(party.clone()).or_default();
+
+            // Beneficiaries are recorded before evaluation so first_seen
+            // reflects this payment when it is the first one to that account.
+            for transaction in observed(payment) {
+                if let Some(beneficiary) = &transaction.beneficiary {
+                    activity.first_seen.entry(beneficiary.clone()).or_insert(now);
+                }
+                activity.transactions.push_back(transaction);
+            }
+            while activity
+                .transactions
+                .front()
+                .is_some_and(|oldest| oldest.observed_at < now - self.retention)
+            {
+                activity.transactions.pop_front();
+            }
+            let memory_cutoff = now - Duration::days(self.settings.beneficiary_memory_days);
+            activity.first_seen.retain(|_, seen| *seen >= memory_cutoff);
+
+            for rule in &self.settings.rules {
+                let window_start = now - rule.window();
+                let recent: Vec<&ObservedTransaction> = activity
+                    .transactions
+                    .iter()
+                    .filter(|txn| txn.observed_at >= window_start)
+                    .collect();
+                if let Some(matched) = rule.evaluate(&recent, &activity.first_seen, window_start) {
+                    matches.push((rule, window_start, matched));
+                }
+            }
+        }
+
+        // One alert per party and rule per window; later payments in the same
+        // burst would otherwise each raise a near-identical alert.
+        let mut raised = Vec::new();
+        for (rule, window_start, matched) in matches {
+            let alert = MonitoringAlert {
+                id: Uuid::new_v4(),
+                rule: rule.name().to_string(),
+                party: party.clone(),
+                reason: matched.reason,
+                transactions: matched.transactions,
+                raised_at: now,
+            };
+            if self.alerts.raise(&alert, window_start).await? {
+                raised.push(alert);
+            }
+        }
+        Ok(raised)
+    }
+
+    async fn alerts(&self, query: &AlertQuery) -> Result<Vec<MonitoringAlert>, RepositoryError> {
+        self.alerts.find(query).await
+    }
+
+    async fn get_alert(&self, id: &Uuid) -> Result<Option<MonitoringAlert>, RepositoryError> {
+        self.alerts.get(id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::{PaymentRequest, PaymentType};
+    use serde_json::json;
+
+    fn payment(sender: &str, amount: f64, creditor_iban: &str, minutes_ago: i64) -> Payment {
+        let request = PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: json!({
+                "CdtTrfTxInf": {
+                    "IntrBkSttlmAmt": { "Ccy": "EUR", "Value": amount },
+                    "CdtrAcct": { "Id": { "IBAN": creditor_iban } }
+                }
+            }),
+            sender_id: sender.to_string(),
+            request_id: Uuid::new_v4().to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        };
+        let mut payment = Payment::new(request, Uuid::new_v4(), Vec::new(), Vec::new());
+        payment.created_at = Utc::now() - Duration::minutes(minutes_ago);
+        payment
+    }
+
+    fn monitor(rules: Vec<MonitoringRule>) -> InMemoryTransactionMonitor {
+        InMemoryTransactionMonitor::new(MonitoringSettings {
+            enabled: true,
+            rules,
+            ..MonitoringSettings::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn raises_one_structuring_alert_with_contributing_payments() {
+        let monitor = monitor(vec![MonitoringRule::Structuring {
+            name: "structuring".to_string(),
+            threshold: 10_000.0,
+            currency: "EUR".to_string(),
+            margin_percent: 10.0,
+            min_count: 3,
+            window_minutes: 24 * 60,
+        }]);
+
+        assert!(monitor.observe(&payment("acme", 9_500.0, "DE1", 30)).await.unwrap().is_empty());
+        assert!(monitor.observe(&payment("acme", 120.0, "DE2", 20)).await.unwrap().is_empty());
+        assert!(monitor.observe(&payment("acme", 9_900.0, "DE3", 10)).await.unwrap().is_empty());
+        let alerts = monitor.observe(&payment("acme", 9_990.0, "DE4", 0)).await.unwrap();
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].transactions.len(), 3);
+        assert!(monitor.observe(&payment("acme", 9_800.0, "DE5", 0)).await.unwrap().is_empty());
+        assert_eq!(monitor.alerts(&AlertQuery::default()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn counts_only_beneficiaries_new_in_the_window() {
+        let monitor = monitor(vec![MonitoringRule::FanOut {
+            name: "fan_out".to_string(),
+            max_new_beneficiaries: 2,
+            window_minutes: 60,
+        }]);
+
+        monitor.observe(&payment("acme", 10.0, "DE-KNOWN", 600)).await.unwrap();
+        monitor.observe(&payment("acme", 10.0, "DE-KNOWN", 5)).await.unwrap();
+        monitor.observe(&payment("acme", 10.0, "DE-NEW1", 4)).await.unwrap();
+        assert!(monitor.observe(&payment("acme", 10.0, "DE-NEW2", 3)).await.unwrap().is_empty());
+        let alerts = monitor.observe(&payment("acme", 10.0, "DE-NEW3", 2)).await.unwrap();
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].party, "acme");
+        assert_eq!(alerts[0].transactions.len(), 3);
+        let query = AlertQuery {
+            party: Some("someone-else".to_string()),
+            ..AlertQuery::default()
+        };
+        assert!(monitor.alerts(&query).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn observes_every_transaction_of_a_batch() {
+        let monitor = monitor(vec![MonitoringRule::Velocity {
+            name: "velocity".to_string(),
+            max_count: 2,
+            window_minutes: 60,
+        }]);
+        let mut batch = payment("acme", 10.0, "DE1", 0);
+        batch.request.message_payload = json!({
+            "CdtTrfTxInf": [
+                { "IntrBkSttlmAmt": { "Ccy": "EUR", "Value": 10.0 } },
+                { "IntrBkSttlmAmt": { "Ccy": "EUR", "Value": 20.0 } },
+                { "IntrBkSttlmAmt": { "Ccy": "EUR", "Value": 30.0 } }
+            ]
+        });
+
+        let alerts = monitor.observe(&batch).await.unwrap();
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].transactions.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn drops_alerts_past_their_retention() {
+        let store = Arc::new(InMemoryAlertStore::new());
+        let monitor = InMemoryTransactionMonitor::new(MonitoringSettings {
+            enabled: true,
+            rules: vec![MonitoringRule::Velocity {
+                name: "velocity".to_string(),
+                max_count: 0,
+                window_minutes: 60,
+            }],
+            alert_retention_days: 1,
+            ..MonitoringSettings::default()
+        })
+        .with_alert_store(store.clone());
+
+        monitor.observe(&payment("acme", 10.0, "DE1", 3 * 24 * 60)).await.unwrap();
+        assert_eq!(store.find(&AlertQuery::default()).await.unwrap().len(), 1);
+        monitor.observe(&payment("globex", 10.0, "DE1", 0)).await.unwrap();
+
+        let alerts = store.find(&AlertQuery::default()).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].party, "globex");
+    }
+}
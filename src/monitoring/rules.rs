@@ -0,0 +1,171 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservedTransaction {
+    pub payment_id: Uuid,
+    pub uetr: Uuid,
+    pub amount: Option<f64>,
+    pub currency: Option<String>,
+    pub beneficiary: Option<String>,
+    pub observed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum MonitoringRule {
+    // More than `max_count` payments from one party inside the window.
+    Velocity {
+        name: String,
+        max_count: usize,
+        window_minutes: i64,
+    },
+    // Repeated payments just below a reporting threshold, e.g. 9,500 EUR
+    // against a 10,000 EUR threshold with a 10% margin.
+    Structuring {
+        name: String,
+        threshold: f64,
+        currency: String,
+        #[serde(default = "default_margin_percent")]
+        margin_percent: f64,
+        min_count: usize,
+        window_minutes: i64,
+    },
+    // Payments to more than `max_new_beneficiaries` accounts the party had not
+    // paid before the window opened.
+    FanOut {
+        name: String,
+        max_new_beneficiaries: usize,
+        window_minutes: i64,
+    },
+}
+
+fn default_margin_percent() -> f64 {
+    10.0
+}
+
+pub fn default_monitoring_rules() -> Vec<MonitoringRule> {
+    vec![
+        MonitoringRule::Velocity {
+            name: "velocity".to_string(),
+            max_count: 50,
+            window_minutes: 60,
+        },
+        MonitoringRule::Structuring {
+            name: "structuring".to_string(),
+            threshold: 10_000.0,
+            currency: "EUR".to_string(),
+            margin_percent: default_margin_percent(),
+            min_count: 3,
+            window_minutes: 24 * 60,
+        },
+        MonitoringRule::FanOut {
+            name: "beneficiary_fan_out".to_string(),
+            max_new_beneficiaries: 10,
+            window_minutes: 24 * 60,
+        },
+    ]
+}
+
+pub struct RuleMatch {
+    pub reason: String,
+    pub transactions: Vec<ObservedTransaction>,
+}
+
+impl MonitoringRule {
+    pub fn name(&self) -> &str {
+        match self {
+            MonitoringRule::Velocity { name, .. }
+            | MonitoringRule::Structuring { name, .. }
+            | MonitoringRule::FanOut { name, .. } => name,
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        match self {
+            MonitoringRule::Velocity { window_minutes, .. }
+            | MonitoringRule::Structuring { window_minutes, .. }
+            | MonitoringRule::FanOut { window_minutes, .. } => Duration::minutes(*window_minutes),
+        }
+    }
+
+    // `recent` holds the party's transactions inside this rule's window, oldest
+    // first; `first_seen` is when the party first paid each beneficiary.
+    pub fn evaluate(
+        &self,
+        recent: &[&ObservedTransaction],
+        first_seen: &HashMap<String, DateTime<Utc>>,
+        window_start: DateTime<Utc>,
+    ) -> Option<RuleMatch> {
+        match self {
+            MonitoringRule::Velocity {
+                max_count,
+                window_minutes,
+                ..
+            } => (recent.len() > *max_count).then(|| RuleMatch {
+                reason: format!(
+                    "{} payments within {} minutes exceeds the limit of {}",
+                    recent.len(),
+                    window_minutes,
+                    max_count
+                ),
+                transactions: recent.iter().map(|&txn| txn.clone()).collect(),
+            }),
+            MonitoringRule::Structuring {
+                threshold,
+                currency,
+                margin_percent,
+                min_count,
+                window_minutes,
+                ..
+            } => {
+                let floor = threshold * (1.0 - margin_percent / 100.0);
+                let below: Vec<ObservedTransaction> = recent
+                    .iter()
+                    .filter(|txn| txn.currency.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(currency)))
+                    .filter(|txn| txn.amount.is_some_and(|amount| amount >= floor && amount < *threshold))
+                    .map(|&txn| txn.clone())
+                    .collect();
+                (below.len() >= *min_count).then(|| RuleMatch {
+                    reason: format!(
+                        "{} payments between {:.2} and {:.2} {} within {} minutes",
+                        below.len(),
+                        floor,
+                        threshold,
+                        currency,
+                        window_minutes
+                    ),
+                    transactions: below,
+                })
+            }
+            MonitoringRule::FanOut {
+                max_new_beneficiaries,
+                window_minutes,
+                ..
+            } => {
+                let is_new = |beneficiary: &str| first_seen.get(beneficiary).is_none_or(|seen| *seen >= window_start);
+                let mut beneficiaries = HashSet::new();
+                let to_new: Vec<ObservedTransaction> = recent
+                    .iter()
+                    .filter(|txn| txn.beneficiary.as_deref().is_some_and(is_new))
+                    .map(|&txn| txn.clone())
+                    .collect();
+                for txn in &to_new {
+                    beneficiaries.extend(txn.beneficiary.clone());
+                }
+                (beneficiaries.len() > *max_new_beneficiaries).then(|| RuleMatch {
+                    reason: format!(
+                        "payments to {} new beneficiaries within {} minutes exceeds the limit of {}",
+                        beneficiaries.len(),
+                        window_minutes,
+                        max_new_beneficiaries
+                    ),
+                    transactions: to_new,
+                })
+            }
+        }
+    }
+}
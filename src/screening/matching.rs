@@ -0,0 +1,130 @@
+use strsim::jaro_winkler;
+
+use crate::validation::charset::CharacterSet;
+
+// Legal-form and filler words carry no identifying weight and would otherwise
+// inflate scores between unrelated companies.
+const NOISE_TOKENS: &[&str] = &[
+    "AG", "AND", "BV", "CO", "COMPANY", "CORP", "CORPORATION", "GMBH", "INC", "LIMITED", "LLC", "LTD", "NV",
+    "OF", "PLC", "SA", "SARL", "SPA", "THE",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedName {
+    pub original: String,
+    pub tokens: Vec<String>,
+    joined: String,
+    sorted: String,
+}
+
+impl PreparedName {
+    pub fn new(name: &str) -> Self {
+        let tokens = tokenize(name);
+        let joined = tokens.join(" ");
+        let mut sorted_tokens = tokens.clone();
+        sorted_tokens.sort();
+        Self {
+            original: name.to_string(),
+            sorted: sorted_tokens.join(" "),
+            joined,
+            tokens,
+        }
+    }
+
+    pub fn from_tokens(tokens: &[String]) -> Self {
+        Self::new(&tokens.join(" "))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    CharacterSet::SepaBasicLatin
+        .transliterate(text)
+        .to_uppercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| !token.is_empty() && !NOISE_TOKENS.contains(token))
+        .map(str::to_string)
+        .collect()
+}
+
+// Letters the SEPA transliteration has no Latin form for, such as Arabic or
+// Han script. They produce no tokens, so a name written in them cannot be
+// matched against the romanised lists at all.
+pub fn has_unscreenable_letters(text: &str) -> bool {
+    text.chars()
+        .filter(|c| c.is_alphabetic() && !c.is_ascii())
+        .any(|c| {
+            !CharacterSet::SepaBasicLatin
+                .transliterate(c.encode_utf8(&mut [0; 4]))
+                .chars()
+                .any(|mapped| mapped.is_ascii_alphanumeric())
+        })
+}
+
+// Best of in-order and token-sorted Jaro-Winkler, so "HUSSEIN SADDAM" still
+// matches "SADDAM HUSSEIN".
+pub fn score(candidate: &PreparedName, listed: &PreparedName) -> f64 {
+    if candidate.is_empty() || listed.is_empty() {
+        return 0.0;
+    }
+    let shorter = candidate.joined.len().min(listed.joined.len()) as f64;
+    let longer = candidate.joined.len().max(listed.joined.len()) as f64;
+    if shorter / longer < 0.5 {
+        return 0.0;
+    }
+    jaro_winkler(&candidate.joined, &listed.joined).max(jaro_winkler(&candidate.sorted, &listed.sorted))
+}
+
+// Slides a window the size of the listed name over free text such as
+// remittance information and returns the best scoring window.
+pub fn best_window_score(text_tokens: &[String], listed: &PreparedName) -> Option<(f64, String)> {
+    let width = listed.tokens.len();
+    if width == 0 || text_tokens.len() < width {
+        return None;
+    }
+    text_tokens
+        .windows(width)
+        .map(|window| {
+            let candidate = PreparedName::from_tokens(window);
+            (score(&candidate, listed), candidate.original)
+        })
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reordered_and_accented_names() {
+        let listed = PreparedName::new("Saddam Hussein Al-Tikriti");
+        let candidate = PreparedName::new("HUSSEIN, Saddám Al Tikriti");
+
+        assert!(score(&candidate, &listed) > 0.95);
+    }
+
+    #[test]
+    fn ignores_legal_form_noise() {
+        assert_eq!(tokenize("The Acme Trading Co. Ltd"), vec!["ACME", "TRADING"]);
+    }
+
+    #[test]
+    fn detects_names_that_cannot_be_romanised() {
+        assert!(!has_unscreenable_letters("Saddám Hussein"));
+        assert!(has_unscreenable_letters("محمد"));
+        assert!(tokenize("محمد").is_empty());
+    }
+
+    #[test]
+    fn finds_names_inside_free_text() {
+        let listed = PreparedName::new("Aerocaribbean Airlines");
+        let text = tokenize("Invoice 42 tickets AEROCARIBEAN AIRLINES Havana");
+
+        let (best, window) = best_window_score(&text, &listed).unwrap();
+        assert!(best > 0.9);
+        assert_eq!(window, "AEROCARIBEAN AIRLINES");
+    }
+}
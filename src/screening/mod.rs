@@ -0,0 +1,5 @@
+pub mod matching;
+pub mod screener;
+pub mod watch_list;
+
+pub use screener::{SanctionsScreener, ScreeningOutcome, ScreeningSettings, UnscreenableValue, WatchListScreener};
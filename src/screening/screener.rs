@@ -0,0 +1,346 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::info;
+
+use crate::domain::payment::{PaymentRequest, ScreeningHit};
+use crate::domain::payment_details::{find, find_all, transactions};
+use crate::error::ScreeningError;
+use crate::screening::matching::{best_window_score, has_unscreenable_letters, score, tokenize, PreparedName};
+use crate::screening::watch_list::{load_file, ListFormat, WatchListEntry};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchListSource {
+    pub format: ListFormat,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScreeningSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub lists: Vec<WatchListSource>,
+    #[serde(default = "default_name_threshold")]
+    pub name_threshold: f64,
+    #[serde(default = "default_free_text_threshold")]
+    pub free_text_threshold: f64,
+}
+
+fn default_name_threshold() -> f64 {
+    0.88
+}
+
+fn default_free_text_threshold() -> f64 {
+    0.94
+}
+
+impl Default for ScreeningSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lists: Vec::new(),
+            name_threshold: default_name_threshold(),
+            free_text_threshold: default_free_text_threshold(),
+        }
+    }
+}
+
+// A name or text the lists could not be checked against, typically because
+// it is written in a script the transliteration has no Latin form for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnscreenableValue {
+    pub field: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScreeningOutcome {
+    pub hits: Vec<ScreeningHit>,
+    pub unscreenable: Vec<UnscreenableValue>,
+}
+
+#[async_trait]
+pub trait SanctionsScreener: Send + Sync {
+    async fn screen(&self, request: &PaymentRequest) -> Result<ScreeningOutcome, ScreeningError>;
+}
+
+enum SubjectKind {
+    Name,
+    Identifier,
+    FreeText,
+}
+
+struct Subject {
+    field: String,
+    value: String,
+    kind: SubjectKind,
+}
+
+struct IndexedEntry {
+    entry: WatchListEntry,
+    names: Vec<PreparedName>,
+}
+
+pub struct WatchListScreener {
+    settings: ScreeningSettings,
+    entries: Vec<IndexedEntry>,
+}
+
+impl WatchListScreener {
+    pub fn new(settings: ScreeningSettings, entries: Vec<WatchListEntry>) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|entry| IndexedEntry {
+                names: entry.names.iter().map(|name| PreparedName::new(name)).collect(),
+                entry,
+            })
+            .collect();
+        Self { settings, entries }
+    }
+
+    pub fn load(settings: ScreeningSettings) -> Result<Self, ScreeningError> {
+        let mut entries = Vec::new();
+        for source in &settings.lists {
+            let loaded = load_file(source.format, &source.path)?;
+            info!(
+                list = source.format.label(),
+                path = %source.path.display(),
+                entries = loaded.len(),
+                "Loaded sanctions list"
+            );
+            entries.extend(loaded);
+        }
+        Ok(Self::new(settings, entries))
+    }
+
+    // Parties and remittance information of every transaction; in a batch the
+    // field is qualified with the transaction's pointer.
+    fn subjects(payload: &Value) -> Vec<Subject> {
+        let transactions = transactions(payload);
+        let batch = transactions.len() > 1;
+        let mut subjects: Vec<Subject> = Vec::new();
+        let mut push = |field: String, value: Option<String>, kind: SubjectKind| {
+            if let Some(value) = value {
+                if !subjects.iter().any(|s| s.field == field && s.value == value) {
+                    subjects.push(Subject { field, value, kind });
+                }
+            }
+        };
+
+        for transaction in &transactions {
+            let label = |field: &str| {
+                if batch {
+                    format!("{} ({})", field, transaction.path)
+                } else {
+                    field.to_string()
+                }
+            };
+            let details = &transaction.details;
+            push(label("debtor"), details.debtor_name.clone(), SubjectKind::Name);
+            push(label("creditor"), details.creditor_name.clone(), SubjectKind::Name);
+            push(
+                label("remittance_information"),
+                details.remittance_information.clone(),
+                SubjectKind::FreeText,
+            );
+            for element in ["UltmtDbtr", "UltmtCdtr"] {
+                push(label(element), party_name(transaction.find(element)), SubjectKind::Name);
+            }
+        }
+        push("InitgPty".to_string(), party_name(find(payload, "InitgPty")), SubjectKind::Name);
+        for element in ["DbtrAgt", "CdtrAgt", "IntrmyAgt1", "IntrmyAgt2", "IntrmyAgt3", "InstgAgt", "InstdAgt"] {
+            for agent in find_all(payload, element) {
+                let institution = agent.get("FinInstnId");
+                push(element.to_string(), party_name(institution), SubjectKind::Name);
+                let bic = institution
+                    .and_then(|id| id.get("BICFI").or_else(|| id.get("BIC")))
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                push(element.to_string(), bic, SubjectKind::Identifier);
+            }
+        }
+        subjects
+    }
+
+    fn screen_subject(&self, subject: &Subject) -> Vec<ScreeningHit> {
+        let mut hits = Vec::new();
+        let candidate = PreparedName::new(&subject.value);
+        let text_tokens = tokenize(&subject.value);
+
+        for indexed in &self.entries {
+            let best = match subject.kind {
+                SubjectKind::Identifier => indexed
+                    .entry
+                    .identifiers
+                    .iter()
+                    .find(|id| bic_matches(id, &subject.value))
+                    .map(|id| (1.0, id.clone())),
+                SubjectKind::Name => indexed
+                    .names
+                    .iter()
+                    .map(|name| (score(&candidate, name), name.original.clone()))
+                    .filter(|(score, _)| *score >= self.settings.name_threshold)
+                    .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal)),
+                SubjectKind::FreeText => indexed
+                    .names
+                    .iter()
+                    .filter_map(|name| best_window_score(&text_tokens, name).map(|(score, _)| (score, name.original.clone())))
+                    .filter(|(score, _)| *score >= self.settings.free_text_threshold)
+                    .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal)),
+            };
+
+            if let Some((score, matched_name)) = best {
+                hits.push(ScreeningHit {
+                    list: indexed.entry.list.label().to_string(),
+                    entry_id: indexed.entry.entry_id.clone(),
+                    matched_name,
+                    field: subject.field.clone(),
+                    screened_value: subject.value.clone(),
+                    score,
+                    programs: indexed.entry.programs.clone(),
+                });
+            }
+        }
+        hits
+    }
+}
+
+#[async_trait]
+impl SanctionsScreener for WatchListScreener {
+    async fn screen(&self, request: &PaymentRequest) -> Result<ScreeningOutcome, ScreeningError> {
+        if !self.settings.enabled {
+            return Ok(ScreeningOutcome::default());
+        }
+        let mut outcome = ScreeningOutcome::default();
+        for subject in Self::subjects(&request.message_payload) {
+            outcome.hits.extend(self.screen_subject(&subject));
+            if !matches!(subject.kind, SubjectKind::Identifier) && has_unscreenable_letters(&subject.value) {
+                outcome.unscreenable.push(UnscreenableValue {
+                    field: subject.field,
+                    value: subject.value,
+                });
+            }
+        }
+        outcome
+            .hits
+            .sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(outcome)
+    }
+}
+
+fn party_name(party: Option<&Value>) -> Option<String> {
+    party
+        .and_then(|party| party.get("Nm"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+// An 8-character BIC on the list covers every branch of the institution.
+fn bic_matches(listed: &str, screened: &str) -> bool {
+    let listed = listed.trim().to_uppercase();
+    let screened = screened.trim().to_uppercase();
+    listed == screened || (listed.len() == 8 && screened.starts_with(&listed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::PaymentType;
+    use serde_json::json;
+
+    fn screener() -> WatchListScreener {
+        WatchListScreener::new(
+            ScreeningSettings {
+                enabled: true,
+                ..ScreeningSettings::default()
+            },
+            vec![WatchListEntry {
+                list: ListFormat::OfacSdn,
+                entry_id: "36".to_string(),
+                names: vec!["AEROCARIBBEAN AIRLINES".to_string()],
+                identifiers: vec!["AEROCUHH".to_string()],
+                programs: vec!["CUBA".to_string()],
+            }],
+        )
+    }
+
+    fn request(payload: Value) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: payload,
+            sender_id: "sender".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn screens_parties_agents_and_remittance() {
+        let payload = json!({
+            "CdtTrfTxInf": {
+                "Dbtr": { "Nm": "Jane Doe" },
+                "Cdtr": { "Nm": "Aero Caribbean Airlines" },
+                "CdtrAgt": { "FinInstnId": { "BICFI": "AEROCUHHXXX" } },
+                "RmtInf": { "Ustrd": "Tickets from AEROCARIBBEAN AIRLINES" }
+            }
+        });
+
+        let hits = screener().screen(&request(payload)).await.unwrap().hits;
+        let fields: Vec<&str> = hits.iter().map(|hit| hit.field.as_str()).collect();
+
+        assert!(fields.contains(&"creditor"));
+        assert!(fields.contains(&"CdtrAgt"));
+        assert!(fields.contains(&"remittance_information"));
+        assert!(!fields.contains(&"debtor"));
+    }
+
+    #[tokio::test]
+    async fn screens_every_transaction_of_a_batch() {
+        let payload = json!({
+            "PmtInf": {
+                "Dbtr": { "Nm": "Jane Doe" },
+                "CdtTrfTxInf": [
+                    { "Cdtr": { "Nm": "John Smith" } },
+                    {
+                        "Cdtr": { "Nm": "Acme Ltd" },
+                        "UltmtCdtr": { "Nm": "Aero Caribbean Airlines" }
+                    }
+                ]
+            }
+        });
+
+        let hits = screener().screen(&request(payload)).await.unwrap().hits;
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].field, "UltmtCdtr (/PmtInf/CdtTrfTxInf/1)");
+    }
+
+    #[tokio::test]
+    async fn reports_names_that_cannot_be_screened() {
+        let payload = json!({
+            "CdtTrfTxInf": {
+                "Dbtr": { "Nm": "Jane Doe" },
+                "Cdtr": { "Nm": "شركة الطيران" }
+            }
+        });
+
+        let outcome = screener().screen(&request(payload)).await.unwrap();
+
+        assert!(outcome.hits.is_empty());
+        assert_eq!(
+            outcome.unscreenable,
+            vec![UnscreenableValue {
+                field: "creditor".to_string(),
+                value: "شركة الطيران".to_string(),
+            }]
+        );
+    }
+}
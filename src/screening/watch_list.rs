@@ -0,0 +1,321 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Deserialize;
+
+use crate::error::ScreeningError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListFormat {
+    OfacSdn,
+    EuConsolidated,
+    UnConsolidated,
+}
+
+impl ListFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ListFormat::OfacSdn => "OFAC SDN",
+            ListFormat::EuConsolidated => "EU Consolidated",
+            ListFormat::UnConsolidated => "UN Consolidated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchListEntry {
+    pub list: ListFormat,
+    pub entry_id: String,
+    pub names: Vec<String>,
+    pub identifiers: Vec<String>,
+    pub programs: Vec<String>,
+}
+
+pub fn load_file(format: ListFormat, path: &Path) -> Result<Vec<WatchListEntry>, ScreeningError> {
+    let display = path.display().to_string();
+    let file = File::open(path).map_err(|source| ScreeningError::Io {
+        path: display.clone(),
+        source,
+    })?;
+    load(format, BufReader::new(file)).map_err(|message| ScreeningError::Parse { path: display, message })
+}
+
+pub fn load<R: BufRead>(format: ListFormat, reader: R) -> Result<Vec<WatchListEntry>, String> {
+    let root = parse_tree(reader)?;
+    Ok(match format {
+        ListFormat::OfacSdn => ofac_entries(&root),
+        ListFormat::EuConsolidated => eu_entries(&root),
+        ListFormat::UnConsolidated => un_entries(&root),
+    })
+}
+
+// Minimal element tree; the lists are a few tens of megabytes at most.
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Node> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn descendants_named<'a>(&'a self, name: &str, found: &mut Vec<&'a Node>) {
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            }
+            child.descendants_named(name, found);
+        }
+    }
+
+    fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name).map(|child| child.text.trim().to_string()).filter(|text| !text.is_empty())
+    }
+
+    fn attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+fn local_name(raw: &[u8]) -> String {
+    let name = String::from_utf8_lossy(raw);
+    name.rsplit(':').next().unwrap_or_default().to_string()
+}
+
+fn start_node(element: &BytesStart) -> Result<Node, String> {
+    let mut attributes = Vec::new();
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+        attributes.push((local_name(attribute.key.as_ref()), value.into_owned()));
+    }
+    Ok(Node {
+        name: local_name(element.name().as_ref()),
+        attributes,
+        ..Node::default()
+    })
+}
+
+fn parse_tree<R: BufRead>(reader: R) -> Result<Node, String> {
+    let mut reader = Reader::from_reader(reader);
+    reader.trim_text(true);
+
+    let mut stack = vec![Node::default()];
+    let mut buffer = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buffer).map_err(|e| e.to_string())? {
+            Event::Start(element) => stack.push(start_node(&element)?),
+            Event::Empty(element) => {
+                let node = start_node(&element)?;
+                stack.last_mut().ok_or("unbalanced document")?.children.push(node);
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| e.to_string())?;
+                stack.last_mut().ok_or("unbalanced document")?.text.push_str(&text);
+            }
+            Event::CData(data) => {
+                let text = String::from_utf8_lossy(&data.into_inner()).into_owned();
+                stack.last_mut().ok_or("unbalanced document")?.text.push_str(&text);
+            }
+            Event::End(_) => {
+                let node = stack.pop().ok_or("unbalanced document")?;
+                stack.last_mut().ok_or("unbalanced document")?.children.push(node);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+
+    if stack.len() != 1 {
+        return Err("unexpected end of document".to_string());
+    }
+    stack.pop().ok_or_else(|| "empty document".to_string())
+}
+
+fn join_names(parts: &[Option<String>]) -> Option<String> {
+    let joined = parts.iter().flatten().cloned().collect::<Vec<_>>().join(" ");
+    Some(joined).filter(|name| !name.is_empty())
+}
+
+fn ofac_entries(root: &Node) -> Vec<WatchListEntry> {
+    let mut entries = Vec::new();
+    root.descendants_named("sdnEntry", &mut entries);
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let mut names: Vec<String> =
+                join_names(&[entry.child_text("firstName"), entry.child_text("lastName")]).into_iter().collect();
+            if let Some(akas) = entry.child("akaList") {
+                names.extend(
+                    akas.children_named("aka")
+                        .filter_map(|aka| join_names(&[aka.child_text("firstName"), aka.child_text("lastName")])),
+                );
+            }
+
+            let identifiers = entry
+                .child("idList")
+                .map(|ids| {
+                    ids.children_named("id")
+                        .filter(|id| id.child_text("idType").is_some_and(|kind| kind.contains("BIC")))
+                        .filter_map(|id| id.child_text("idNumber"))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let programs = entry
+                .child("programList")
+                .map(|programs| programs.children_named("program").map(|p| p.text.trim().to_string()).collect())
+                .unwrap_or_default();
+
+            WatchListEntry {
+                list: ListFormat::OfacSdn,
+                entry_id: entry.child_text("uid").unwrap_or_default(),
+                names,
+                identifiers,
+                programs,
+            }
+        })
+        .collect()
+}
+
+fn eu_entries(root: &Node) -> Vec<WatchListEntry> {
+    let mut entities = Vec::new();
+    root.descendants_named("sanctionEntity", &mut entities);
+
+    entities
+        .into_iter()
+        .map(|entity| {
+            let names = entity
+                .children_named("nameAlias")
+                .filter_map(|alias| {
+                    alias.attribute("wholeName").or_else(|| {
+                        join_names(&[
+                            alias.attribute("firstName"),
+                            alias.attribute("middleName"),
+                            alias.attribute("lastName"),
+                        ])
+                    })
+                })
+                .collect();
+
+            let identifiers = entity
+                .children_named("identification")
+                .filter(|id| id.attribute("identificationTypeCode").is_some_and(|kind| kind.eq_ignore_ascii_case("swiftbic")))
+                .filter_map(|id| id.attribute("number"))
+                .collect();
+
+            let programs = entity
+                .children_named("regulation")
+                .filter_map(|regulation| regulation.attribute("programme"))
+                .collect();
+
+            WatchListEntry {
+                list: ListFormat::EuConsolidated,
+                entry_id: entity.attribute("logicalId").unwrap_or_default(),
+                names,
+                identifiers,
+                programs,
+            }
+        })
+        .collect()
+}
+
+fn un_entries(root: &Node) -> Vec<WatchListEntry> {
+    let mut records = Vec::new();
+    root.descendants_named("INDIVIDUAL", &mut records);
+    root.descendants_named("ENTITY", &mut records);
+
+    records
+        .into_iter()
+        .map(|record| {
+            let mut names: Vec<String> = join_names(&[
+                record.child_text("FIRST_NAME"),
+                record.child_text("SECOND_NAME"),
+                record.child_text("THIRD_NAME"),
+                record.child_text("FOURTH_NAME"),
+            ])
+            .into_iter()
+            .collect();
+            for alias_element in ["INDIVIDUAL_ALIAS", "ENTITY_ALIAS"] {
+                names.extend(record.children_named(alias_element).filter_map(|alias| alias.child_text("ALIAS_NAME")));
+            }
+
+            WatchListEntry {
+                list: ListFormat::UnConsolidated,
+                entry_id: record
+                    .child_text("REFERENCE_NUMBER")
+                    .or_else(|| record.child_text("DATAID"))
+                    .unwrap_or_default(),
+                names,
+                identifiers: Vec::new(),
+                programs: record.child_text("UN_LIST_TYPE").into_iter().collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ofac_sdn_entries_with_akas() {
+        let xml = r#"<?xml version="1.0"?>
+<sdnList xmlns="http://tempuri.org/sdnList.xsd">
+  <sdnEntry>
+    <uid>36</uid>
+    <lastName>AEROCARIBBEAN AIRLINES</lastName>
+    <sdnType>Entity</sdnType>
+    <programList><program>CUBA</program></programList>
+    <akaList>
+      <aka><uid>12</uid><type>a.k.a.</type><lastName>AERO-CARIBBEAN</lastName></aka>
+    </akaList>
+  </sdnEntry>
+</sdnList>"#;
+
+        let entries = load(ListFormat::OfacSdn, xml.as_bytes()).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].entry_id, "36");
+        assert_eq!(entries[0].names, vec!["AEROCARIBBEAN AIRLINES", "AERO-CARIBBEAN"]);
+        assert_eq!(entries[0].programs, vec!["CUBA"]);
+    }
+
+    #[test]
+    fn parses_eu_and_un_entries() {
+        let eu = r#"<export><sanctionEntity logicalId="13">
+            <nameAlias wholeName="Saddam Hussein Al-Tikriti"/>
+            <regulation programme="IRQ"/>
+        </sanctionEntity></export>"#;
+        let un = r#"<CONSOLIDATED_LIST><INDIVIDUALS><INDIVIDUAL>
+            <DATAID>6908555</DATAID><FIRST_NAME>RI</FIRST_NAME><SECOND_NAME>WON HO</SECOND_NAME>
+            <UN_LIST_TYPE>DPRK</UN_LIST_TYPE><REFERENCE_NUMBER>KPi.033</REFERENCE_NUMBER>
+        </INDIVIDUAL></INDIVIDUALS></CONSOLIDATED_LIST>"#;
+
+        let eu = load(ListFormat::EuConsolidated, eu.as_bytes()).unwrap();
+        let un = load(ListFormat::UnConsolidated, un.as_bytes()).unwrap();
+
+        assert_eq!(eu[0].names, vec!["Saddam Hussein Al-Tikriti"]);
+        assert_eq!(eu[0].programs, vec!["IRQ"]);
+        assert_eq!(un[0].entry_id, "KPi.033");
+        assert_eq!(un[0].names, vec!["RI WON HO"]);
+    }
+}
@@ -0,0 +1,175 @@
+use serde::Deserialize;
+
+use crate::domain::payment::{ApprovalRequirement, PaymentRequest, PaymentType};
+use crate::domain::payment_details::{find_all, transactions};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApprovalThreshold {
+    pub currency: String,
+    pub amount: f64,
+    #[serde(default)]
+    pub payment_types: Vec<PaymentType>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApprovalSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub thresholds: Vec<ApprovalThreshold>,
+    // Messages carrying at least this many transactions need approval.
+    #[serde(default)]
+    pub bulk_transaction_count: Option<usize>,
+    // Channels whose payments always need approval, e.g. manual repairs.
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default = "default_required_approvals")]
+    pub required_approvals: usize,
+}
+
+fn default_required_approvals() -> usize {
+    1
+}
+
+impl Default for ApprovalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            thresholds: Vec::new(),
+            bulk_transaction_count: None,
+            channels: Vec::new(),
+            required_approvals: default_required_approvals(),
+        }
+    }
+}
+
+impl ApprovalSettings {
+    pub fn requirement(&self, request: &PaymentRequest) -> Option<ApprovalRequirement> {
+        if !self.enabled {
+            return None;
+        }
+
+        let reason = self
+            .channel_reason(request)
+            .or_else(|| self.amount_reason(request))
+            .or_else(|| self.bulk_reason(request))?;
+        Some(ApprovalRequirement {
+            reason,
+            required_approvals: self.required_approvals.max(1),
+        })
+    }
+
+    fn channel_reason(&self, request: &PaymentRequest) -> Option<String> {
+        let channel = request.channel.as_deref()?;
+        self.channels
+            .iter()
+            .any(|c| c == channel)
+            .then(|| format!("payments from channel {} require approval", channel))
+    }
+
+    // Thresholds apply to the message total in each currency, so splitting a
+    // payment into several transactions does not avoid approval.
+    fn amount_reason(&self, request: &PaymentRequest) -> Option<String> {
+        let mut totals: Vec<(String, f64)> = Vec::new();
+        for transaction in transactions(&request.message_payload) {
+            let details = transaction.details;
+            if let (Some(amount), Some(currency)) = (details.amount, details.currency) {
+                let currency = currency.to_uppercase();
+                match totals.iter_mut().find(|(seen, _)| *seen == currency) {
+                    Some((_, total)) => *total += amount,
+                    None => totals.push((currency, amount)),
+                }
+            }
+        }
+        self.thresholds
+            .iter()
+            .filter(|threshold| threshold.payment_types.is_empty() || threshold.payment_types.contains(&request.payment_type))
+            .find_map(|threshold| {
+                totals
+                    .iter()
+                    .find(|(currency, total)| threshold.currency.eq_ignore_ascii_case(currency) && *total >= threshold.amount)
+                    .map(|(currency, total)| {
+                        format!(
+                            "amount {:.2} {} is at or above the approval threshold of {:.2}",
+                            total, currency, threshold.amount
+                        )
+                    })
+            })
+    }
+
+    fn bulk_reason(&self, request: &PaymentRequest) -> Option<String> {
+        let limit = self.bulk_transaction_count?;
+        let transactions = ["CdtTrfTxInf", "DrctDbtTxInf"]
+            .iter()
+            .flat_map(|element| find_all(&request.message_payload, element))
+            .map(|found| found.as_array().map_or(1, Vec::len))
+            .sum::<usize>();
+        (transactions >= limit).then(|| {
+            format!(
+                "{} transactions in one message reaches the bulk approval limit of {}",
+                transactions, limit
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(payload: serde_json::Value, channel: Option<&str>) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pain.001".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: payload,
+            sender_id: "sender".to_string(),
+            request_id: "request".to_string(),
+            channel: channel.map(str::to_string),
+            uetr: None,
+            submitted_by: Some("alice".to_string()),
+        }
+    }
+
+    #[test]
+    fn requires_approval_above_threshold_for_bulk_and_for_repair_channel() {
+        let settings = ApprovalSettings {
+            enabled: true,
+            thresholds: vec![ApprovalThreshold {
+                currency: "EUR".to_string(),
+                amount: 50_000.0,
+                payment_types: Vec::new(),
+            }],
+            bulk_transaction_count: Some(3),
+            channels: vec!["repair".to_string()],
+            ..ApprovalSettings::default()
+        };
+        let single = |amount: f64| json!({ "CdtTrfTxInf": { "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": amount } } } });
+        let bulk = json!({ "PmtInf": { "CdtTrfTxInf": [{}, {}, {}] } });
+
+        assert!(settings.requirement(&request(single(1_000.0), None)).is_none());
+        assert!(settings.requirement(&request(single(75_000.0), None)).is_some());
+        assert!(settings.requirement(&request(bulk, None)).is_some());
+        assert!(settings.requirement(&request(single(1_000.0), Some("repair"))).is_some());
+    }
+
+    #[test]
+    fn totals_every_transaction_against_the_threshold() {
+        let settings = ApprovalSettings {
+            enabled: true,
+            thresholds: vec![ApprovalThreshold {
+                currency: "EUR".to_string(),
+                amount: 50_000.0,
+                payment_types: Vec::new(),
+            }],
+            ..ApprovalSettings::default()
+        };
+        let split = json!({ "PmtInf": { "CdtTrfTxInf": [
+            { "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 30_000.0 } } },
+            { "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 30_000.0 } } }
+        ] } });
+
+        let requirement = settings.requirement(&request(split, None)).unwrap();
+        assert!(requirement.reason.contains("60000.00 EUR"));
+    }
+}
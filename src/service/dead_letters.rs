@@ -0,0 +1,263 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::domain::dead_letter::{
+    DeadLetter, DeadLetterAction, DeadLetterActionKind, DeadLetterQuery, DeadLetterStatus, DeadLetterSummary,
+    DiscardRequest, ResubmitRequest,
+};
+use crate::error::DeadLetterError;
+use crate::infrastructure::database::dead_letters::DeadLetterStore;
+use crate::infrastructure::messaging::dead_letters::DeadLetterQueue;
+
+// Operator handling of dead-lettered messages. Everything that exposes or
+// changes a message is recorded with who did it and why; the actor is the
+// authenticated operator, never taken from the request.
+pub struct DeadLetterService {
+    store: Arc<dyn DeadLetterStore>,
+    queue: Arc<dyn DeadLetterQueue>,
+}
+
+impl DeadLetterService {
+    pub fn new(store: Arc<dyn DeadLetterStore>, queue: Arc<dyn DeadLetterQueue>) -> Self {
+        Self { store, queue }
+    }
+
+    pub async fn list(&self, query: &DeadLetterQuery) -> Result<Vec<DeadLetterSummary>, DeadLetterError> {
+        let letters = self.store.list(query).await?;
+        Ok(letters.iter().map(DeadLetterSummary::from).collect())
+    }
+
+    // The payload may hold customer data, so reading it is audited too.
+    pub async fn view(&self, id: &Uuid, actor: &str) -> Result<DeadLetter, DeadLetterError> {
+        let letter = self.letter(id).await?;
+        self.store
+            .record_action(action(id, DeadLetterActionKind::Viewed, actor, None, None))
+            .await?;
+        Ok(letter)
+    }
+
+    pub async fn actions(&self, id: &Uuid) -> Result<Vec<DeadLetterAction>, DeadLetterError> {
+        self.letter(id).await?;
+        Ok(self.store.actions(id).await?)
+    }
+
+    // The letter is claimed before it is published so two operators, or a
+    // retried request, cannot put the same message back on the queue twice.
+    pub async fn resubmit(
+        &self,
+        id: &Uuid,
+        actor: &str,
+        request: ResubmitRequest,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        let reason = required(&request.reason, "reason")?;
+        let mut letter = self.pending(id).await?;
+        let edited = request.payload.map(|payload| payload.to_string());
+        let payload = edited.as_deref().unwrap_or(&letter.payload).to_string();
+
+        self.move_to(&mut letter, DeadLetterStatus::Pending, DeadLetterStatus::Resubmitting, None)
+            .await?;
+        if let Err(e) = self.queue.resubmit(&letter, payload.as_bytes()).await {
+            // Not published, so the letter can be tried again.
+            if let Err(release) = self
+                .store
+                .transition(id, DeadLetterStatus::Resubmitting, DeadLetterStatus::Pending, None)
+                .await
+            {
+                error!(dead_letter_id = %id, "Failed to release dead letter after a failed resubmit: {:?}", release);
+            }
+            return Err(e.into());
+        }
+        let resubmitted = action(id, DeadLetterActionKind::Resubmitted, actor, Some(reason), edited.clone());
+        self.move_to(
+            &mut letter,
+            DeadLetterStatus::Resubmitting,
+            DeadLetterStatus::Resubmitted,
+            Some(resubmitted),
+        )
+        .await?;
+        info!(
+            dead_letter_id = %id,
+            queue = %letter.queue,
+            actor,
+            edited = edited.is_some(),
+            "Resubmitted dead letter: {}",
+            reason
+        );
+        Ok(letter)
+    }
+
+    pub async fn discard(
+        &self,
+        id: &Uuid,
+        actor: &str,
+        request: DiscardRequest,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        let reason = required(&request.reason, "reason")?;
+        let mut letter = self.pending(id).await?;
+
+        let discarded = action(id, DeadLetterActionKind::Discarded, actor, Some(reason), None);
+        self.move_to(&mut letter, DeadLetterStatus::Pending, DeadLetterStatus::Discarded, Some(discarded))
+            .await?;
+        info!(dead_letter_id = %id, queue = %letter.queue, actor, "Discarded dead letter: {}", reason);
+        Ok(letter)
+    }
+
+    async fn letter(&self, id: &Uuid) -> Result<DeadLetter, DeadLetterError> {
+        self.store.get(id).await?.ok_or(DeadLetterError::NotFound(*id))
+    }
+
+    async fn pending(&self, id: &Uuid) -> Result<DeadLetter, DeadLetterError> {
+        let letter = self.letter(id).await?;
+        if letter.status != DeadLetterStatus::Pending {
+            return Err(DeadLetterError::AlreadyResolved {
+                id: *id,
+                status: letter.status,
+            });
+        }
+        Ok(letter)
+    }
+
+    async fn move_to(
+        &self,
+        letter: &mut DeadLetter,
+        from: DeadLetterStatus,
+        status: DeadLetterStatus,
+        action: Option<DeadLetterAction>,
+    ) -> Result<(), DeadLetterError> {
+        if !self.store.transition(&letter.id, from, status, action).await? {
+            // Someone else got there first.
+            let current = self.letter(&letter.id).await?;
+            return Err(DeadLetterError::AlreadyResolved {
+                id: letter.id,
+                status: current.status,
+            });
+        }
+        letter.status = status;
+        Ok(())
+    }
+}
+
+fn required<'a>(value: &'a str, field: &'static str) -> Result<&'a str, DeadLetterError> {
+    match value.trim() {
+        "" => Err(DeadLetterError::Missing(field)),
+        value => Ok(value),
+    }
+}
+
+fn action(
+    id: &Uuid,
+    kind: DeadLetterActionKind,
+    actor: &str,
+    reason: Option<&str>,
+    edited_payload: Option<String>,
+) -> DeadLetterAction {
+    DeadLetterAction {
+        id: Uuid::new_v4(),
+        dead_letter_id: *id,
+        action: kind,
+        actor: actor.to_string(),
+        reason: reason.map(str::to_string),
+        edited_payload,
+        at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MessagingError;
+    use crate::infrastructure::database::dead_letters::InMemoryDeadLetterStore;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingQueue {
+        resubmitted: Mutex<Vec<(String, String)>>,
+        unavailable: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl DeadLetterQueue for RecordingQueue {
+        async fn resubmit(&self, letter: &DeadLetter, payload: &[u8]) -> Result<(), MessagingError> {
+            if *self.unavailable.lock().unwrap() {
+                return Err(MessagingError::Connection("broker down".to_string()));
+            }
+            let payload = String::from_utf8(payload.to_vec()).unwrap();
+            self.resubmitted.lock().unwrap().push((letter.queue.clone(), payload));
+            Ok(())
+        }
+    }
+
+    fn letter() -> DeadLetter {
+        DeadLetter {
+            id: Uuid::new_v4(),
+            queue: "upstream.core-banking".to_string(),
+            message_id: Some("MSG-1".to_string()),
+            message_type: Some("pacs.008".to_string()),
+            correlation_id: None,
+            reply_to: None,
+            failure_class: Some("transient".to_string()),
+            failure_reason: Some("database unavailable".to_string()),
+            attempts: 6,
+            payload: r#"{"GrpHdr":{"MsgId":"MSG-1"}}"#.to_string(),
+            dead_lettered_at: Utc::now(),
+            status: DeadLetterStatus::Pending,
+        }
+    }
+
+    #[tokio::test]
+    async fn resubmits_edited_payload_once_and_audits_every_step() {
+        let store = Arc::new(InMemoryDeadLetterStore::new());
+        let queue = Arc::new(RecordingQueue::default());
+        let service = DeadLetterService::new(store.clone(), queue.clone());
+        let letter = letter();
+        store.capture(letter.clone()).await.unwrap();
+
+        let viewed = service.view(&letter.id, "ops-1").await.unwrap();
+        assert_eq!(viewed.failure_reason.as_deref(), Some("database unavailable"));
+
+        let request = |payload| ResubmitRequest {
+            reason: "fixed debtor name".to_string(),
+            payload,
+        };
+        let edited = json!({ "GrpHdr": { "MsgId": "MSG-1" }, "fixed": true });
+
+        // A failed publish leaves the letter pending for another try.
+        *queue.unavailable.lock().unwrap() = true;
+        assert!(matches!(
+            service.resubmit(&letter.id, "ops-1", request(None)).await,
+            Err(DeadLetterError::Messaging(_))
+        ));
+        assert_eq!(store.get(&letter.id).await.unwrap().unwrap().status, DeadLetterStatus::Pending);
+        *queue.unavailable.lock().unwrap() = false;
+
+        let resubmitted = service
+            .resubmit(&letter.id, "ops-1", request(Some(edited.clone())))
+            .await
+            .unwrap();
+        assert_eq!(resubmitted.status, DeadLetterStatus::Resubmitted);
+        assert_eq!(
+            *queue.resubmitted.lock().unwrap(),
+            vec![("upstream.core-banking".to_string(), edited.to_string())]
+        );
+
+        let again = service.resubmit(&letter.id, "ops-1", request(None)).await;
+        assert!(matches!(again, Err(DeadLetterError::AlreadyResolved { .. })));
+        let discard = DiscardRequest {
+            reason: " ".to_string(),
+        };
+        assert!(matches!(
+            service.discard(&letter.id, "ops-2", discard).await,
+            Err(DeadLetterError::Missing("reason"))
+        ));
+
+        let actions = service.actions(&letter.id).await.unwrap();
+        let kinds: Vec<DeadLetterActionKind> = actions.iter().map(|action| action.action).collect();
+        assert_eq!(kinds, vec![DeadLetterActionKind::Viewed, DeadLetterActionKind::Resubmitted]);
+        assert_eq!(actions[1].edited_payload, Some(edited.to_string()));
+    }
+}
@@ -0,0 +1,415 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::payment::{Payment, PaymentRequest, PaymentType};
+use crate::domain::payment_details::transactions;
+use crate::domain::status_reason::ReasonCode;
+use crate::error::{RepositoryError, ServiceError, ValidationError};
+use crate::infrastructure::database::limits::{LimitCharge, LimitPeriod, LimitRepository, UsageKey};
+
+const LIMITS_RULE: &str = "payment_limits";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitScope {
+    Sender,
+    DebitAccount,
+}
+
+// A rule only counts payments in its own currency; payments in other
+// currencies need a rule of their own and are refused until they have one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LimitRule {
+    pub name: String,
+    pub scope: LimitScope,
+    // Specific sender ids or accounts; empty applies the rule to each one.
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    #[serde(default)]
+    pub payment_types: Vec<PaymentType>,
+    pub currency: String,
+    #[serde(default)]
+    pub single_max: Option<Decimal>,
+    #[serde(default)]
+    pub daily_amount: Option<Decimal>,
+    #[serde(default)]
+    pub monthly_amount: Option<Decimal>,
+    #[serde(default)]
+    pub daily_count: Option<u64>,
+    #[serde(default)]
+    pub monthly_count: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LimitSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub rules: Vec<LimitRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeadroomQuery {
+    pub sender_id: String,
+    #[serde(default)]
+    pub debit_account: Option<String>,
+    #[serde(default)]
+    pub payment_type: Option<PaymentType>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodHeadroom {
+    pub period: LimitPeriod,
+    pub period_start: NaiveDate,
+    pub max_amount: Option<Decimal>,
+    pub used_amount: Decimal,
+    pub remaining_amount: Option<Decimal>,
+    pub max_count: Option<u64>,
+    pub used_count: u64,
+    pub remaining_count: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitHeadroom {
+    pub rule: String,
+    pub scope: LimitScope,
+    pub subject: String,
+    pub currency: String,
+    pub payment_types: Vec<PaymentType>,
+    pub single_max: Option<Decimal>,
+    pub periods: Vec<PeriodHeadroom>,
+}
+
+impl LimitRule {
+    fn subject(&self, sender_id: &str, debit_account: Option<&str>) -> Option<String> {
+        let subject = match self.scope {
+            LimitScope::Sender => Some(sender_id),
+            LimitScope::DebitAccount => debit_account,
+        }?;
+        (self.subjects.is_empty() || self.subjects.iter().any(|s| s == subject)).then(|| subject.to_string())
+    }
+
+    fn covers(&self, payment_type: Option<PaymentType>) -> bool {
+        match payment_type {
+            Some(payment_type) => self.payment_types.is_empty() || self.payment_types.contains(&payment_type),
+            None => true,
+        }
+    }
+
+    fn periods(&self, at: DateTime<Utc>) -> Vec<(LimitPeriod, NaiveDate, Option<Decimal>, Option<u64>)> {
+        let day = at.date_naive();
+        let month = NaiveDate::from_ymd_opt(day.year(), day.month(), 1).unwrap_or(day);
+        [
+            (LimitPeriod::Day, day, self.daily_amount, self.daily_count),
+            (LimitPeriod::Month, month, self.monthly_amount, self.monthly_count),
+        ]
+        .into_iter()
+        .filter(|(_, _, amount, count)| amount.is_some() || count.is_some())
+        .collect()
+    }
+}
+
+pub struct PaymentLimits {
+    settings: LimitSettings,
+    repository: Box<dyn LimitRepository>,
+}
+
+impl PaymentLimits {
+    pub fn new(settings: LimitSettings, repository: Box<dyn LimitRepository>) -> Self {
+        Self { settings, repository }
+    }
+
+    fn applicable<'a>(
+        &'a self,
+        sender_id: &'a str,
+        debit_account: Option<&'a str>,
+        payment_type: Option<PaymentType>,
+    ) -> impl Iterator<Item = (&'a LimitRule, String)> + 'a {
+        self.settings
+            .rules
+            .iter()
+            .filter(move |rule| self.settings.enabled && rule.covers(payment_type))
+            .filter_map(move |rule| rule.subject(sender_id, debit_account).map(|subject| (rule, subject)))
+    }
+
+    // One charge per rule, subject and period covering every transaction of
+    // the message, so a batch counts as the payments in it.
+    fn charges(&self, request: &PaymentRequest, at: DateTime<Utc>) -> Result<Vec<LimitCharge>, ValidationError> {
+        let mut charges: Vec<LimitCharge> = Vec::new();
+        for transaction in transactions(&request.message_payload) {
+            let details = transaction.details;
+            let rules: Vec<(&LimitRule, String)> = self
+                .applicable(
+                    &request.sender_id,
+                    details.debtor_account.as_deref(),
+                    Some(request.payment_type),
+                )
+                .collect();
+            let (subject_rule, subject) = match rules.first() {
+                Some(first) => first,
+                None => continue,
+            };
+            // Payload amounts are parsed as f64; from_f64 rounds them back to
+            // the decimal they were written as.
+            let amount = details.amount.and_then(Decimal::from_f64);
+            let (amount, currency) = match (amount, details.currency.as_deref()) {
+                (Some(amount), Some(currency)) => (amount, currency),
+                _ => {
+                    return Err(limit_violation(
+                        ReasonCode::AM12,
+                        format!(
+                            "an amount and currency are needed to check {} for {}",
+                            subject_rule.name, subject
+                        ),
+                    ))
+                }
+            };
+            let matching: Vec<&(&LimitRule, String)> = rules
+                .iter()
+                .filter(|(rule, _)| rule.currency.eq_ignore_ascii_case(currency))
+                .collect();
+            if matching.is_empty() {
+                return Err(limit_violation(
+                    ReasonCode::AM03,
+                    format!(
+                        "no payment limit covers {} payments for {}; {} is set in {}",
+                        currency, subject, subject_rule.name, subject_rule.currency
+                    ),
+                ));
+            }
+
+            for (rule, subject) in matching {
+                if let Some(max) = rule.single_max.filter(|max| amount > *max) {
+                    return Err(limit_violation(
+                        ReasonCode::AM14,
+                        format!(
+                            "amount {:.2} {} exceeds the single payment limit of {:.2} set by {} for {}",
+                            amount, currency, max, rule.name, subject
+                        ),
+                    ));
+                }
+                for (period, period_start, max_amount, max_count) in rule.periods(at) {
+                    let key = UsageKey {
+                        rule: rule.name.clone(),
+                        subject: subject.clone(),
+                        period,
+                        period_start,
+                    };
+                    match charges.iter_mut().find(|charge| charge.key == key) {
+                        Some(charge) => {
+                            charge.amount += amount;
+                            charge.count += 1;
+                        }
+                        None => charges.push(LimitCharge {
+                            key,
+                            amount,
+                            count: 1,
+                            max_amount,
+                            max_count,
+                        }),
+                    }
+                }
+            }
+        }
+        Ok(charges)
+    }
+
+    pub async fn reserve(&self, request: &PaymentRequest, at: DateTime<Utc>) -> Result<(), ServiceError> {
+        let charges = self.charges(request, at).map_err(ServiceError::business_rule)?;
+        if charges.is_empty() {
+            return Ok(());
+        }
+
+        match self.repository.reserve(&charges).await? {
+            None => Ok(()),
+            Some(breach) => {
+                let key = &breach.charge.key;
+                let period = match key.period {
+                    LimitPeriod::Day => "daily",
+                    LimitPeriod::Month => "monthly",
+                };
+                let message = match breach.charge.max_amount {
+                    Some(max) if breach.used.amount + breach.charge.amount > max => format!(
+                        "{} amount limit of {:.2} set by {} for {} would be exceeded: {:.2} already used, {:.2} requested",
+                        period, max, key.rule, key.subject, breach.used.amount, breach.charge.amount
+                    ),
+                    _ => format!(
+                        "{} count limit of {} set by {} for {} reached",
+                        period,
+                        breach.charge.max_count.unwrap_or_default(),
+                        key.rule,
+                        key.subject
+                    ),
+                };
+                Err(ServiceError::business_rule(limit_violation(ReasonCode::AM14, message)))
+            }
+        }
+    }
+
+    // Gives the payment's usage back, e.g. when it is rejected after intake
+    // or could not be stored.
+    pub async fn release(&self, payment: &Payment) -> Result<(), RepositoryError> {
+        let charges = self.charges(&payment.request, payment.created_at).unwrap_or_default();
+        if charges.is_empty() {
+            return Ok(());
+        }
+        self.repository.release(&charges).await
+    }
+
+    pub async fn headroom(&self, query: &HeadroomQuery, at: DateTime<Utc>) -> Result<Vec<LimitHeadroom>, RepositoryError> {
+        let mut headroom = Vec::new();
+        for (rule, subject) in self.applicable(&query.sender_id, query.debit_account.as_deref(), query.payment_type) {
+            let mut periods = Vec::new();
+            for (period, period_start, max_amount, max_count) in rule.periods(at) {
+                let key = UsageKey {
+                    rule: rule.name.clone(),
+                    subject: subject.clone(),
+                    period,
+                    period_start,
+                };
+                let used = self.repository.usage(&key).await?;
+                periods.push(PeriodHeadroom {
+                    period,
+                    period_start,
+                    max_amount,
+                    used_amount: used.amount,
+                    remaining_amount: max_amount.map(|max| (max - used.amount).max(Decimal::ZERO)),
+                    max_count,
+                    used_count: used.count,
+                    remaining_count: max_count.map(|max| max.saturating_sub(used.count)),
+                });
+            }
+            headroom.push(LimitHeadroom {
+                rule: rule.name.clone(),
+                scope: rule.scope,
+                subject,
+                currency: rule.currency.clone(),
+                payment_types: rule.payment_types.clone(),
+                single_max: rule.single_max,
+                periods,
+            });
+        }
+        Ok(headroom)
+    }
+}
+
+fn limit_violation(reason_code: ReasonCode, message: String) -> ValidationError {
+    ValidationError::RuleViolation {
+        rule: LIMITS_RULE.to_string(),
+        path: None,
+        reason_code,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::database::limits::InMemoryLimitRepository;
+    use serde_json::json;
+
+    fn request(amount: f64) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pain.001".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: json!({
+                "DbtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } },
+                "CdtTrfTxInf": { "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": amount } } }
+            }),
+            sender_id: "acme".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    fn limits() -> PaymentLimits {
+        PaymentLimits::new(
+            LimitSettings {
+                enabled: true,
+                rules: vec![
+                    LimitRule {
+                        name: "sender_daily".to_string(),
+                        scope: LimitScope::Sender,
+                        subjects: Vec::new(),
+                        payment_types: Vec::new(),
+                        currency: "EUR".to_string(),
+                        single_max: Some(Decimal::from(10_000)),
+                        daily_amount: Some(Decimal::from(15_000)),
+                        monthly_amount: None,
+                        daily_count: None,
+                        monthly_count: None,
+                    },
+                    LimitRule {
+                        name: "account_count".to_string(),
+                        scope: LimitScope::DebitAccount,
+                        subjects: Vec::new(),
+                        payment_types: vec![PaymentType::CreditTransfer],
+                        currency: "EUR".to_string(),
+                        single_max: None,
+                        daily_amount: None,
+                        monthly_amount: None,
+                        daily_count: None,
+                        monthly_count: Some(3),
+                    },
+                ],
+            },
+            Box::new(InMemoryLimitRepository::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn enforces_single_daily_and_count_limits() {
+        let limits = limits();
+        let now = Utc::now();
+
+        assert!(limits.reserve(&request(12_000.0), now).await.is_err());
+        limits.reserve(&request(9_000.0), now).await.unwrap();
+        assert!(limits.reserve(&request(7_000.0), now).await.is_err());
+        limits.reserve(&request(5_000.0), now).await.unwrap();
+
+        let query = HeadroomQuery {
+            sender_id: "acme".to_string(),
+            debit_account: Some("DE89370400440532013000".to_string()),
+            payment_type: Some(PaymentType::CreditTransfer),
+        };
+        let headroom = limits.headroom(&query, now).await.unwrap();
+        assert_eq!(headroom[0].periods[0].remaining_amount, Some(Decimal::from(1_000)));
+        assert_eq!(headroom[1].periods[0].remaining_count, Some(1));
+    }
+
+    #[tokio::test]
+    async fn charges_every_transaction_and_refuses_uncovered_currencies() {
+        let limits = limits();
+        let now = Utc::now();
+        let mut batch = request(0.0);
+        batch.message_payload = json!({
+            "PmtInf": {
+                "DbtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } },
+                "CdtTrfTxInf": [
+                    { "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 8_000.0 } } },
+                    { "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 8_000.0 } } }
+                ]
+            }
+        });
+        assert!(limits.reserve(&batch, now).await.is_err());
+
+        let mut dollars = request(0.0);
+        dollars.message_payload["CdtTrfTxInf"]["Amt"]["InstdAmt"] = json!({ "Ccy": "USD", "Value": 50.0 });
+        assert!(limits.reserve(&dollars, now).await.is_err());
+
+        batch.message_payload["PmtInf"]["CdtTrfTxInf"][1]["Amt"]["InstdAmt"]["Value"] = json!(2_000.0);
+        limits.reserve(&batch, now).await.unwrap();
+        let query = HeadroomQuery {
+            sender_id: "acme".to_string(),
+            debit_account: Some("DE89370400440532013000".to_string()),
+            payment_type: Some(PaymentType::CreditTransfer),
+        };
+        let headroom = limits.headroom(&query, now).await.unwrap();
+        assert_eq!(headroom[0].periods[0].remaining_amount, Some(Decimal::from(5_000)));
+        assert_eq!(headroom[1].periods[0].remaining_count, Some(1));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod approval;
+pub mod dead_letters;
+pub mod limits;
+pub mod payment_service;
+
+pub use payment_service::{PaymentService, PaymentServiceImpl};
@@ -1,70 +1,818 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::domain::payment::{PaymentRequest, PaymentResponse, PaymentStatus};
-use crate::error::ServiceError;
+use crate::domain::event::{replay, PaymentEventKind, PaymentTimeline};
+use crate::domain::payment::{
+    ApprovalOutcome, ApprovalRecord, ApprovalRequest, FraudAssessment, FraudDecision, HeldPaymentResponse,
+    HoldDecision, HoldDecisionRequest, HoldOutcome, Payment, PaymentFlag, PaymentRequest, PaymentResponse,
+    PaymentStatus, PaymentType, PendingApprovalResponse, ReviewAction, ScreeningHit,
+};
+use crate::domain::status_reason::ReasonCode;
+use crate::domain::status_report;
+use crate::domain::uetr;
+use crate::error::{RepositoryError, ServiceError, ValidationError};
 use crate::validation::PaymentValidator;
-use crate::infrastructure::messaging::MessagePublisher;
+use crate::infrastructure::database::events::EventStore;
+use crate::infrastructure::database::outbox::OutboxMessage;
+use crate::infrastructure::database::PaymentRepository;
+use crate::fraud::scorer::assess;
+use crate::fraud::{FraudScorer, FraudSettings};
+use crate::monitoring::TransactionMonitor;
+use crate::screening::SanctionsScreener;
+use crate::service::approval::ApprovalSettings;
+use crate::service::limits::{HeadroomQuery, LimitHeadroom, PaymentLimits};
+
+const SCREENING_RULE: &str = "sanctions_screening";
+const FRAUD_RULE: &str = "fraud_scoring";
+const FOUR_EYES_RULE: &str = "four_eyes";
 
 #[async_trait]
 pub trait PaymentService: Send + Sync {
     async fn process_payment(&self, request: PaymentRequest) -> Result<PaymentResponse, ServiceError>;
     async fn get_status(&self, payment_id: &Uuid) -> Result<PaymentStatus, ServiceError>;
+    async fn get_payment_by_uetr(&self, uetr: &Uuid) -> Result<PaymentResponse, ServiceError>;
+    async fn list_held(&self) -> Result<Vec<HeldPaymentResponse>, ServiceError>;
+    async fn release_payment(
+        &self,
+        payment_id: &Uuid,
+        decided_by: &str,
+        decision: HoldDecisionRequest,
+    ) -> Result<PaymentResponse, ServiceError>;
+    async fn reject_payment(
+        &self,
+        payment_id: &Uuid,
+        decided_by: &str,
+        decision: HoldDecisionRequest,
+    ) -> Result<PaymentResponse, ServiceError>;
+    async fn list_pending_approval(&self) -> Result<Vec<PendingApprovalResponse>, ServiceError>;
+    async fn get_limit_headroom(&self, query: &HeadroomQuery) -> Result<Vec<LimitHeadroom>, ServiceError>;
+    async fn get_approvals(&self, payment_id: &Uuid) -> Result<Vec<ApprovalRecord>, ServiceError>;
+    async fn get_events(&self, payment_id: &Uuid) -> Result<PaymentTimeline, ServiceError>;
+    // Adds a pacs.002 received from the next party to the timeline of every
+    // payment it reports on; returns how many statuses were recorded.
+    async fn record_status_report(&self, report: &Value) -> Result<usize, ServiceError>;
+    async fn approve_payment(
+        &self,
+        payment_id: &Uuid,
+        approver: &str,
+        approval: ApprovalRequest,
+    ) -> Result<PaymentResponse, ServiceError>;
+    async fn reject_approval(
+        &self,
+        payment_id: &Uuid,
+        approver: &str,
+        approval: ApprovalRequest,
+    ) -> Result<PaymentResponse, ServiceError>;
 }
 
 pub struct PaymentServiceImpl {
     validator: Box<dyn PaymentValidator>,
-    message_publisher: Box<dyn MessagePublisher>,
     repository: Box<dyn PaymentRepository>,
+    screener: Option<Box<dyn SanctionsScreener>>,
+    monitor: Option<Arc<dyn TransactionMonitor>>,
+    fraud: Option<(Box<dyn FraudScorer>, FraudSettings)>,
+    approval: ApprovalSettings,
+    limits: Option<PaymentLimits>,
+    events: Option<Arc<dyn EventStore>>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::domain::payment::{PaymentRequest, PaymentType};
-    use crate::error::ServiceError;
-    use async_trait::async_trait;
-    use serde_json::json;
-    use uuid::Uuid;
+impl PaymentServiceImpl {
+    pub fn new(
+        validator: Box<dyn PaymentValidator>,
+        repository: Box<dyn PaymentRepository>,
+    ) -> Self {
+        Self {
+            validator,
+            repository,
+            screener: None,
+            monitor: None,
+            fraud: None,
+            approval: ApprovalSettings::default(),
+            limits: None,
+            events: None,
+        }
+    }
+
+    pub fn with_screener(mut self, screener: Box<dyn SanctionsScreener>) -> Self {
+        self.screener = Some(screener);
+        self
+    }
+
+    // The monitor is shared with the alerts API, hence the Arc.
+    pub fn with_monitor(mut self, monitor: Arc<dyn TransactionMonitor>) -> Self {
+        self.monitor = Some(monitor);
+        self
+    }
+
+    pub fn with_fraud_scorer(mut self, scorer: Box<dyn FraudScorer>, settings: FraudSettings) -> Self {
+        self.fraud = Some((scorer, settings));
+        self
+    }
 
-    struct MockPaymentValidator;
+    pub fn with_approval(mut self, approval: ApprovalSettings) -> Self {
+        self.approval = approval;
+        self
+    }
 
-    #[async_trait]
-    impl PaymentValidator for MockPaymentValidator {
-        async fn validate(&self, _request: &PaymentRequest) -> Result<(), ValidationError> {
-            Ok(())
+    pub fn with_limits(mut self, limits: PaymentLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    pub fn with_events(mut self, events: Arc<dyn EventStore>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    async fn release_limits(&self, payment: &Payment) {
+        if let Some(limits) = &self.limits {
+            if let Err(e) = limits.release(payment).await {
+                error!(payment_id = %payment.id, "Failed to release limit usage: {:?}", e);
+            }
+        }
+    }
+
+    // Only instant payments are scored synchronously: they settle within
+    // seconds, so there is no later point at which to stop them.
+    async fn score_fraud(&self, request: &PaymentRequest) -> Option<FraudAssessment> {
+        match &self.fraud {
+            Some((scorer, settings)) if settings.enabled && request.payment_type == PaymentType::RealTimePayment => {
+                Some(assess(scorer.as_ref(), settings, request).await)
+            }
+            _ => None,
         }
+    }
+
+    // Monitoring runs after the payment is accepted for processing and never
+    // blocks it; alerts are for investigators, not the submitter.
+    async fn monitor(&self, payment: &Payment) {
+        let monitor = match &self.monitor {
+            Some(monitor) => monitor,
+            None => return,
+        };
+
+        match monitor.observe(payment).await {
+            Ok(alerts) => {
+                for alert in alerts {
+                    warn!(
+                        alert_id = %alert.id,
+                        rule = %alert.rule,
+                        party = %alert.party,
+                        transactions = alert.transactions.len(),
+                        "Monitoring alert raised: {}",
+                        alert.reason
+                    );
+                }
+            }
+            Err(e) => error!(payment_id = %payment.id, "Transaction monitoring failed: {:?}", e),
+        }
+    }
+
+    // Screening fails closed: if the lists cannot be consulted, or a name is in
+    // a script they cannot be matched against, the payment is held rather
+    // than released unscreened.
+    async fn screen(&self, request: &PaymentRequest) -> (Vec<ScreeningHit>, Option<PaymentFlag>) {
+        let screener = match &self.screener {
+            Some(screener) => screener,
+            None => return (Vec::new(), None),
+        };
+
+        match screener.screen(request).await {
+            Ok(outcome) => {
+                let mut reasons = Vec::new();
+                if let Some(best) = outcome.hits.first() {
+                    reasons.push(format!(
+                        "{} potential sanctions match(es); best is {} entry {} ({}) on {} with score {:.2}",
+                        outcome.hits.len(),
+                        best.list,
+                        best.entry_id,
+                        best.matched_name,
+                        best.field,
+                        best.score
+                    ));
+                }
+                if !outcome.unscreenable.is_empty() {
+                    let fields: Vec<&str> = outcome.unscreenable.iter().map(|value| value.field.as_str()).collect();
+                    reasons.push(format!(
+                        "could not be screened against the lists (no Latin transliteration): {}",
+                        fields.join(", ")
+                    ));
+                }
+                let flag = (!reasons.is_empty()).then(|| PaymentFlag {
+                    rule: SCREENING_RULE.to_string(),
+                    action: ReviewAction::Hold,
+                    reason: reasons.join("; "),
+                    related_payment_id: None,
+                });
+                (outcome.hits, flag)
+            }
+            Err(e) => {
+                error!("Sanctions screening failed: {:?}", e);
+                let flag = PaymentFlag {
+                    rule: SCREENING_RULE.to_string(),
+                    action: ReviewAction::Hold,
+                    reason: format!("sanctions screening unavailable: {}", e),
+                    related_payment_id: None,
+                };
+                (Vec::new(), Some(flag))
+            }
+        }
+    }
+
+    // The analyst is the authenticated caller.
+    async fn decide_hold(
+        &self,
+        payment_id: &Uuid,
+        decided_by: &str,
+        outcome: HoldOutcome,
+        request: HoldDecisionRequest,
+    ) -> Result<PaymentResponse, ServiceError> {
+        if request.reason.trim().is_empty() {
+            return Err(ValidationError::RuleViolation {
+                rule: "hold_decision".to_string(),
+                path: Some("/reason".to_string()),
+                reason_code: ReasonCode::NARR,
+                message: "reason is required to decide on a held payment".to_string(),
+            }
+            .into());
+        }
+
+        let payment = self
+            .repository
+            .get_payment(payment_id)
+            .await?
+            .ok_or(ServiceError::NotFound(*payment_id))?;
+        if payment.status != PaymentStatus::Held {
+            return Err(ServiceError::InvalidState {
+                payment_id: *payment_id,
+                status: payment.status,
+                expected: PaymentStatus::Held,
+            });
+        }
+
+        let decision = HoldDecision {
+            outcome,
+            decided_by: decided_by.to_string(),
+            reason: request.reason.trim().to_string(),
+            decided_at: Utc::now(),
+        };
+        // A release puts the payment back where an unflagged one would have
+        // been after intake; a rejection is final.
+        let (status, routing_key) = match outcome {
+            HoldOutcome::Released if payment.approval.is_some() => {
+                (PaymentStatus::PendingApproval, "payment.pending_approval")
+            }
+            HoldOutcome::Released => (PaymentStatus::Received, "payment.received"),
+            HoldOutcome::Rejected => (PaymentStatus::Rejected, "payment.rejected"),
+        };
+
+        let mut decided = payment.clone();
+        decided.status = status;
+        decided.updated_at = decision.decided_at;
+        decided.hold_decision = Some(decision.clone());
+        let outbox = vec![OutboxMessage::payment_event(&decided, routing_key)?];
+        let events = vec![
+            PaymentEventKind::HoldDecided {
+                decision: decision.clone(),
+            },
+            PaymentEventKind::StatusChanged {
+                from: Some(payment.status),
+                to: status,
+                actor: decision.decided_by.clone(),
+                reason: Some(decision.reason.clone()),
+            },
+        ];
+        // Another analyst may have decided since the payment was read.
+        if !self
+            .repository
+            .record_hold_decision(payment_id, decision.clone(), status, outbox, events)
+            .await?
+        {
+            let current = self
+                .repository
+                .get_payment(payment_id)
+                .await?
+                .ok_or(ServiceError::NotFound(*payment_id))?;
+            return Err(ServiceError::InvalidState {
+                payment_id: *payment_id,
+                status: current.status,
+                expected: PaymentStatus::Held,
+            });
+        }
+        let payment = decided;
+        if status == PaymentStatus::Rejected {
+            self.release_limits(&payment).await;
+        }
+
+        info!(
+            payment_id = %payment.id,
+            uetr = %payment.uetr,
+            outcome = ?outcome,
+            decided_by = %decided_by,
+            "Hold decision recorded: {}",
+            request.reason
+        );
+
+        Ok(PaymentResponse::from(&payment))
+    }
+
+    // The approver is the authenticated caller. A decision is recorded only
+    // if no other one landed since the payment was read; otherwise it is
+    // made again on the fresh state, so concurrent approvals cannot leave
+    // the payment pending or count one approver twice.
+    async fn decide_approval(
+        &self,
+        payment_id: &Uuid,
+        approver: &str,
+        outcome: ApprovalOutcome,
+        request: ApprovalRequest,
+    ) -> Result<PaymentResponse, ServiceError> {
+        let approver = approver.trim();
+        if approver.is_empty() {
+            return Err(ValidationError::RuleViolation {
+                rule: FOUR_EYES_RULE.to_string(),
+                path: None,
+                reason_code: ReasonCode::CH21,
+                message: "approver is required".to_string(),
+            }
+            .into());
+        }
+        let comment = request.comment.map(|comment| comment.trim().to_string()).filter(|c| !c.is_empty());
+
+        loop {
+            let payment = self
+                .repository
+                .get_payment(payment_id)
+                .await?
+                .ok_or(ServiceError::NotFound(*payment_id))?;
+            if payment.status != PaymentStatus::PendingApproval {
+                return Err(ServiceError::InvalidState {
+                    payment_id: *payment_id,
+                    status: payment.status,
+                    expected: PaymentStatus::PendingApproval,
+                });
+            }
+
+            let is_submitter = payment
+                .request
+                .submitted_by
+                .as_deref()
+                .is_some_and(|submitter| submitter.trim().eq_ignore_ascii_case(approver));
+            let already_decided = payment
+                .approvals
+                .iter()
+                .any(|record| record.approver.eq_ignore_ascii_case(approver));
+            if is_submitter || already_decided {
+                let message = if is_submitter {
+                    format!("{} submitted this payment and cannot also decide on it", approver)
+                } else {
+                    format!("{} has already decided on this payment", approver)
+                };
+                return Err(ServiceError::BusinessRule(ValidationError::RuleViolation {
+                    rule: FOUR_EYES_RULE.to_string(),
+                    path: None,
+                    reason_code: ReasonCode::AG01,
+                    message,
+                }));
+            }
+
+            let record = ApprovalRecord {
+                outcome,
+                approver: approver.to_string(),
+                comment: comment.clone(),
+                decided_at: Utc::now(),
+            };
+            let required = payment.approval.as_ref().map_or(1, |approval| approval.required_approvals);
+            let approvers: HashSet<String> = payment
+                .approvals
+                .iter()
+                .chain(std::iter::once(&record))
+                .filter(|record| record.outcome == ApprovalOutcome::Approved)
+                .map(|record| record.approver.to_lowercase())
+                .collect();
+            let approved = approvers.len();
+            let (status, routing_key) = match outcome {
+                ApprovalOutcome::Rejected => (PaymentStatus::Rejected, Some("payment.rejected")),
+                ApprovalOutcome::Approved if approved >= required => (PaymentStatus::Received, Some("payment.received")),
+                ApprovalOutcome::Approved => (PaymentStatus::PendingApproval, None),
+            };
+
+            let mut decided = payment.clone();
+            decided.status = status;
+            decided.updated_at = record.decided_at;
+            decided.approvals.push(record.clone());
+            let outbox = match routing_key {
+                Some(routing_key) => vec![OutboxMessage::payment_event(&decided, routing_key)?],
+                None => Vec::new(),
+            };
+            let mut events = vec![PaymentEventKind::ApprovalRecorded { record: record.clone() }];
+            if status != payment.status {
+                events.push(PaymentEventKind::StatusChanged {
+                    from: Some(payment.status),
+                    to: status,
+                    actor: record.approver.clone(),
+                    reason: record.comment.clone(),
+                });
+            }
+            let recorded = self
+                .repository
+                .record_approval(payment_id, record.clone(), payment.approvals.len(), status, outbox, events)
+                .await?;
+            if !recorded {
+                continue;
+            }
+            let payment = decided;
+            if status == PaymentStatus::Rejected {
+                self.release_limits(&payment).await;
+            }
+
+            info!(
+                payment_id = %payment.id,
+                uetr = %payment.uetr,
+                outcome = ?outcome,
+                approver = %approver,
+                approvals = approved,
+                required_approvals = required,
+                "Approval decision recorded"
+            );
 
-        async fn validate_business_rules(&self, _request: &PaymentRequest) -> Result<(), ValidationError> {
-            Ok(())
+            return Ok(PaymentResponse::from(&payment));
         }
     }
+}
+
+#[async_trait]
+impl PaymentService for PaymentServiceImpl {
+    async fn process_payment(&self, mut request: PaymentRequest) -> Result<PaymentResponse, ServiceError> {
+        let fingerprint = request.fingerprint();
+        if let Some(existing) = self.repository.find_by_request(&request.sender_id, &request.request_id).await? {
+            return replay_request(&existing, &fingerprint);
+        }
+        // Screening sees the names as submitted: transliteration turns letters
+        // it has no Latin form for into dots, which would hide them from the
+        // unscreenable-script check.
+        let submitted = request.clone();
+        let transliterations = self.validator.normalize(&mut request).await?;
+        self.validator.validate(&request).await?;
+        let mut flags = self
+            .validator
+            .validate_business_rules(&request)
+            .await
+            .map_err(ServiceError::business_rule)?;
+        let (screening_hits, screening_flag) = self.screen(&submitted).await;
+        flags.extend(screening_flag);
+
+        let fraud_assessment = self.score_fraud(&request).await;
+        if let Some(assessment) = &fraud_assessment {
+            let summary = match assessment.score {
+                Some(score) => format!("fraud risk score {:.0}: {}", score, assessment.reasons.join("; ")),
+                None => format!("fraud risk not scored: {}", assessment.reasons.join("; ")),
+            };
+            match assessment.decision {
+                FraudDecision::Accept => {}
+                FraudDecision::Review => flags.push(PaymentFlag {
+                    rule: FRAUD_RULE.to_string(),
+                    action: ReviewAction::Hold,
+                    reason: summary,
+                    related_payment_id: None,
+                }),
+                FraudDecision::Reject => {
+                    return Err(ServiceError::BusinessRule(ValidationError::RuleViolation {
+                        rule: FRAUD_RULE.to_string(),
+                        path: None,
+                        reason_code: ReasonCode::FR01,
+                        message: summary,
+                    }))
+                }
+            }
+        }
 
-    struct MockMessagePublisher;
+        // The payment is known by the UETR the client supplied or the first one
+        // in the message, else a new one. Transactions without a UETR of their
+        // own get one stamped into the payload.
+        let uetr = match (request.uetr, uetr::extract(&request.message_payload)?) {
+            (Some(supplied), Some(embedded)) if supplied != embedded => {
+                return Err(ValidationError::RuleViolation {
+                    rule: "uetr".to_string(),
+                    path: None,
+                    reason_code: ReasonCode::CH16,
+                    message: "uetr does not match the first UETR in the message".to_string(),
+                }
+                .into())
+            }
+            (Some(supplied), _) => {
+                uetr::ensure_v4(&supplied)?;
+                supplied
+            }
+            (None, Some(embedded)) => embedded,
+            (None, None) => uetr::generate(),
+        };
+        uetr::stamp(&mut request.message_payload, &uetr);
+        request.uetr = Some(uetr);
 
-    #[async_trait]
-    impl MessagePublisher for MockMessagePublisher {
-        async fn publish_message(&self, _routing_key: &str, _message: serde_json::Value) -> Result<(), MessagingError> {
-            Ok(())
+        let approval = self.approval.requirement(&request);
+        if approval.is_some()
+            && request
+                .submitted_by
+                .as_deref()
+                .is_none_or(|submitter| submitter.trim().is_empty())
+        {
+            return Err(ValidationError::RuleViolation {
+                rule: FOUR_EYES_RULE.to_string(),
+                path: None,
+                reason_code: ReasonCode::CH21,
+                message: "payments that need approval must be submitted by an authenticated user".to_string(),
+            }
+            .into());
         }
+
+        let mut payment = Payment::new(request, uetr, transliterations, flags);
+        // Compliance holds come first; approval is asked for once released.
+        if approval.is_some() && payment.status == PaymentStatus::Received {
+            payment.status = PaymentStatus::PendingApproval;
+        }
+        payment.approval = approval;
+        payment.fingerprint = Some(fingerprint.clone());
+        payment.screening_hits = screening_hits;
+        payment.fraud_assessment = fraud_assessment;
+        for record in &payment.transliterations {
+            info!(
+                payment_id = %payment.id,
+                uetr = %payment.uetr,
+                path = %record.path,
+                original = %record.original,
+                transliterated = %record.transliterated,
+                "Transliterated payment text"
+            );
+        }
+
+        for flag in &payment.flags {
+            warn!(
+                payment_id = %payment.id,
+                rule = %flag.rule,
+                action = ?flag.action,
+                related_payment_id = ?flag.related_payment_id,
+                "{}",
+                flag.reason
+            );
+        }
+
+        let response = PaymentResponse::from(&payment);
+        let routing_key = match payment.status {
+            PaymentStatus::Held => "payment.held",
+            PaymentStatus::PendingApproval => "payment.pending_approval",
+            _ => "payment.received",
+        };
+        if let Some(limits) = &self.limits {
+            limits.reserve(&payment.request, payment.created_at).await?;
+        }
+        let outbox = vec![OutboxMessage::payment_event(&payment, routing_key)?];
+        let events = intake_events(&payment, self.screener.is_some());
+        if let Err(e) = self.repository.save_payment(payment.clone(), outbox, events).await {
+            self.release_limits(&payment).await;
+            // A concurrent submission of the same request id got there first.
+            if let RepositoryError::Constraint { .. } = e {
+                let request = &payment.request;
+                if let Some(existing) = self.repository.find_by_request(&request.sender_id, &request.request_id).await? {
+                    return replay_request(&existing, &fingerprint);
+                }
+            }
+            return Err(e.into());
+        }
+        // The payment is committed; a later duplicate check missing it is
+        // not worth failing a request the client would then retry.
+        if let Err(e) = self.validator.record_processed(&payment).await {
+            error!(payment_id = %payment.id, "Failed to record payment for duplicate checks: {:?}", e);
+        }
+        if let Some((scorer, _)) = &self.fraud {
+            if let Err(e) = scorer.record(&payment).await {
+                error!(payment_id = %payment.id, "Failed to update fraud profile: {:?}", e);
+            }
+        }
+        self.monitor(&payment).await;
+
+        Ok(response)
     }
 
-    struct MockPaymentRepository;
+    async fn get_status(&self, payment_id: &Uuid) -> Result<PaymentStatus, ServiceError> {
+        self.repository
+            .get_payment(payment_id)
+            .await?
+            .map(|payment| payment.status)
+            .ok_or(ServiceError::NotFound(*payment_id))
+    }
 
-    #[async_trait]
-    impl PaymentRepository for MockPaymentRepository {
-        async fn save_payment(&self, _payment: Payment) -> Result<(), RepositoryError> {
-            Ok(())
+    async fn get_payment_by_uetr(&self, uetr: &Uuid) -> Result<PaymentResponse, ServiceError> {
+        self.repository
+            .get_payment_by_uetr(uetr)
+            .await?
+            .map(|payment| PaymentResponse::from(&payment))
+            .ok_or(ServiceError::NotFound(*uetr))
+    }
+
+    async fn list_held(&self) -> Result<Vec<HeldPaymentResponse>, ServiceError> {
+        let mut held = self.repository.list_by_status(PaymentStatus::Held).await?;
+        held.sort_by_key(|payment| payment.created_at);
+        Ok(held.iter().map(HeldPaymentResponse::from).collect())
+    }
+
+    async fn release_payment(
+        &self,
+        payment_id: &Uuid,
+        decided_by: &str,
+        decision: HoldDecisionRequest,
+    ) -> Result<PaymentResponse, ServiceError> {
+        self.decide_hold(payment_id, decided_by, HoldOutcome::Released, decision).await
+    }
+
+    async fn reject_payment(
+        &self,
+        payment_id: &Uuid,
+        decided_by: &str,
+        decision: HoldDecisionRequest,
+    ) -> Result<PaymentResponse, ServiceError> {
+        self.decide_hold(payment_id, decided_by, HoldOutcome::Rejected, decision).await
+    }
+
+    async fn list_pending_approval(&self) -> Result<Vec<PendingApprovalResponse>, ServiceError> {
+        let mut pending = self.repository.list_by_status(PaymentStatus::PendingApproval).await?;
+        pending.sort_by_key(|payment| payment.created_at);
+        Ok(pending.iter().map(PendingApprovalResponse::from).collect())
+    }
+
+    async fn get_limit_headroom(&self, query: &HeadroomQuery) -> Result<Vec<LimitHeadroom>, ServiceError> {
+        match &self.limits {
+            Some(limits) => Ok(limits.headroom(query, Utc::now()).await?),
+            None => Ok(Vec::new()),
         }
+    }
+
+    async fn get_approvals(&self, payment_id: &Uuid) -> Result<Vec<ApprovalRecord>, ServiceError> {
+        self.repository
+            .get_payment(payment_id)
+            .await?
+            .map(|payment| payment.approvals)
+            .ok_or(ServiceError::NotFound(*payment_id))
+    }
+
+    async fn get_events(&self, payment_id: &Uuid) -> Result<PaymentTimeline, ServiceError> {
+        let events = match &self.events {
+            Some(store) => store.events(payment_id).await?,
+            None => Vec::new(),
+        };
+        // Payments stored before the event store existed have no timeline;
+        // their status still comes from the payment record.
+        let status = match replay(&events) {
+            Some(payment) => payment.status,
+            None => self
+                .repository
+                .get_payment(payment_id)
+                .await?
+                .ok_or(ServiceError::NotFound(*payment_id))?
+                .status,
+        };
+        Ok(PaymentTimeline {
+            payment_id: *payment_id,
+            status,
+            events,
+        })
+    }
+
+    // Statuses are matched to payments by UETR. A report for a payment this
+    // service does not know is logged and skipped, and a redelivered report
+    // is not recorded twice.
+    async fn record_status_report(&self, report: &Value) -> Result<usize, ServiceError> {
+        let store = match &self.events {
+            Some(store) => store,
+            None => {
+                warn!("No event store configured; status report not recorded");
+                return Ok(0);
+            }
+        };
 
-        async fn get_payment(&self, _id: &Uuid) -> Result<Option<Payment>, RepositoryError> {
-            Ok(None)
+        let mut reported: Vec<(Uuid, Vec<PaymentEventKind>)> = Vec::new();
+        for (reported_uetr, event) in status_report::received(report) {
+            let payment = match reported_uetr {
+                Some(reported_uetr) => self.repository.get_payment_by_uetr(&reported_uetr).await?,
+                None => None,
+            };
+            let payment = match payment {
+                Some(payment) => payment,
+                None => {
+                    warn!(uetr = ?reported_uetr, "Status report for a payment that is not known here");
+                    continue;
+                }
+            };
+            match reported.iter_mut().find(|(id, _)| *id == payment.id) {
+                Some((_, events)) => events.push(event),
+                None => reported.push((payment.id, vec![event])),
+            }
         }
 
-        async fn update_status(&self, _id: &Uuid, _status: PaymentStatus) -> Result<(), RepositoryError> {
-            Ok(())
+        let mut recorded = 0;
+        for (payment_id, events) in reported {
+            let message_id = match events.first() {
+                Some(PaymentEventKind::StatusReportReceived { message_id, .. }) => message_id.clone(),
+                _ => None,
+            };
+            let already = match &message_id {
+                Some(message_id) => store.events(&payment_id).await?.iter().any(|event| {
+                    matches!(
+                        &event.kind,
+                        PaymentEventKind::StatusReportReceived { message_id: Some(known), .. } if known == message_id
+                    )
+                }),
+                None => false,
+            };
+            if already {
+                continue;
+            }
+            recorded += events.len();
+            store.append(&payment_id, Utc::now(), events).await?;
         }
+        info!(recorded, "Recorded status report");
+        Ok(recorded)
     }
 
-    // Remove the test functions
+    async fn approve_payment(
+        &self,
+        payment_id: &Uuid,
+        approver: &str,
+        approval: ApprovalRequest,
+    ) -> Result<PaymentResponse, ServiceError> {
+        self.decide_approval(payment_id, approver, ApprovalOutcome::Approved, approval).await
+    }
+
+    async fn reject_approval(
+        &self,
+        payment_id: &Uuid,
+        approver: &str,
+        approval: ApprovalRequest,
+    ) -> Result<PaymentResponse, ServiceError> {
+        self.decide_approval(payment_id, approver, ApprovalOutcome::Rejected, approval).await
+    }
+}
+
+// The sender already used this request id. Sending the same request again
+// gets the stored outcome, so retries and redeliveries are safe; anything
+// else under that id is refused.
+fn replay_request(existing: &Payment, fingerprint: &str) -> Result<PaymentResponse, ServiceError> {
+    if existing.fingerprint.as_deref() != Some(fingerprint) {
+        return Err(ServiceError::BusinessRule(ValidationError::DuplicatePayment {
+            original_payment_id: existing.id,
+            reason: format!(
+                "request_id {} was already used for a different payment",
+                existing.request.request_id
+            ),
+        }));
+    }
+    info!(
+        payment_id = %existing.id,
+        sender_id = %existing.request.sender_id,
+        request_id = %existing.request.request_id,
+        "Replaying stored outcome for repeated request"
+    );
+    Ok(PaymentResponse::from(existing))
+}
+
+// What intake established about a new payment, in the order it was decided.
+fn intake_events(payment: &Payment, screened: bool) -> Vec<PaymentEventKind> {
+    let mut events = vec![
+        PaymentEventKind::Received {
+            uetr: payment.uetr,
+            request: payment.request.clone(),
+        },
+        PaymentEventKind::Validated {
+            flags: payment.flags.clone(),
+            transliterations: payment.transliterations.clone(),
+        },
+    ];
+    if screened {
+        events.push(PaymentEventKind::Screened {
+            hits: payment.screening_hits.clone(),
+        });
+    }
+    if let Some(assessment) = &payment.fraud_assessment {
+        events.push(PaymentEventKind::FraudAssessed {
+            assessment: assessment.clone(),
+        });
+    }
+    if let Some(requirement) = &payment.approval {
+        events.push(PaymentEventKind::ApprovalRequested {
+            requirement: requirement.clone(),
+        });
+    }
+    events.push(PaymentEventKind::StatusChanged {
+        from: None,
+        to: payment.status,
+        actor: payment.request.submitter().to_string(),
+        reason: None,
+    });
+    events
 }
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::domain::payment::TransliterationRecord;
+use crate::error::ValidationError;
+
+const SEPA_SPECIALS: &str = "/-?:().,'+ ";
+const CBPR_PLUS_SPECIALS: &str = "/-?:().,'+ !#$%&*=^_`{|}~\";<>@[\\]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharacterSet {
+    SepaBasicLatin,
+    CbprPlus,
+    Unrestricted,
+}
+
+impl CharacterSet {
+    pub fn allows(&self, c: char) -> bool {
+        match self {
+            CharacterSet::SepaBasicLatin => c.is_ascii_alphanumeric() || SEPA_SPECIALS.contains(c),
+            CharacterSet::CbprPlus => c.is_ascii_alphanumeric() || CBPR_PLUS_SPECIALS.contains(c),
+            CharacterSet::Unrestricted => !c.is_control(),
+        }
+    }
+
+    // Substitutions recommended by the EPC for characters that have no
+    // Latin equivalent; everything else collapses to a full stop.
+    fn replacement(&self, c: char) -> &'static str {
+        match (self, c) {
+            (CharacterSet::SepaBasicLatin, '&') => "+",
+            (CharacterSet::SepaBasicLatin, '_') => "-",
+            (_, '\n') | (_, '\r') | (_, '\t') => " ",
+            _ => ".",
+        }
+    }
+
+    pub fn transliterate(&self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        for c in input.chars() {
+            if self.allows(c) {
+                output.push(c);
+                continue;
+            }
+            match transliterate_char(c) {
+                Some(mapped) if mapped.chars().all(|m| self.allows(m)) => output.push_str(mapped),
+                _ => output.push_str(self.replacement(c)),
+            }
+        }
+        output
+    }
+
+    // Returns the 1-based character position and the offending character.
+    pub fn first_invalid(&self, input: &str) -> Option<(usize, char)> {
+        input
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !self.allows(*c))
+            .map(|(index, c)| (index + 1, c))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharsetAction {
+    Reject,
+    Transliterate,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CharsetPolicy {
+    pub charset: CharacterSet,
+    pub action: CharsetAction,
+}
+
+impl Default for CharsetPolicy {
+    fn default() -> Self {
+        Self {
+            charset: CharacterSet::Unrestricted,
+            action: CharsetAction::Reject,
+        }
+    }
+}
+
+impl CharsetPolicy {
+    pub fn check(&self, payload: &Value) -> Result<(), ValidationError> {
+        if self.action == CharsetAction::Transliterate {
+            return Ok(());
+        }
+        let mut result = Ok(());
+        walk_strings(payload, "", &mut |path, text| {
+            if result.is_ok() {
+                if let Some((position, character)) = self.charset.first_invalid(text) {
+                    result = Err(ValidationError::InvalidCharacter {
+                        path: path.to_string(),
+                        position,
+                        character,
+                    });
+                }
+            }
+        });
+        result
+    }
+
+    pub fn apply(&self, payload: &mut Value) -> Result<Vec<TransliterationRecord>, ValidationError> {
+        match self.action {
+            CharsetAction::Reject => self.check(payload).map(|_| Vec::new()),
+            CharsetAction::Transliterate => {
+                let mut records = Vec::new();
+                transliterate_strings(payload, String::new(), self.charset, &mut records);
+                Ok(records)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CharsetPolicies {
+    #[serde(default)]
+    pub default: CharsetPolicy,
+    #[serde(default)]
+    pub channels: HashMap<String, CharsetPolicy>,
+}
+
+impl CharsetPolicies {
+    pub fn for_channel(&self, channel: Option<&str>) -> &CharsetPolicy {
+        channel
+            .and_then(|name| self.channels.get(name))
+            .unwrap_or(&self.default)
+    }
+}
+
+fn pointer_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn walk_strings(value: &Value, path: &str, visit: &mut dyn FnMut(&str, &str)) {
+    match value {
+        Value::String(text) => visit(path, text),
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                walk_strings(item, &format!("{}/{}", path, index), visit);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, item) in fields {
+                walk_strings(item, &format!("{}/{}", path, pointer_segment(key)), visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn transliterate_strings(
+    value: &mut Value,
+    path: String,
+    charset: CharacterSet,
+    records: &mut Vec<TransliterationRecord>,
+) {
+    match value {
+        Value::String(text) => {
+            let converted = charset.transliterate(text);
+            if converted != *text {
+                records.push(TransliterationRecord {
+                    path,
+                    original: std::mem::replace(text, converted.clone()),
+                    transliterated: converted,
+                });
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                transliterate_strings(item, format!("{}/{}", path, index), charset, records);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, item) in fields.iter_mut() {
+                transliterate_strings(item, format!("{}/{}", path, pointer_segment(key)), charset, records);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn transliterate_char(c: char) -> Option<&'static str> {
+    let mapped = match c {
+        // Latin-1 and Latin Extended-A
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à' | 'á' | 'â' | 'ã' | 'ā' | 'ă' | 'ą' => "a",
+        'Ä' => "Ae",
+        'ä' => "ae",
+        'Å' => "Aa",
+        'å' => "aa",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ď' | 'Đ' | 'Ð' => "D",
+        'ď' | 'đ' | 'ð' => "d",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Ö' | 'Ø' => "Oe",
+        'ö' | 'ø' => "oe",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' | 'Ș' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' | 'Ț' => "T",
+        'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+        'Þ' => "Th",
+        'þ' => "th",
+        'Ù' | 'Ú' | 'Û' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù' | 'ú' | 'û' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ü' => "Ue",
+        'ü' => "ue",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ÿ' | 'Ŷ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        // Greek (ELOT 743)
+        'Α' | 'Ά' => "A",
+        'α' | 'ά' => "a",
+        'Β' => "V",
+        'β' => "v",
+        'Γ' => "G",
+        'γ' => "g",
+        'Δ' => "D",
+        'δ' => "d",
+        'Ε' | 'Έ' => "E",
+        'ε' | 'έ' => "e",
+        'Ζ' => "Z",
+        'ζ' => "z",
+        'Η' | 'Ή' => "I",
+        'η' | 'ή' => "i",
+        'Θ' => "Th",
+        'θ' => "th",
+        'Ι' | 'Ί' | 'Ϊ' => "I",
+        'ι' | 'ί' | 'ϊ' | 'ΐ' => "i",
+        'Κ' => "K",
+        'κ' => "k",
+        'Λ' => "L",
+        'λ' => "l",
+        'Μ' => "M",
+        'μ' => "m",
+        'Ν' => "N",
+        'ν' => "n",
+        'Ξ' => "X",
+        'ξ' => "x",
+        'Ο' | 'Ό' => "O",
+        'ο' | 'ό' => "o",
+        'Π' => "P",
+        'π' => "p",
+        'Ρ' => "R",
+        'ρ' => "r",
+        'Σ' => "S",
+        'σ' | 'ς' => "s",
+        'Τ' => "T",
+        'τ' => "t",
+        'Υ' | 'Ύ' | 'Ϋ' => "Y",
+        'υ' | 'ύ' | 'ϋ' | 'ΰ' => "y",
+        'Φ' => "F",
+        'φ' => "f",
+        'Χ' => "Ch",
+        'χ' => "ch",
+        'Ψ' => "Ps",
+        'ψ' => "ps",
+        'Ω' | 'Ώ' => "O",
+        'ω' | 'ώ' => "o",
+        // Cyrillic (ISO 9 / ICAO simplified)
+        'А' => "A",
+        'а' => "a",
+        'Б' => "B",
+        'б' => "b",
+        'В' => "V",
+        'в' => "v",
+        'Г' => "G",
+        'г' => "g",
+        'Д' => "D",
+        'д' => "d",
+        'Е' | 'Ё' | 'Э' => "E",
+        'е' | 'ё' | 'э' => "e",
+        'Ж' => "Zh",
+        'ж' => "zh",
+        'З' => "Z",
+        'з' => "z",
+        'И' | 'Й' | 'І' => "I",
+        'и' | 'й' | 'і' => "i",
+        'К' => "K",
+        'к' => "k",
+        'Л' => "L",
+        'л' => "l",
+        'М' => "M",
+        'м' => "m",
+        'Н' => "N",
+        'н' => "n",
+        'О' => "O",
+        'о' => "o",
+        'П' => "P",
+        'п' => "p",
+        'Р' => "R",
+        'р' => "r",
+        'С' => "S",
+        'с' => "s",
+        'Т' => "T",
+        'т' => "t",
+        'У' => "U",
+        'у' => "u",
+        'Ф' => "F",
+        'ф' => "f",
+        'Х' => "Kh",
+        'х' => "kh",
+        'Ц' => "Ts",
+        'ц' => "ts",
+        'Ч' => "Ch",
+        'ч' => "ch",
+        'Ш' => "Sh",
+        'ш' => "sh",
+        'Щ' => "Shch",
+        'щ' => "shch",
+        'Ы' => "Y",
+        'ы' => "y",
+        'Ю' => "Iu",
+        'ю' => "iu",
+        'Я' => "Ia",
+        'я' => "ia",
+        'Ъ' | 'ъ' | 'Ь' | 'ь' => "",
+        // Typography
+        '‘' | '’' | '‚' | '`' | '´' => "'",
+        '“' | '”' | '„' => "\"",
+        '–' | '—' => "-",
+        '\u{a0}' => " ",
+        _ => return None,
+    };
+    Some(mapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sepa_transliteration_is_deterministic() {
+        let charset = CharacterSet::SepaBasicLatin;
+        assert_eq!(charset.transliterate("Müller & Søn"), "Mueller + Soen");
+        assert_eq!(charset.transliterate("Иван Петров"), "Ivan Petrov");
+        assert_eq!(charset.transliterate("李小龙"), "...");
+    }
+
+    #[test]
+    fn reject_policy_reports_path_and_position() {
+        let policy = CharsetPolicy {
+            charset: CharacterSet::SepaBasicLatin,
+            action: CharsetAction::Reject,
+        };
+        let payload = json!({ "Cdtr": { "Nm": "José" } });
+
+        match policy.check(&payload) {
+            Err(ValidationError::InvalidCharacter { path, position, character }) => {
+                assert_eq!(path, "/Cdtr/Nm");
+                assert_eq!(position, 4);
+                assert_eq!(character, 'é');
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn transliterate_policy_records_original_text() {
+        let policy = CharsetPolicy {
+            charset: CharacterSet::SepaBasicLatin,
+            action: CharsetAction::Transliterate,
+        };
+        let mut payload = json!({ "Dbtr": { "Nm": "Zoë" }, "Amt": 10 });
+
+        let records = policy.apply(&mut payload).unwrap();
+
+        assert_eq!(payload["Dbtr"]["Nm"], "Zoe");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].path, "/Dbtr/Nm");
+        assert_eq!(records[0].original, "Zoë");
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::domain::payment::{Payment, PaymentFlag, PaymentRequest, ReviewAction};
+use crate::domain::payment_details::PaymentDetails;
+use crate::error::{RepositoryError, ValidationError};
+
+pub const DUPLICATE_RULE: &str = "duplicate_suspicion";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKey {
+    SenderId,
+    PaymentType,
+    DebtorName,
+    DebtorAccount,
+    CreditorName,
+    CreditorAccount,
+    Amount,
+    Currency,
+    Reference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateAction {
+    Reject,
+    Hold,
+    Flag,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DuplicateRule {
+    pub name: String,
+    pub keys: Vec<MatchKey>,
+    pub window_minutes: i64,
+    pub action: DuplicateAction,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DuplicateSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub rules: Vec<DuplicateRule>,
+}
+
+#[async_trait]
+pub trait DuplicateRegistry: Send + Sync {
+    async fn find_since(&self, fingerprint: &str, since: DateTime<Utc>) -> Result<Option<Uuid>, RepositoryError>;
+    async fn record(&self, fingerprint: &str, payment_id: Uuid, at: DateTime<Utc>) -> Result<(), RepositoryError>;
+}
+
+// Payments seen under one fingerprint, with when each was recorded.
+type Sightings = Vec<(Uuid, DateTime<Utc>)>;
+
+pub struct InMemoryDuplicateRegistry {
+    retention: Duration,
+    entries: Mutex<HashMap<String, Sightings>>,
+}
+
+impl InMemoryDuplicateRegistry {
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl DuplicateRegistry for InMemoryDuplicateRegistry {
+    async fn find_since(&self, fingerprint: &str, since: DateTime<Utc>) -> Result<Option<Uuid>, RepositoryError> {
+        let entries = self.entries.lock().await;
+        Ok(entries.get(fingerprint).and_then(|seen| {
+            seen.iter()
+                .filter(|(_, at)| *at >= since)
+                .min_by_key(|(_, at)| *at)
+                .map(|(id, _)| *id)
+        }))
+    }
+
+    async fn record(&self, fingerprint: &str, payment_id: Uuid, at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        let cutoff = at - self.retention;
+        let mut entries = self.entries.lock().await;
+        let seen = entries.entry(fingerprint.to_string()).or_default();
+        seen.retain(|(_, seen_at)| *seen_at >= cutoff);
+        seen.push((payment_id, at));
+        Ok(())
+    }
+}
+
+pub struct DuplicateDetector {
+    settings: DuplicateSettings,
+    registry: Box<dyn DuplicateRegistry>,
+}
+
+impl DuplicateDetector {
+    pub fn new(settings: DuplicateSettings, registry: Box<dyn DuplicateRegistry>) -> Self {
+        Self { settings, registry }
+    }
+
+    // Rules are evaluated in configuration order; a reject stops processing,
+    // holds and flags are collected so the payment carries every suspicion.
+    pub async fn check(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, ValidationError> {
+        if !self.settings.enabled {
+            return Ok(Vec::new());
+        }
+
+        let details = PaymentDetails::from_payload(&request.message_payload);
+        let now = Utc::now();
+        let mut flags = Vec::new();
+
+        for rule in &self.settings.rules {
+            let fingerprint = match fingerprint(rule, request, &details) {
+                Some(fingerprint) => fingerprint,
+                None => continue,
+            };
+            let since = now - Duration::minutes(rule.window_minutes);
+            let original = self
+                .registry
+                .find_since(&fingerprint, since)
+                .await
+                .map_err(|source| ValidationError::Unavailable {
+                    check: "duplicate check".to_string(),
+                    source,
+                })?;
+
+            let original = match original {
+                Some(original) => original,
+                None => continue,
+            };
+            let reason = format!(
+                "suspected duplicate of payment {} (rule {}, {} minute window)",
+                original, rule.name, rule.window_minutes
+            );
+
+            match rule.action {
+                DuplicateAction::Reject => {
+                    return Err(ValidationError::DuplicatePayment {
+                        original_payment_id: original,
+                        reason,
+                    })
+                }
+                DuplicateAction::Hold | DuplicateAction::Flag => flags.push(PaymentFlag {
+                    rule: DUPLICATE_RULE.to_string(),
+                    action: if rule.action == DuplicateAction::Hold {
+                        ReviewAction::Hold
+                    } else {
+                        ReviewAction::Flag
+                    },
+                    reason,
+                    related_payment_id: Some(original),
+                }),
+            }
+        }
+
+        Ok(flags)
+    }
+
+    pub async fn record(&self, payment: &Payment) -> Result<(), RepositoryError> {
+        if !self.settings.enabled {
+            return Ok(());
+        }
+
+        let details = PaymentDetails::from_payload(&payment.request.message_payload);
+        for rule in &self.settings.rules {
+            if let Some(fingerprint) = fingerprint(rule, &payment.request, &details) {
+                self.registry.record(&fingerprint, payment.id, payment.created_at).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fingerprint(rule: &DuplicateRule, request: &PaymentRequest, details: &PaymentDetails) -> Option<String> {
+    let mut parts = vec![rule.name.clone()];
+    for key in &rule.keys {
+        let value = match key {
+            MatchKey::SenderId => Some(request.sender_id.clone()),
+            MatchKey::PaymentType => Some(format!("{:?}", request.payment_type)),
+            MatchKey::DebtorName => details.debtor_name.as_deref().map(normalize),
+            MatchKey::DebtorAccount => details.debtor_account.as_deref().map(normalize),
+            MatchKey::CreditorName => details.creditor_name.as_deref().map(normalize),
+            MatchKey::CreditorAccount => details.creditor_account.as_deref().map(normalize),
+            MatchKey::Amount => details.amount.map(|amount| format!("{:.2}", amount)),
+            MatchKey::Currency => details.currency.as_deref().map(normalize),
+            MatchKey::Reference => details.reference().map(normalize),
+        };
+        // A rule only applies when every key it matches on is present.
+        parts.push(value?);
+    }
+    Some(parts.join("|"))
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::PaymentType;
+    use crate::error::ServiceError;
+    use serde_json::json;
+
+    fn request(request_id: &str) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: json!({
+                "CdtTrfTxInf": {
+                    "PmtId": { "EndToEndId": "INV-1" },
+                    "IntrBkSttlmAmt": { "Ccy": "EUR", "value": 100.0 },
+                    "DbtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } },
+                    "CdtrAcct": { "Id": { "IBAN": "FR1420041010050500013M02606" } }
+                }
+            }),
+            sender_id: "sender".to_string(),
+            request_id: request_id.to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    fn detector(action: DuplicateAction) -> DuplicateDetector {
+        DuplicateDetector::new(
+            DuplicateSettings {
+                enabled: true,
+                rules: vec![DuplicateRule {
+                    name: "same-parties-amount-reference".to_string(),
+                    keys: vec![
+                        MatchKey::DebtorAccount,
+                        MatchKey::CreditorAccount,
+                        MatchKey::Amount,
+                        MatchKey::Reference,
+                    ],
+                    window_minutes: 30,
+                    action,
+                }],
+            },
+            Box::new(InMemoryDuplicateRegistry::new(Duration::days(1))),
+        )
+    }
+
+    #[tokio::test]
+    async fn flags_repeat_with_different_request_id() {
+        let detector = detector(DuplicateAction::Hold);
+        let original = Payment::new(request("r1"), Uuid::new_v4(), Vec::new(), Vec::new());
+        detector.record(&original).await.unwrap();
+
+        let flags = detector.check(&request("r2")).await.unwrap();
+
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].action, ReviewAction::Hold);
+        assert_eq!(flags[0].related_payment_id, Some(original.id));
+    }
+
+    #[tokio::test]
+    async fn rejects_when_configured() {
+        let detector = detector(DuplicateAction::Reject);
+        let original = Payment::new(request("r1"), Uuid::new_v4(), Vec::new(), Vec::new());
+        detector.record(&original).await.unwrap();
+
+        assert!(matches!(
+            detector.check(&request("r2")).await,
+            Err(ValidationError::DuplicatePayment { .. })
+        ));
+    }
+
+    struct UnavailableRegistry;
+
+    #[async_trait]
+    impl DuplicateRegistry for UnavailableRegistry {
+        async fn find_since(&self, _fingerprint: &str, _since: DateTime<Utc>) -> Result<Option<Uuid>, RepositoryError> {
+            Err(RepositoryError::Database("connection refused".to_string()))
+        }
+
+        async fn record(&self, _fingerprint: &str, _payment_id: Uuid, _at: DateTime<Utc>) -> Result<(), RepositoryError> {
+            Err(RepositoryError::Database("connection refused".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn registry_outages_are_not_rejections() {
+        let settings = detector(DuplicateAction::Reject).settings;
+        let detector = DuplicateDetector::new(settings, Box::new(UnavailableRegistry));
+
+        let error = detector.check(&request("r1")).await.unwrap_err();
+        assert!(matches!(error, ValidationError::Unavailable { .. }));
+        assert!(matches!(
+            ServiceError::business_rule(error),
+            ServiceError::Repository(RepositoryError::Database(_))
+        ));
+    }
+}
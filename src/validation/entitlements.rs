@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::domain::entitlement::normalize_account;
+use crate::domain::payment::{PaymentFlag, PaymentRequest, PaymentType};
+use crate::domain::payment_details::transactions;
+use crate::domain::status_reason::ReasonCode;
+use crate::error::ValidationError;
+use crate::infrastructure::database::entitlements::EntitlementRepository;
+use crate::validation::pipeline::{RuleStage, ValidationRule};
+
+const ENTITLEMENT_RULE: &str = "account_entitlement";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EntitlementSettings {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+pub struct EntitlementRule {
+    repository: Arc<dyn EntitlementRepository>,
+}
+
+impl EntitlementRule {
+    pub fn new(repository: Arc<dyn EntitlementRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl ValidationRule for EntitlementRule {
+    fn name(&self) -> &str {
+        ENTITLEMENT_RULE
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Business
+    }
+
+    // The accounts checked are the ones on the sender's side of the payment:
+    // the debtor account of every transaction, or the creditor account for
+    // direct debits where the sender collects.
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        let element = match request.payment_type {
+            PaymentType::DirectDebit => "CdtrAcct",
+            _ => "DbtrAcct",
+        };
+
+        let mut errors = Vec::new();
+        let mut checked = HashSet::new();
+        for transaction in transactions(&request.message_payload) {
+            let pointer = transaction
+                .locate_all(element)
+                .into_iter()
+                .next()
+                .map_or_else(|| format!("{}/{}", transaction.path, element), |(path, _)| path);
+            let account = match request.payment_type {
+                PaymentType::DirectDebit => transaction.details.creditor_account,
+                _ => transaction.details.debtor_account,
+            };
+            let account = match account {
+                Some(account) => normalize_account(&account),
+                None => {
+                    errors.push(violation(
+                        pointer,
+                        ReasonCode::CH21,
+                        "the account to be checked against the sender's entitlements is missing".to_string(),
+                    ));
+                    continue;
+                }
+            };
+            if !checked.insert(account.clone()) {
+                continue;
+            }
+
+            let entitlement = self
+                .repository
+                .find(&request.sender_id, &account)
+                .await
+                .map_err(|e| vec![ValidationError::BusinessRule(format!("failed to load account entitlements: {}", e))])?;
+            match entitlement {
+                None => errors.push(violation(
+                    pointer,
+                    ReasonCode::AG01,
+                    format!("sender {} is not entitled to use account {}", request.sender_id, account),
+                )),
+                Some(entitlement) if !entitlement.permits(request.payment_type) => errors.push(violation(
+                    pointer,
+                    ReasonCode::AG03,
+                    format!(
+                        "sender {} may not use account {} for {:?} payments",
+                        request.sender_id, account, request.payment_type
+                    ),
+                )),
+                Some(_) => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Vec::new())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn violation(pointer: String, reason_code: ReasonCode, message: String) -> ValidationError {
+    ValidationError::RuleViolation {
+        rule: ENTITLEMENT_RULE.to_string(),
+        path: Some(pointer),
+        reason_code,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entitlement::AccountEntitlement;
+    use crate::infrastructure::database::entitlements::InMemoryEntitlementRepository;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn request(payment_type: PaymentType, debtor_iban: &str) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pain.001".to_string(),
+            payment_type,
+            message_payload: json!({ "DbtrAcct": { "Id": { "IBAN": debtor_iban } } }),
+            sender_id: "acme".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn enforces_account_and_payment_type_entitlements() {
+        let repository = Arc::new(InMemoryEntitlementRepository::new());
+        repository
+            .upsert(AccountEntitlement {
+                sender_id: "acme".to_string(),
+                account: "DE89370400440532013000".to_string(),
+                payment_types: vec![PaymentType::CreditTransfer],
+                granted_by: "admin".to_string(),
+                updated_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        let rule = EntitlementRule::new(repository);
+
+        rule.evaluate(&request(PaymentType::CreditTransfer, "de89 3704 0044 0532 0130 00"))
+            .await
+            .unwrap();
+        assert!(rule.evaluate(&request(PaymentType::RealTimePayment, "DE89370400440532013000")).await.is_err());
+        assert!(rule.evaluate(&request(PaymentType::CreditTransfer, "FR1420041010050500013M02606")).await.is_err());
+
+        let mut batch = request(PaymentType::CreditTransfer, "DE89370400440532013000");
+        batch.message_payload = json!({
+            "PmtInf": [
+                { "DbtrAcct": { "Id": { "IBAN": "DE89370400440532013000" } }, "CdtTrfTxInf": [{}] },
+                { "DbtrAcct": { "Id": { "IBAN": "FR1420041010050500013M02606" } }, "CdtTrfTxInf": [{}] }
+            ]
+        });
+        match rule.evaluate(&batch).await {
+            Err(errors) => {
+                assert_eq!(errors.len(), 1);
+                assert!(matches!(
+                    &errors[0],
+                    ValidationError::RuleViolation { path: Some(path), .. } if path == "/PmtInf/1/DbtrAcct"
+                ));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
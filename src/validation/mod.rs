@@ -0,0 +1,8 @@
+pub mod charset;
+pub mod duplicate;
+pub mod entitlements;
+pub mod payment_validator;
+pub mod pipeline;
+pub mod rules;
+
+pub use payment_validator::{ISO20022PaymentValidator, PaymentValidator};
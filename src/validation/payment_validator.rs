@@ -1,36 +1,112 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use jsonschema::JSONSchema;
-use serde_json::Value;
 
-use crate::domain::payment::PaymentRequest;
-use crate::error::ValidationError;
+use crate::config::ValidationSettings;
+use crate::domain::payment::{Payment, PaymentFlag, PaymentRequest, TransliterationRecord};
+use crate::error::{RepositoryError, ValidationError};
+use crate::validation::charset::CharsetPolicies;
+use crate::validation::duplicate::{DuplicateDetector, DuplicateRegistry};
+use crate::infrastructure::database::entitlements::EntitlementRepository;
+use crate::validation::pipeline::{RuleConfig, RuleKind, RuleStage, ValidationPipeline};
 
 #[async_trait]
 pub trait PaymentValidator: Send + Sync {
     async fn validate(&self, request: &PaymentRequest) -> Result<(), ValidationError>;
-    async fn validate_business_rules(&self, request: &PaymentRequest) -> Result<(), ValidationError>;
+    async fn validate_business_rules(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, ValidationError>;
+
+    async fn normalize(&self, _request: &mut PaymentRequest) -> Result<Vec<TransliterationRecord>, ValidationError> {
+        Ok(Vec::new())
+    }
+
+    // Remembers a stored payment for later checks, such as duplicates.
+    async fn record_processed(&self, _payment: &Payment) -> Result<(), RepositoryError> {
+        Ok(())
+    }
 }
 
 pub struct ISO20022PaymentValidator {
-    schemas: HashMap<String, JSONSchema>,
+    pipeline: ValidationPipeline,
+    charset_policies: Arc<CharsetPolicies>,
+    duplicates: Arc<DuplicateDetector>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::domain::payment::{PaymentRequest, PaymentType};
-    use serde_json::json;
-
-    struct MockISO20022PaymentValidator;
+impl ISO20022PaymentValidator {
+    pub fn new(
+        pipeline: ValidationPipeline,
+        charset_policies: Arc<CharsetPolicies>,
+        duplicates: Arc<DuplicateDetector>,
+    ) -> Self {
+        Self {
+            pipeline,
+            charset_policies,
+            duplicates,
+        }
+    }
 
-    #[async_trait]
-    impl PaymentValidator for MockISO20022PaymentValidator {
-        async fn validate(&self, _request: &PaymentRequest) -> Result<(), ValidationError> {
-            Ok(())
+    pub fn from_settings(
+        settings: &ValidationSettings,
+        schemas: HashMap<String, JSONSchema>,
+        registry: Box<dyn DuplicateRegistry>,
+        entitlements: Arc<dyn EntitlementRepository>,
+    ) -> Result<Self, ValidationError> {
+        let charset_policies = Arc::new(settings.charset.clone());
+        let duplicates = Arc::new(DuplicateDetector::new(settings.duplicates.clone(), registry));
+        // Enabling entitlements adds the rule for every payment unless the
+        // rule list already scopes it to some channels or payment types.
+        let mut rules = settings.rules.clone();
+        if settings.entitlements.enabled && !rules.iter().any(|rule| matches!(rule.kind, RuleKind::Entitlement)) {
+            rules.push(RuleConfig::unscoped(RuleKind::Entitlement));
         }
+        let pipeline = ValidationPipeline::from_config(
+            &rules,
+            Arc::new(schemas),
+            charset_policies.clone(),
+            duplicates.clone(),
+            entitlements,
+        )?;
+        Ok(Self::new(pipeline, charset_policies, duplicates))
+    }
+}
 
-        async fn validate_business_rules(&self, _request: &PaymentRequest) -> Result<(), ValidationError> {
-            Ok(())
+// Compiles the JSON schema configured for each message type.
+pub fn load_schemas(paths: &HashMap<String, PathBuf>) -> Result<HashMap<String, JSONSchema>, ValidationError> {
+    let mut schemas = HashMap::new();
+    for (message_type, path) in paths {
+        let failed = |message: String| {
+            ValidationError::Configuration(format!("schema for {} at {}: {}", message_type, path.display(), message))
+        };
+        let text = std::fs::read_to_string(path).map_err(|e| failed(e.to_string()))?;
+        let schema: serde_json::Value = serde_json::from_str(&text).map_err(|e| failed(e.to_string()))?;
+        let compiled = JSONSchema::compile(&schema).map_err(|e| failed(e.to_string()))?;
+        schemas.insert(message_type.clone(), compiled);
+    }
+    Ok(schemas)
+}
+
+#[async_trait]
+impl PaymentValidator for ISO20022PaymentValidator {
+    async fn validate(&self, request: &PaymentRequest) -> Result<(), ValidationError> {
+        self.pipeline.run(RuleStage::Syntax, request).await.map(|_| ())
+    }
+
+    async fn validate_business_rules(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, ValidationError> {
+        if request.sender_id.trim().is_empty() {
+            return Err(ValidationError::BusinessRule("sender_id must not be empty".to_string()));
         }
+        self.pipeline.run(RuleStage::Business, request).await
+    }
+
+    async fn normalize(&self, request: &mut PaymentRequest) -> Result<Vec<TransliterationRecord>, ValidationError> {
+        self.charset_policies
+            .for_channel(request.channel.as_deref())
+            .apply(&mut request.message_payload)
+    }
+
+    async fn record_processed(&self, payment: &Payment) -> Result<(), RepositoryError> {
+        self.duplicates.record(payment).await
     }
 }
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use jsonschema::JSONSchema;
+use serde::Deserialize;
+
+use crate::domain::payment::{PaymentFlag, PaymentRequest, PaymentType};
+use crate::error::ValidationError;
+use crate::infrastructure::database::entitlements::EntitlementRepository;
+use crate::validation::charset::CharsetPolicies;
+use crate::validation::duplicate::DuplicateDetector;
+use crate::validation::entitlements::EntitlementRule;
+use crate::validation::rules::{
+    CharsetRule, CustomRule, CustomRuleSettings, DuplicateCheckRule, EmbargoRule, EmbargoSettings, IdentifierRule,
+    LimitsRule, LimitsSettings, SchemaRule, SchemeRule, SchemeSettings,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleStage {
+    #[default]
+    Syntax,
+    Business,
+}
+
+#[async_trait]
+pub trait ValidationRule: Send + Sync {
+    fn name(&self) -> &str;
+    fn stage(&self) -> RuleStage;
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>>;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum RuleKind {
+    Schema,
+    Charset,
+    Identifier,
+    Limits(LimitsSettings),
+    Scheme(SchemeSettings),
+    Custom(CustomRuleSettings),
+    Duplicate,
+    Embargo(EmbargoSettings),
+    Entitlement,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleConfig {
+    #[serde(flatten)]
+    pub kind: RuleKind,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub exclude_channels: Vec<String>,
+    #[serde(default)]
+    pub payment_types: Vec<PaymentType>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl RuleConfig {
+    pub fn unscoped(kind: RuleKind) -> Self {
+        Self {
+            kind,
+            enabled: true,
+            channels: Vec::new(),
+            exclude_channels: Vec::new(),
+            payment_types: Vec::new(),
+        }
+    }
+
+    // Empty lists mean "everywhere"; a payment without a channel only
+    // matches rules that are not restricted to specific channels.
+    fn applies_to(&self, request: &PaymentRequest) -> bool {
+        let channel = request.channel.as_deref();
+        let channel_allowed = self.channels.is_empty()
+            || channel.is_some_and(|channel| self.channels.iter().any(|c| c == channel));
+        let channel_excluded = channel.is_some_and(|channel| self.exclude_channels.iter().any(|c| c == channel));
+        let type_allowed = self.payment_types.is_empty() || self.payment_types.contains(&request.payment_type);
+
+        self.enabled && channel_allowed && !channel_excluded && type_allowed
+    }
+}
+
+pub fn default_rules() -> Vec<RuleConfig> {
+    vec![
+        RuleConfig::unscoped(RuleKind::Schema),
+        RuleConfig::unscoped(RuleKind::Charset),
+        RuleConfig::unscoped(RuleKind::Identifier),
+        RuleConfig::unscoped(RuleKind::Duplicate),
+    ]
+}
+
+struct ConfiguredRule {
+    config: RuleConfig,
+    rule: Box<dyn ValidationRule>,
+}
+
+pub struct ValidationPipeline {
+    rules: Vec<ConfiguredRule>,
+}
+
+impl ValidationPipeline {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn with_rule(mut self, config: RuleConfig, rule: Box<dyn ValidationRule>) -> Self {
+        self.rules.push(ConfiguredRule { config, rule });
+        self
+    }
+
+    pub fn from_config(
+        configs: &[RuleConfig],
+        schemas: Arc<HashMap<String, JSONSchema>>,
+        charset_policies: Arc<CharsetPolicies>,
+        duplicates: Arc<DuplicateDetector>,
+        entitlements: Arc<dyn EntitlementRepository>,
+    ) -> Result<Self, ValidationError> {
+        let mut pipeline = Self::new();
+        for config in configs {
+            let rule: Box<dyn ValidationRule> = match &config.kind {
+                RuleKind::Schema => Box::new(SchemaRule::new(schemas.clone())),
+                RuleKind::Charset => Box::new(CharsetRule::new(charset_policies.clone())),
+                RuleKind::Identifier => Box::new(IdentifierRule::new()),
+                RuleKind::Limits(settings) => Box::new(LimitsRule::new(settings.clone())),
+                RuleKind::Scheme(settings) => Box::new(SchemeRule::new(settings.clone())),
+                RuleKind::Custom(settings) => Box::new(CustomRule::new(settings.clone())?),
+                RuleKind::Duplicate => Box::new(DuplicateCheckRule::new(duplicates.clone())),
+                RuleKind::Embargo(settings) => Box::new(EmbargoRule::new(settings.clone())),
+                RuleKind::Entitlement => Box::new(EntitlementRule::new(entitlements.clone())),
+            };
+            pipeline = pipeline.with_rule(config.clone(), rule);
+        }
+        Ok(pipeline)
+    }
+
+    // Every applicable rule runs even after a failure so the caller sees the
+    // complete list of problems in one response.
+    pub async fn run(&self, stage: RuleStage, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, ValidationError> {
+        let mut flags = Vec::new();
+        let mut errors = Vec::new();
+
+        for configured in &self.rules {
+            if configured.rule.stage() != stage || !configured.config.applies_to(request) {
+                continue;
+            }
+            match configured.rule.evaluate(request).await {
+                Ok(rule_flags) => flags.extend(rule_flags),
+                Err(rule_errors) => errors.extend(rule_errors),
+            }
+        }
+
+        match ValidationError::from_all(errors) {
+            Some(error) => Err(error),
+            None => Ok(flags),
+        }
+    }
+}
+
+impl Default for ValidationPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::status_reason::ReasonCode;
+    use serde_json::json;
+
+    struct AlwaysFails(&'static str);
+
+    #[async_trait]
+    impl ValidationRule for AlwaysFails {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn stage(&self) -> RuleStage {
+            RuleStage::Syntax
+        }
+
+        async fn evaluate(&self, _request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+            Err(vec![ValidationError::RuleViolation {
+                rule: self.0.to_string(),
+                path: None,
+                reason_code: ReasonCode::NARR,
+                message: "failed".to_string(),
+            }])
+        }
+    }
+
+    fn request(channel: Option<&str>) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: json!({}),
+            sender_id: "sender".to_string(),
+            request_id: "request".to_string(),
+            channel: channel.map(str::to_string),
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn collects_failures_from_every_applicable_rule() {
+        let mut sepa_only = RuleConfig::unscoped(RuleKind::Custom(CustomRuleSettings::default()));
+        sepa_only.channels = vec!["sepa".to_string()];
+
+        let pipeline = ValidationPipeline::new()
+            .with_rule(RuleConfig::unscoped(RuleKind::Schema), Box::new(AlwaysFails("first")))
+            .with_rule(RuleConfig::unscoped(RuleKind::Identifier), Box::new(AlwaysFails("second")))
+            .with_rule(sepa_only, Box::new(AlwaysFails("sepa")));
+
+        match pipeline.run(RuleStage::Syntax, &request(None)).await {
+            Err(ValidationError::Multiple(errors)) => assert_eq!(errors.len(), 2),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match pipeline.run(RuleStage::Syntax, &request(Some("sepa"))).await {
+            Err(ValidationError::Multiple(errors)) => assert_eq!(errors.len(), 3),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_rule_configuration_from_yaml() {
+        let yaml = r#"
+- rule: schema
+- rule: scheme
+  scheme: sepa
+  channels: [sepa]
+- rule: custom
+  name: purpose-code
+  pointer: /CdtTrfTxInf/Purp/Cd
+  pattern: "^[A-Z]{4}$"
+  required: true
+  payment_types: [CreditTransfer]
+- rule: duplicate
+  enabled: false
+"#;
+        let rules: Vec<RuleConfig> = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(rules.len(), 4);
+        assert!(matches!(rules[1].kind, RuleKind::Scheme(_)));
+        assert_eq!(rules[2].payment_types, vec![PaymentType::CreditTransfer]);
+        assert!(!rules[3].enabled);
+    }
+}
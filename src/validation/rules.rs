@@ -0,0 +1,721 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use jsonschema::JSONSchema;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::domain::payment::{PaymentFlag, PaymentRequest};
+use crate::domain::payment_details::{locate_all, transactions, PaymentDetails, TransactionDetails};
+use crate::domain::status_reason::ReasonCode;
+use crate::domain::uetr;
+use crate::error::ValidationError;
+use crate::validation::charset::CharsetPolicies;
+use crate::validation::duplicate::DuplicateDetector;
+use crate::validation::pipeline::{RuleStage, ValidationRule};
+
+fn violation(rule: &str, path: Option<String>, reason_code: ReasonCode, message: impl Into<String>) -> ValidationError {
+    ValidationError::RuleViolation {
+        rule: rule.to_string(),
+        path,
+        reason_code,
+        message: message.into(),
+    }
+}
+
+fn collect(errors: Vec<ValidationError>) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+    if errors.is_empty() {
+        Ok(Vec::new())
+    } else {
+        Err(errors)
+    }
+}
+
+pub struct SchemaRule {
+    schemas: Arc<HashMap<String, JSONSchema>>,
+}
+
+impl SchemaRule {
+    pub fn new(schemas: Arc<HashMap<String, JSONSchema>>) -> Self {
+        Self { schemas }
+    }
+}
+
+#[async_trait]
+impl ValidationRule for SchemaRule {
+    fn name(&self) -> &str {
+        "schema"
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Syntax
+    }
+
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        let schema = self
+            .schemas
+            .get(&request.message_type)
+            .ok_or_else(|| vec![ValidationError::UnsupportedMessageType(request.message_type.clone())])?;
+
+        let result = schema.validate(&request.message_payload);
+        match result {
+            Ok(()) => Ok(Vec::new()),
+            Err(errors) => Err(errors
+                .map(|e| violation(self.name(), Some(e.instance_path.to_string()), ReasonCode::FF01, e.to_string()))
+                .collect()),
+        }
+    }
+}
+
+pub struct CharsetRule {
+    policies: Arc<CharsetPolicies>,
+}
+
+impl CharsetRule {
+    pub fn new(policies: Arc<CharsetPolicies>) -> Self {
+        Self { policies }
+    }
+}
+
+#[async_trait]
+impl ValidationRule for CharsetRule {
+    fn name(&self) -> &str {
+        "charset"
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Syntax
+    }
+
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        self.policies
+            .for_channel(request.channel.as_deref())
+            .check(&request.message_payload)
+            .map(|_| Vec::new())
+            .map_err(|e| vec![e])
+    }
+}
+
+pub struct IdentifierRule {
+    bic: Regex,
+}
+
+impl IdentifierRule {
+    pub fn new() -> Self {
+        Self {
+            bic: Regex::new(r"^[A-Z]{4}[A-Z]{2}[A-Z0-9]{2}([A-Z0-9]{3})?$").expect("valid BIC pattern"),
+        }
+    }
+}
+
+impl Default for IdentifierRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn is_valid_iban(iban: &str) -> bool {
+    let iban: String = iban.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+    if iban.len() < 15 || iban.len() > 34 || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    if !iban[..2].chars().all(|c| c.is_ascii_alphabetic()) || !iban[2..4].chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let rearranged = iban[4..].chars().chain(iban[..4].chars());
+    let mut remainder: u32 = 0;
+    for c in rearranged {
+        let value = c.to_digit(36).unwrap_or(0);
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+#[async_trait]
+impl ValidationRule for IdentifierRule {
+    fn name(&self) -> &str {
+        "identifier"
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Syntax
+    }
+
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        let payload = &request.message_payload;
+        let mut errors = Vec::new();
+
+        for (path, value) in locate_all(payload, "IBAN") {
+            if !value.as_str().is_some_and(is_valid_iban) {
+                errors.push(violation(self.name(), Some(path), ReasonCode::AC01, format!("{} is not a valid IBAN", value)));
+            }
+        }
+        for element in ["BICFI", "BIC", "AnyBIC"] {
+            for (path, value) in locate_all(payload, element) {
+                if !value.as_str().is_some_and(|bic| self.bic.is_match(bic)) {
+                    errors.push(violation(self.name(), Some(path), ReasonCode::RC01, format!("{} is not a valid BIC", value)));
+                }
+            }
+        }
+        for element in ["UETR", "OrgnlUETR"] {
+            for (path, value) in locate_all(payload, element) {
+                if let Err(e) = value.as_str().map_or_else(
+                    || Err(ValidationError::SchemaViolation(format!("{} is not a string", value))),
+                    uetr::parse,
+                ) {
+                    errors.push(violation(self.name(), Some(path), ReasonCode::CH16, e.to_string()));
+                }
+            }
+        }
+
+        collect(errors)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LimitsSettings {
+    #[serde(default)]
+    pub max_amount: HashMap<String, f64>,
+    #[serde(default)]
+    pub allowed_currencies: Vec<String>,
+}
+
+pub struct LimitsRule {
+    settings: LimitsSettings,
+}
+
+impl LimitsRule {
+    pub fn new(settings: LimitsSettings) -> Self {
+        Self { settings }
+    }
+}
+
+pub fn minor_units(currency: &str) -> u32 {
+    match currency {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX" | "VND" | "VUV"
+        | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+#[async_trait]
+impl ValidationRule for LimitsRule {
+    fn name(&self) -> &str {
+        "limits"
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Business
+    }
+
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        let details = PaymentDetails::from_payload(&request.message_payload);
+        let mut errors = Vec::new();
+
+        let amount = match details.amount {
+            Some(amount) => amount,
+            None => return Err(vec![violation(self.name(), None, ReasonCode::AM12, "payment amount is missing")]),
+        };
+        if amount <= 0.0 {
+            errors.push(violation(self.name(), None, ReasonCode::AM01, "payment amount must be positive"));
+        }
+
+        if let Some(currency) = details.currency.as_deref() {
+            if !self.settings.allowed_currencies.is_empty()
+                && !self.settings.allowed_currencies.iter().any(|c| c == currency)
+            {
+                errors.push(violation(self.name(), None, ReasonCode::AM03, format!("currency {} is not accepted", currency)));
+            }
+
+            let scale = 10f64.powi(minor_units(currency) as i32);
+            if ((amount * scale).round() - amount * scale).abs() > 1e-6 {
+                errors.push(violation(
+                    self.name(),
+                    None,
+                    ReasonCode::AM12,
+                    format!("{} allows at most {} decimal places", currency, minor_units(currency)),
+                ));
+            }
+
+            if let Some(max) = self.settings.max_amount.get(currency) {
+                if amount > *max {
+                    errors.push(violation(
+                        self.name(),
+                        None,
+                        ReasonCode::AM02,
+                        format!("amount {:.2} {} exceeds the maximum of {:.2}", amount, currency, max),
+                    ));
+                }
+            }
+        }
+
+        collect(errors)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheme {
+    Sepa,
+    CbprPlus,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SchemeSettings {
+    pub scheme: Scheme,
+}
+
+pub struct SchemeRule {
+    settings: SchemeSettings,
+}
+
+impl SchemeRule {
+    pub fn new(settings: SchemeSettings) -> Self {
+        Self { settings }
+    }
+
+    fn check_sepa(&self, payload: &Value, details: &PaymentDetails, errors: &mut Vec<ValidationError>) {
+        if details.currency.as_deref() != Some("EUR") {
+            errors.push(violation(self.name(), None, ReasonCode::AM03, "SEPA payments must be denominated in EUR"));
+        }
+        if details.amount.is_some_and(|amount| amount > 999_999_999.99) {
+            errors.push(violation(self.name(), None, ReasonCode::AM02, "SEPA amount must not exceed 999999999.99"));
+        }
+        for account in ["DbtrAcct", "CdtrAcct"] {
+            if locate_all(payload, account)
+                .iter()
+                .any(|(_, value)| value.get("Id").and_then(|id| id.get("IBAN")).is_none())
+            {
+                errors.push(violation(self.name(), None, ReasonCode::AC01, format!("{} must be identified by IBAN", account)));
+            }
+        }
+        for (path, value) in locate_all(payload, "Ustrd") {
+            if value.as_str().is_some_and(|text| text.chars().count() > 140) {
+                errors.push(violation(self.name(), Some(path), ReasonCode::CH16, "unstructured remittance exceeds 140 characters"));
+            }
+        }
+        for (path, value) in locate_all(payload, "EndToEndId") {
+            if value.as_str().is_some_and(|id| id.chars().count() > 35) {
+                errors.push(violation(self.name(), Some(path), ReasonCode::CH16, "EndToEndId exceeds 35 characters"));
+            }
+        }
+    }
+
+    fn check_cbpr_plus(&self, payload: &Value, details: &PaymentDetails, errors: &mut Vec<ValidationError>) {
+        if details.creditor_agent.is_none() {
+            errors.push(violation(self.name(), None, ReasonCode::CH21, "CBPR+ requires a creditor agent BIC"));
+        }
+        for (path, value) in locate_all(payload, "Nm") {
+            if value.as_str().is_some_and(|name| name.chars().count() > 140) {
+                errors.push(violation(self.name(), Some(path), ReasonCode::CH16, "party name exceeds 140 characters"));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ValidationRule for SchemeRule {
+    fn name(&self) -> &str {
+        match self.settings.scheme {
+            Scheme::Sepa => "scheme.sepa",
+            Scheme::CbprPlus => "scheme.cbpr_plus",
+        }
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Business
+    }
+
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        let payload = &request.message_payload;
+        let details = PaymentDetails::from_payload(payload);
+        let mut errors = Vec::new();
+
+        match self.settings.scheme {
+            Scheme::Sepa => self.check_sepa(payload, &details, &mut errors),
+            Scheme::CbprPlus => self.check_cbpr_plus(payload, &details, &mut errors),
+        }
+
+        collect(errors)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CustomRuleSettings {
+    pub name: String,
+    pub pointer: String,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub stage: RuleStage,
+    #[serde(default)]
+    pub reason_code: Option<ReasonCode>,
+}
+
+pub struct CustomRule {
+    settings: CustomRuleSettings,
+    pattern: Option<Regex>,
+}
+
+impl CustomRule {
+    pub fn new(settings: CustomRuleSettings) -> Result<Self, ValidationError> {
+        let pattern = settings
+            .pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| ValidationError::Configuration(format!("custom rule {}: {}", settings.name, e)))?;
+        Ok(Self { settings, pattern })
+    }
+
+    fn fail(&self, default_code: ReasonCode, default_message: String) -> ValidationError {
+        violation(
+            &self.settings.name,
+            Some(self.settings.pointer.clone()),
+            self.settings.reason_code.unwrap_or(default_code),
+            self.settings.message.clone().unwrap_or(default_message),
+        )
+    }
+}
+
+#[async_trait]
+impl ValidationRule for CustomRule {
+    fn name(&self) -> &str {
+        &self.settings.name
+    }
+
+    fn stage(&self) -> RuleStage {
+        self.settings.stage
+    }
+
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        let value = match request.message_payload.pointer(&self.settings.pointer) {
+            Some(value) => value,
+            None if self.settings.required => {
+                return Err(vec![self.fail(ReasonCode::CH21, format!("{} is required", self.settings.pointer))])
+            }
+            None => return Ok(Vec::new()),
+        };
+
+        let text = match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        let mut errors = Vec::new();
+        if let Some(pattern) = &self.pattern {
+            if !pattern.is_match(&text) {
+                errors.push(self.fail(ReasonCode::CH16, format!("{} does not match {}", self.settings.pointer, pattern)));
+            }
+        }
+        if let Some(max_length) = self.settings.max_length {
+            if text.chars().count() > max_length {
+                errors.push(self.fail(ReasonCode::CH16, format!("{} exceeds {} characters", self.settings.pointer, max_length)));
+            }
+        }
+        collect(errors)
+    }
+}
+
+// Every condition that is set must hold for an entry to apply; `country`
+// matches any party, account or agent on the payment, intermediaries included.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmbargoEntry {
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub debtor_country: Option<String>,
+    #[serde(default)]
+    pub creditor_country: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmbargoSettings {
+    #[serde(default)]
+    pub entries: Vec<EmbargoEntry>,
+}
+
+pub struct EmbargoRule {
+    settings: EmbargoSettings,
+}
+
+struct PartyCountry {
+    country: String,
+    path: String,
+}
+
+const DEBTOR_SIDE: &[&str] = &["Dbtr", "UltmtDbtr", "DbtrAcct", "DbtrAgt"];
+const CREDITOR_SIDE: &[&str] = &["Cdtr", "UltmtCdtr", "CdtrAcct", "CdtrAgt"];
+const INTERMEDIARIES: &[&str] = &["IntrmyAgt1", "IntrmyAgt2", "IntrmyAgt3"];
+
+impl EmbargoRule {
+    pub fn new(settings: EmbargoSettings) -> Self {
+        Self { settings }
+    }
+
+    // Countries come from the IBAN prefix, the BIC country code and postal or
+    // residence addresses, so each element can contribute several.
+    fn countries(transaction: &TransactionDetails, elements: &[&str]) -> Vec<PartyCountry> {
+        let mut countries = Vec::new();
+        for element in elements {
+            for (path, value) in transaction.locate_all(element) {
+                let (institution, prefix) = match value.get("FinInstnId") {
+                    Some(institution) => (institution, "/FinInstnId"),
+                    None => (value, ""),
+                };
+                let candidates = [
+                    ("/Id/IBAN".to_string(), value.pointer("/Id/IBAN").and_then(Value::as_str).and_then(|iban| iban.get(..2))),
+                    (format!("{}/BICFI", prefix), institution.get("BICFI").and_then(bic_country)),
+                    (format!("{}/BIC", prefix), institution.get("BIC").and_then(bic_country)),
+                    (format!("{}/PstlAdr/Ctry", prefix), institution.pointer("/PstlAdr/Ctry").and_then(Value::as_str)),
+                    ("/CtryOfRes".to_string(), value.get("CtryOfRes").and_then(Value::as_str)),
+                ];
+                for (source, country) in candidates {
+                    if let Some(country) = country.filter(|c| c.len() == 2 && c.chars().all(|c| c.is_ascii_alphabetic())) {
+                        countries.push(PartyCountry {
+                            country: country.to_uppercase(),
+                            path: format!("{}{}", path, source),
+                        });
+                    }
+                }
+            }
+        }
+        countries
+    }
+}
+
+fn bic_country(bic: &Value) -> Option<&str> {
+    bic.as_str().and_then(|bic| bic.get(4..6))
+}
+
+fn find_country<'a>(countries: &'a [PartyCountry], wanted: &Option<String>) -> Option<Option<&'a PartyCountry>> {
+    match wanted {
+        None => Some(None),
+        Some(wanted) => countries
+            .iter()
+            .find(|found| found.country.eq_ignore_ascii_case(wanted))
+            .map(Some),
+    }
+}
+
+#[async_trait]
+impl ValidationRule for EmbargoRule {
+    fn name(&self) -> &str {
+        "embargo"
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Business
+    }
+
+    // Each transaction is checked on its own, so a corridor only matches when
+    // its debtor and creditor countries meet in the same transaction.
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        for transaction in transactions(&request.message_payload) {
+            self.check_transaction(&transaction, &mut errors);
+        }
+        collect(errors)
+    }
+}
+
+impl EmbargoRule {
+    fn check_transaction(&self, transaction: &TransactionDetails, errors: &mut Vec<ValidationError>) {
+        let currency = &transaction.details.currency;
+        let debtor = Self::countries(transaction, DEBTOR_SIDE);
+        let creditor = Self::countries(transaction, CREDITOR_SIDE);
+        let all: Vec<PartyCountry> = [DEBTOR_SIDE, CREDITOR_SIDE, INTERMEDIARIES]
+            .iter()
+            .flat_map(|elements| Self::countries(transaction, elements))
+            .collect();
+
+        for entry in &self.settings.entries {
+            if entry.country.is_none()
+                && entry.debtor_country.is_none()
+                && entry.creditor_country.is_none()
+                && entry.currency.is_none()
+            {
+                continue;
+            }
+            let currency_matches = match (&entry.currency, currency) {
+                (None, _) => true,
+                (Some(wanted), Some(currency)) => wanted.eq_ignore_ascii_case(currency),
+                (Some(_), None) => false,
+            };
+            if !currency_matches {
+                continue;
+            }
+            let (Some(any), Some(from), Some(to)) = (
+                find_country(&all, &entry.country),
+                find_country(&debtor, &entry.debtor_country),
+                find_country(&creditor, &entry.creditor_country),
+            ) else {
+                continue;
+            };
+
+            let mut matched: Vec<String> = [any, from, to]
+                .into_iter()
+                .flatten()
+                .map(|found| format!("{} at {}", found.country, found.path))
+                .collect();
+            if let (Some(_), Some(currency)) = (&entry.currency, currency) {
+                matched.push(format!("currency {}", currency));
+            }
+            let path = any
+                .or(from)
+                .or(to)
+                .map(|found| found.path.clone())
+                .or_else(|| Some(transaction.path.clone()).filter(|path| !path.is_empty()));
+            errors.push(violation(
+                self.name(),
+                path,
+                ReasonCode::RR04,
+                format!("embargoed: {} ({})", entry.reason, matched.join(", ")),
+            ));
+        }
+    }
+}
+
+pub struct DuplicateCheckRule {
+    detector: Arc<DuplicateDetector>,
+}
+
+impl DuplicateCheckRule {
+    pub fn new(detector: Arc<DuplicateDetector>) -> Self {
+        Self { detector }
+    }
+}
+
+#[async_trait]
+impl ValidationRule for DuplicateCheckRule {
+    fn name(&self) -> &str {
+        "duplicate"
+    }
+
+    fn stage(&self) -> RuleStage {
+        RuleStage::Business
+    }
+
+    async fn evaluate(&self, request: &PaymentRequest) -> Result<Vec<PaymentFlag>, Vec<ValidationError>> {
+        self.detector.check(request).await.map_err(|e| vec![e])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_iban_checksum() {
+        assert!(is_valid_iban("DE89 3704 0044 0532 0130 00"));
+        assert!(is_valid_iban("GB82WEST12345698765432"));
+        assert!(!is_valid_iban("DE89370400440532013001"));
+        assert!(!is_valid_iban("NOTANIBAN"));
+    }
+
+    fn embargo_request(payload: Value) -> PaymentRequest {
+        PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: crate::domain::payment::PaymentType::CreditTransfer,
+            message_payload: payload,
+            sender_id: "sender".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_embargoed_corridors_and_intermediaries() {
+        let rule = EmbargoRule::new(EmbargoSettings {
+            entries: vec![
+                EmbargoEntry {
+                    country: Some("KP".to_string()),
+                    reason: "DPRK sanctions".to_string(),
+                    ..EmbargoEntry::default()
+                },
+                EmbargoEntry {
+                    debtor_country: Some("US".to_string()),
+                    creditor_country: Some("CU".to_string()),
+                    currency: Some("USD".to_string()),
+                    reason: "US-Cuba corridor".to_string(),
+                    ..EmbargoEntry::default()
+                },
+            ],
+        });
+        let payload = |currency: &str, intermediary_bic: &str| {
+            serde_json::json!({
+                "CdtTrfTxInf": {
+                    "IntrBkSttlmAmt": { "Ccy": currency, "Value": 100.0 },
+                    "Dbtr": { "PstlAdr": { "Ctry": "US" } },
+                    "IntrmyAgt1": { "FinInstnId": { "BICFI": intermediary_bic } },
+                    "CdtrAgt": { "FinInstnId": { "BICFI": "BNACCUHH" } }
+                }
+            })
+        };
+
+        assert!(rule.evaluate(&embargo_request(payload("EUR", "DEUTDEFF"))).await.is_ok());
+        let corridor = rule.evaluate(&embargo_request(payload("USD", "DEUTDEFF"))).await.unwrap_err();
+        assert_eq!(corridor.len(), 1);
+        let intermediary = rule.evaluate(&embargo_request(payload("EUR", "FTBDKPPY"))).await.unwrap_err();
+        assert_eq!(
+            intermediary[0].violations()[0].pointer.as_deref(),
+            Some("/CdtTrfTxInf/IntrmyAgt1/FinInstnId/BICFI")
+        );
+    }
+
+    #[tokio::test]
+    async fn pairs_corridor_countries_within_each_transaction() {
+        let rule = EmbargoRule::new(EmbargoSettings {
+            entries: vec![EmbargoEntry {
+                debtor_country: Some("US".to_string()),
+                creditor_country: Some("CU".to_string()),
+                currency: Some("USD".to_string()),
+                reason: "US-Cuba corridor".to_string(),
+                ..EmbargoEntry::default()
+            }],
+        });
+        let batch = |second_currency: &str, second_creditor: &str| {
+            serde_json::json!({
+                "PmtInf": {
+                    "Dbtr": { "PstlAdr": { "Ctry": "US" } },
+                    "CdtTrfTxInf": [
+                        {
+                            "Amt": { "InstdAmt": { "Ccy": "EUR", "Value": 10.0 } },
+                            "CdtrAgt": { "FinInstnId": { "BICFI": "BNACCUHH" } }
+                        },
+                        {
+                            "Amt": { "InstdAmt": { "Ccy": second_currency, "Value": 20.0 } },
+                            "CdtrAgt": { "FinInstnId": { "BICFI": second_creditor } }
+                        }
+                    ]
+                }
+            })
+        };
+
+        // USD and the Cuban creditor sit in different transactions.
+        assert!(rule.evaluate(&embargo_request(batch("USD", "DEUTDEFF"))).await.is_ok());
+        let errors = rule.evaluate(&embargo_request(batch("USD", "BNACCUHH"))).await.unwrap_err();
+        assert_eq!(
+            errors[0].violations()[0].pointer.as_deref(),
+            Some("/PmtInf/Dbtr/PstlAdr/Ctry")
+        );
+    }
+}
@@ -0,0 +1,118 @@
+// Runs against a local Kafka broker. Point TEST_KAFKA_BROKERS at it and run
+// `cargo test --test kafka_tests -- --ignored`.
+use iso20022_payment_processor::config::{MessagingBackend, MessagingSettings};
+use iso20022_payment_processor::error::MessagingError;
+use iso20022_payment_processor::infrastructure::messaging::kafka::{
+    ConsumedEvent, EventHandler, KafkaEventConsumer, KafkaPublisher, KafkaSettings,
+};
+use iso20022_payment_processor::infrastructure::messaging::retry::RetrySettings;
+use iso20022_payment_processor::infrastructure::messaging::MessagePublisher;
+use async_trait::async_trait;
+use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, TopicReplication};
+use rdkafka::client::DefaultClientContext;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+async fn settings() -> MessagingSettings {
+    let kafka = KafkaSettings {
+        brokers: std::env::var("TEST_KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string()),
+        topic: format!("payments-test-{}", Uuid::new_v4()),
+        group_id: format!("payments-test-{}", Uuid::new_v4()),
+        delivery_timeout_ms: 10_000,
+        ..KafkaSettings::default()
+    };
+
+    // Several partitions, so ordering has to come from the key.
+    let admin: AdminClient<DefaultClientContext> = kafka.producer_config().create().unwrap();
+    let topic = NewTopic::new(&kafka.topic, 3, TopicReplication::Fixed(1));
+    let created = admin.create_topics(&[topic], &AdminOptions::new()).await.expect("local Kafka is not reachable");
+    assert!(created.iter().all(Result::is_ok));
+
+    MessagingSettings {
+        backend: MessagingBackend::Kafka,
+        url: String::new(),
+        exchange: String::new(),
+        exchange_kind: String::new(),
+        routing_key_prefix: String::new(),
+        mandatory: true,
+        confirm_timeout_ms: 5_000,
+        reconnect_attempts: 5,
+        reconnect_initial_delay_ms: 100,
+        reconnect_max_delay_ms: 1_000,
+        inbound: Vec::new(),
+        retry: RetrySettings::default(),
+        kafka,
+    }
+}
+
+#[derive(Default)]
+struct RecordingHandler {
+    events: Mutex<Vec<ConsumedEvent>>,
+}
+
+#[async_trait]
+impl EventHandler for RecordingHandler {
+    async fn handle(&self, event: ConsumedEvent) -> Result<(), MessagingError> {
+        self.events.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a local Kafka broker"]
+async fn test_payment_events_are_keyed_and_read_back_in_order() {
+    let settings = settings().await;
+    let publisher = KafkaPublisher::new(&settings).unwrap();
+
+    let first = Uuid::new_v4();
+    let second = Uuid::new_v4();
+    let uetr = Uuid::new_v4();
+    let published = [
+        (first, "payment.held"),
+        (second, "payment.received"),
+        (first, "payment.received"),
+        (first, "payment.completed"),
+    ];
+    for (payment_id, routing_key) in published {
+        publisher
+            .publish_payment_event(routing_key, &payment_id, &uetr, json!({ "id": payment_id }))
+            .await
+            .unwrap();
+    }
+
+    let handler = Arc::new(RecordingHandler::default());
+    let consumer = KafkaEventConsumer::new(settings, handler.clone()).unwrap();
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while handler.events.lock().unwrap().len() < published.len() {
+            consumer.consume_one().await;
+        }
+    })
+    .await
+    .expect("events were not consumed in time");
+
+    let events = handler.events.lock().unwrap().clone();
+    let of = |payment_id: Uuid| -> Vec<ConsumedEvent> {
+        events
+            .iter()
+            .filter(|event| event.key == Some(payment_id.to_string()))
+            .cloned()
+            .collect()
+    };
+
+    let first_events = of(first);
+    let routing_keys: Vec<Option<String>> = first_events.iter().map(|event| event.routing_key.clone()).collect();
+    assert_eq!(
+        routing_keys,
+        vec![
+            Some("payment.held".to_string()),
+            Some("payment.received".to_string()),
+            Some("payment.completed".to_string()),
+        ]
+    );
+    assert!(first_events.iter().all(|event| event.partition == first_events[0].partition));
+    assert!(first_events.iter().all(|event| event.message_id.is_some()));
+    assert_eq!(first_events[0].payload["uetr"], json!(uetr));
+    assert_eq!(of(second).len(), 1);
+}
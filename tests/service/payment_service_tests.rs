@@ -1,11 +1,21 @@
 use async_trait::async_trait;
-use crate::service::payment_service::{PaymentService, PaymentServiceImpl};
-use crate::domain::payment::{PaymentRequest, PaymentResponse, PaymentStatus};
-use crate::error::ServiceError;
-use crate::validation::PaymentValidator;
-use crate::infrastructure::messaging::MessagePublisher;
-use uuid::Uuid;
+use iso20022_payment_processor::domain::event::PaymentEventKind;
+use iso20022_payment_processor::domain::lifecycle::{StatusChange, StatusTransition};
+use iso20022_payment_processor::domain::payment::{
+    ApprovalRecord, ApprovalRequest, ApprovalRequirement, HoldDecision, HoldDecisionRequest, HoldOutcome, Payment,
+    PaymentFlag, PaymentRequest, PaymentStatus, PaymentType, ReviewAction, TransliterationRecord,
+};
+use iso20022_payment_processor::error::{RepositoryError, ServiceError, ValidationError};
+use iso20022_payment_processor::infrastructure::database::events::{EventStore, InMemoryEventStore};
+use iso20022_payment_processor::infrastructure::database::outbox::OutboxMessage;
+use iso20022_payment_processor::infrastructure::database::PaymentRepository;
+use iso20022_payment_processor::screening::{ScreeningSettings, WatchListScreener};
+use iso20022_payment_processor::service::payment_service::{PaymentService, PaymentServiceImpl};
+use iso20022_payment_processor::validation::charset::{CharacterSet, CharsetAction, CharsetPolicy};
+use iso20022_payment_processor::validation::PaymentValidator;
 use serde_json::json;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
 
 struct MockPaymentValidator;
 
@@ -15,25 +25,43 @@ impl PaymentValidator for MockPaymentValidator {
         Ok(())
     }
 
-    async fn validate_business_rules(&self, _request: &PaymentRequest) -> Result<(), ValidationError> {
-        Ok(())
+    async fn validate_business_rules(&self, _request: &PaymentRequest) -> Result<Vec<PaymentFlag>, ValidationError> {
+        Ok(Vec::new())
     }
 }
 
-struct MockMessagePublisher;
+// Normalises like a channel configured to transliterate to SEPA Latin.
+struct TransliteratingValidator;
 
 #[async_trait]
-impl MessagePublisher for MockMessagePublisher {
-    async fn publish_message(&self, _routing_key: &str, _message: serde_json::Value) -> Result<(), MessagingError> {
+impl PaymentValidator for TransliteratingValidator {
+    async fn validate(&self, _request: &PaymentRequest) -> Result<(), ValidationError> {
         Ok(())
     }
+
+    async fn validate_business_rules(&self, _request: &PaymentRequest) -> Result<Vec<PaymentFlag>, ValidationError> {
+        Ok(Vec::new())
+    }
+
+    async fn normalize(&self, request: &mut PaymentRequest) -> Result<Vec<TransliterationRecord>, ValidationError> {
+        CharsetPolicy {
+            charset: CharacterSet::SepaBasicLatin,
+            action: CharsetAction::Transliterate,
+        }
+        .apply(&mut request.message_payload)
+    }
 }
 
 struct MockPaymentRepository;
 
 #[async_trait]
 impl PaymentRepository for MockPaymentRepository {
-    async fn save_payment(&self, _payment: Payment) -> Result<(), RepositoryError> {
+    async fn save_payment(
+        &self,
+        _payment: Payment,
+        _outbox: Vec<OutboxMessage>,
+        _events: Vec<PaymentEventKind>,
+    ) -> Result<(), RepositoryError> {
         Ok(())
     }
 
@@ -41,21 +69,203 @@ impl PaymentRepository for MockPaymentRepository {
         Ok(None)
     }
 
-    async fn update_status(&self, _id: &Uuid, _status: PaymentStatus) -> Result<(), RepositoryError> {
+    async fn get_payment_by_uetr(&self, _uetr: &Uuid) -> Result<Option<Payment>, RepositoryError> {
+        Ok(None)
+    }
+
+    async fn find_by_request(&self, _sender_id: &str, _request_id: &str) -> Result<Option<Payment>, RepositoryError> {
+        Ok(None)
+    }
+
+    async fn update_status(&self, _id: &Uuid, _change: StatusChange) -> Result<(), RepositoryError> {
         Ok(())
     }
+
+    async fn status_history(&self, _id: &Uuid) -> Result<Vec<StatusTransition>, RepositoryError> {
+        Ok(Vec::new())
+    }
+
+    async fn list_by_status(&self, _status: PaymentStatus) -> Result<Vec<Payment>, RepositoryError> {
+        Ok(Vec::new())
+    }
+
+    async fn record_hold_decision(
+        &self,
+        _id: &Uuid,
+        _decision: HoldDecision,
+        _status: PaymentStatus,
+        _outbox: Vec<OutboxMessage>,
+        _events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError> {
+        Ok(true)
+    }
+
+    async fn record_approval(
+        &self,
+        _id: &Uuid,
+        _approval: ApprovalRecord,
+        _recorded: usize,
+        _status: PaymentStatus,
+        _outbox: Vec<OutboxMessage>,
+        _events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError> {
+        Ok(true)
+    }
+}
+
+struct SinglePaymentRepository {
+    payment: Mutex<Payment>,
+    // Shared so tests can inspect what was queued and recorded after handing
+    // the repository to the service.
+    outbox: Arc<Mutex<Vec<OutboxMessage>>>,
+    events: Arc<InMemoryEventStore>,
+}
+
+impl SinglePaymentRepository {
+    fn held() -> Self {
+        let request = PaymentRequest {
+            message_type: "pacs.008".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: json!({}),
+            sender_id: "sender".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: None,
+        };
+        let flag = PaymentFlag {
+            rule: "sanctions_screening".to_string(),
+            action: ReviewAction::Hold,
+            reason: "potential sanctions match".to_string(),
+            related_payment_id: None,
+        };
+        Self {
+            payment: Mutex::new(Payment::new(request, Uuid::new_v4(), Vec::new(), vec![flag])),
+            outbox: Arc::default(),
+            events: Arc::new(InMemoryEventStore::new()),
+        }
+    }
+
+    fn pending_approval(submitted_by: &str) -> Self {
+        let request = PaymentRequest {
+            message_type: "pain.001".to_string(),
+            payment_type: PaymentType::CreditTransfer,
+            message_payload: json!({}),
+            sender_id: "sender".to_string(),
+            request_id: "request".to_string(),
+            channel: None,
+            uetr: None,
+            submitted_by: Some(submitted_by.to_string()),
+        };
+        let mut payment = Payment::new(request, Uuid::new_v4(), Vec::new(), Vec::new());
+        payment.status = PaymentStatus::PendingApproval;
+        payment.approval = Some(ApprovalRequirement {
+            reason: "amount above approval threshold".to_string(),
+            required_approvals: 1,
+        });
+        Self {
+            payment: Mutex::new(payment),
+            outbox: Arc::default(),
+            events: Arc::new(InMemoryEventStore::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentRepository for SinglePaymentRepository {
+    async fn save_payment(
+        &self,
+        _payment: Payment,
+        _outbox: Vec<OutboxMessage>,
+        _events: Vec<PaymentEventKind>,
+    ) -> Result<(), RepositoryError> {
+        Ok(())
+    }
+
+    async fn get_payment(&self, _id: &Uuid) -> Result<Option<Payment>, RepositoryError> {
+        Ok(Some(self.payment.lock().unwrap().clone()))
+    }
+
+    async fn get_payment_by_uetr(&self, _uetr: &Uuid) -> Result<Option<Payment>, RepositoryError> {
+        Ok(None)
+    }
+
+    async fn find_by_request(&self, _sender_id: &str, _request_id: &str) -> Result<Option<Payment>, RepositoryError> {
+        Ok(None)
+    }
+
+    async fn update_status(&self, _id: &Uuid, change: StatusChange) -> Result<(), RepositoryError> {
+        self.payment.lock().unwrap().status = change.status;
+        Ok(())
+    }
+
+    async fn status_history(&self, _id: &Uuid) -> Result<Vec<StatusTransition>, RepositoryError> {
+        Ok(Vec::new())
+    }
+
+    async fn list_by_status(&self, status: PaymentStatus) -> Result<Vec<Payment>, RepositoryError> {
+        let payment = self.payment.lock().unwrap().clone();
+        Ok(if payment.status == status { vec![payment] } else { Vec::new() })
+    }
+
+    async fn record_hold_decision(
+        &self,
+        _id: &Uuid,
+        decision: HoldDecision,
+        status: PaymentStatus,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError> {
+        let (id, decided_at) = {
+            let mut payment = self.payment.lock().unwrap();
+            if payment.status != PaymentStatus::Held {
+                return Ok(false);
+            }
+            payment.status = status;
+            payment.hold_decision = Some(decision.clone());
+            self.outbox.lock().unwrap().extend(outbox);
+            (payment.id, decision.decided_at)
+        };
+        self.events.append(&id, decided_at, events).await?;
+        Ok(true)
+    }
+
+    async fn record_approval(
+        &self,
+        _id: &Uuid,
+        approval: ApprovalRecord,
+        recorded: usize,
+        status: PaymentStatus,
+        outbox: Vec<OutboxMessage>,
+        events: Vec<PaymentEventKind>,
+    ) -> Result<bool, RepositoryError> {
+        let (id, decided_at) = {
+            let mut payment = self.payment.lock().unwrap();
+            if payment.status != PaymentStatus::PendingApproval
+                || payment.approvals.len() != recorded
+                || payment
+                    .approvals
+                    .iter()
+                    .any(|record| record.approver.eq_ignore_ascii_case(&approval.approver))
+            {
+                return Ok(false);
+            }
+            payment.status = status;
+            let decided_at = approval.decided_at;
+            payment.approvals.push(approval);
+            self.outbox.lock().unwrap().extend(outbox);
+            (payment.id, decided_at)
+        };
+        self.events.append(&id, decided_at, events).await?;
+        Ok(true)
+    }
 }
 
 #[tokio::test]
 async fn test_process_payment() {
     let validator = Box::new(MockPaymentValidator);
-    let message_publisher = Box::new(MockMessagePublisher);
     let repository = Box::new(MockPaymentRepository);
-    let service = PaymentServiceImpl {
-        validator,
-        message_publisher,
-        repository,
-    };
+    let service = PaymentServiceImpl::new(validator, repository);
 
     let request = PaymentRequest {
         message_type: "pain.001".to_string(),
@@ -63,24 +273,153 @@ async fn test_process_payment() {
         message_payload: json!({}),
         sender_id: "sender".to_string(),
         request_id: "request".to_string(),
+        channel: None,
+        uetr: None,
+        submitted_by: None,
     };
 
     let result = service.process_payment(request).await;
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_transliterated_names_are_screened_as_submitted() {
+    let screener = WatchListScreener::new(
+        ScreeningSettings {
+            enabled: true,
+            ..ScreeningSettings::default()
+        },
+        Vec::new(),
+    );
+    let service = PaymentServiceImpl::new(Box::new(TransliteratingValidator), Box::new(MockPaymentRepository))
+        .with_screener(Box::new(screener));
+
+    let request = PaymentRequest {
+        message_type: "pain.001".to_string(),
+        payment_type: PaymentType::CreditTransfer,
+        message_payload: json!({
+            "CdtTrfTxInf": {
+                "Dbtr": { "Nm": "محمد علي" },
+                "Cdtr": { "Nm": "Jane Doe" }
+            }
+        }),
+        sender_id: "sender".to_string(),
+        request_id: "request".to_string(),
+        channel: Some("sepa".to_string()),
+        uetr: None,
+        submitted_by: None,
+    };
+
+    let response = service.process_payment(request).await.unwrap();
+    assert_eq!(response.status, PaymentStatus::Held);
+    assert!(response
+        .flags
+        .iter()
+        .any(|flag| flag.rule == "sanctions_screening" && flag.reason.contains("debtor")));
+}
+
 #[tokio::test]
 async fn test_get_status() {
     let validator = Box::new(MockPaymentValidator);
-    let message_publisher = Box::new(MockMessagePublisher);
-    let repository = Box::new(MockPaymentRepository);
-    let service = PaymentServiceImpl {
-        validator,
-        message_publisher,
-        repository,
-    };
+    let repository = SinglePaymentRepository::held();
+    let payment_id = repository.payment.lock().unwrap().id;
+    let service = PaymentServiceImpl::new(validator, Box::new(repository));
 
-    let payment_id = Uuid::new_v4();
     let result = service.get_status(&payment_id).await;
-    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), PaymentStatus::Held);
+}
+
+#[tokio::test]
+async fn test_payment_timeline() {
+    let repository = SinglePaymentRepository::held();
+    let outbox = repository.outbox.clone();
+    let events = repository.events.clone();
+    let service = PaymentServiceImpl::new(Box::new(MockPaymentValidator), Box::new(repository)).with_events(events);
+
+    let payment_id = service.list_held().await.unwrap()[0].payment_id;
+    service
+        .release_payment(&payment_id, "analyst-7", decision("false positive"))
+        .await
+        .unwrap();
+
+    let timeline = service.get_events(&payment_id).await.unwrap();
+    assert_eq!(timeline.status, PaymentStatus::Received);
+    let kinds: Vec<&PaymentEventKind> = timeline.events.iter().map(|event| &event.kind).collect();
+    assert!(matches!(kinds[0], PaymentEventKind::HoldDecided { .. }));
+    assert!(matches!(
+        kinds[1],
+        PaymentEventKind::StatusChanged { from: Some(PaymentStatus::Held), to: PaymentStatus::Received, .. }
+    ));
+    assert_eq!(timeline.events.iter().map(|event| event.sequence).collect::<Vec<_>>(), vec![1, 2]);
+
+    // Publishing is left to the outbox relay, which records Published itself.
+    let queued = outbox.lock().unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].routing_key, "payment.received");
+    assert_eq!(queued[0].payload["status"], json!("Received"));
+}
+
+fn decision(reason: &str) -> HoldDecisionRequest {
+    HoldDecisionRequest {
+        reason: reason.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_release_held_payment() {
+    let service = PaymentServiceImpl::new(
+        Box::new(MockPaymentValidator),
+        Box::new(SinglePaymentRepository::held()),
+    );
+
+    let held = service.list_held().await.unwrap();
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].reasons[0].rule, "sanctions_screening");
+
+    let response = service
+        .release_payment(&held[0].payment_id, "analyst-7", decision("false positive, different date of birth"))
+        .await
+        .unwrap();
+    assert_eq!(response.status, PaymentStatus::Received);
+    let hold_decision = response.hold_decision.unwrap();
+    assert_eq!(hold_decision.outcome, HoldOutcome::Released);
+    assert_eq!(hold_decision.decided_by, "analyst-7");
+    assert!(service.list_held().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_reject_requires_reason_and_held_status() {
+    let service = PaymentServiceImpl::new(
+        Box::new(MockPaymentValidator),
+        Box::new(SinglePaymentRepository::held()),
+    );
+    let payment_id = service.list_held().await.unwrap()[0].payment_id;
+
+    let missing_reason = service.reject_payment(&payment_id, "analyst-7", decision(" ")).await;
+    assert!(matches!(missing_reason, Err(ServiceError::Validation(_))));
+
+    let rejected = service.reject_payment(&payment_id, "analyst-7", decision("confirmed match")).await.unwrap();
+    assert_eq!(rejected.status, PaymentStatus::Rejected);
+
+    let again = service.release_payment(&payment_id, "analyst-7", decision("changed my mind")).await;
+    assert!(matches!(again, Err(ServiceError::InvalidState { .. })));
+}
+
+#[tokio::test]
+async fn test_four_eyes_refuses_submitter_and_records_approver() {
+    let service = PaymentServiceImpl::new(
+        Box::new(MockPaymentValidator),
+        Box::new(SinglePaymentRepository::pending_approval("alice")),
+    );
+    let payment_id = service.list_pending_approval().await.unwrap()[0].payment_id;
+
+    let own = service.approve_payment(&payment_id, "Alice", ApprovalRequest::default()).await;
+    assert!(matches!(own, Err(ServiceError::BusinessRule(_))));
+
+    let approved = service.approve_payment(&payment_id, "bob", ApprovalRequest::default()).await.unwrap();
+    assert_eq!(approved.status, PaymentStatus::Received);
+
+    let trail = service.get_approvals(&payment_id).await.unwrap();
+    assert_eq!(trail.len(), 1);
+    assert_eq!(trail[0].approver, "bob");
 }
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
-use crate::validation::payment_validator::{PaymentValidator, ISO20022PaymentValidator};
-use crate::domain::payment::PaymentRequest;
-use crate::error::ValidationError;
+use iso20022_payment_processor::domain::payment::{PaymentFlag, PaymentRequest, PaymentType};
+use iso20022_payment_processor::error::ValidationError;
+use iso20022_payment_processor::validation::PaymentValidator;
 use serde_json::json;
 
 struct MockISO20022PaymentValidator;
@@ -12,8 +12,8 @@ impl PaymentValidator for MockISO20022PaymentValidator {
         Ok(())
     }
 
-    async fn validate_business_rules(&self, _request: &PaymentRequest) -> Result<(), ValidationError> {
-        Ok(())
+    async fn validate_business_rules(&self, _request: &PaymentRequest) -> Result<Vec<PaymentFlag>, ValidationError> {
+        Ok(Vec::new())
     }
 }
 
@@ -26,6 +26,9 @@ async fn test_validate() {
         message_payload: json!({}),
         sender_id: "sender".to_string(),
         request_id: "request".to_string(),
+        channel: None,
+        uetr: None,
+        submitted_by: None,
     };
 
     let result = validator.validate(&request).await;
@@ -41,6 +44,9 @@ async fn test_validate_business_rules() {
         message_payload: json!({}),
         sender_id: "sender".to_string(),
         request_id: "request".to_string(),
+        channel: None,
+        uetr: None,
+        submitted_by: None,
     };
 
     let result = validator.validate_business_rules(&request).await;